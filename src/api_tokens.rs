@@ -0,0 +1,32 @@
+use crate::{
+    colors::{CYAN, RED},
+    server::auth::API_LOG,
+    DownloadManager,
+};
+use chrono::Local;
+
+//moves the server's per-token request log into the ui log
+pub fn check_api_log(interface: &mut DownloadManager) {
+    let requests = match API_LOG.try_lock() {
+        Ok(mut locked) => std::mem::take(&mut *locked),
+        Err(_) => return,
+    };
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    for request in requests {
+        let token = request.token_name.as_deref().unwrap_or("unknown token");
+        let (text, color) = if request.allowed {
+            (format!("Api: {} by {}", request.action, token), *CYAN)
+        } else {
+            (
+                format!("Api: denied {} for {}", request.action, token),
+                *RED,
+            )
+        };
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, color));
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use crate::dl::disk::is_portable;
+use std::io::Error;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::w,
+    Win32::{
+        Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS},
+        System::Registry::{RegDeleteKeyValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ},
+    },
+};
+
+//the command the session runs on login, always straight into the tray
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn launch_command() -> Result<String, Error> {
+    let exe = std::env::current_exe()?;
+    let mut command = format!("\"{}\" --hidden", exe.to_string_lossy());
+    //a portable marker file is found again on its own, only the flag has to be passed on
+    if is_portable() && std::env::args().any(|arg| arg == "--portable") {
+        command.push_str(" --portable");
+    }
+    Ok(command)
+}
+
+//a value under the user's Run key
+#[cfg(target_os = "windows")]
+pub fn set_autostart(enabled: bool) -> Result<(), Error> {
+    let key = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
+    let name = w!("Rusty Dl Manager");
+    let status = if enabled {
+        let command = launch_command()?
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect::<Vec<u16>>();
+        unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                key,
+                name,
+                REG_SZ.0,
+                Some(command.as_ptr() as _),
+                (command.len() * 2) as u32,
+            )
+        }
+    } else {
+        match unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, key, name) } {
+            ERROR_FILE_NOT_FOUND => ERROR_SUCCESS,
+            status => status,
+        }
+    };
+    if status != ERROR_SUCCESS {
+        return Err(Error::from_raw_os_error(status.0 as i32));
+    }
+    Ok(())
+}
+
+//a desktop entry in the xdg autostart folder
+#[cfg(target_os = "linux")]
+pub fn set_autostart(enabled: bool) -> Result<(), Error> {
+    let Some(dir) = dirs_next::config_dir() else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "no config folder"));
+    };
+    let dir = dir.join("autostart");
+    let path = dir.join("rusty-dl-manager.desktop");
+    if !enabled {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=Rusty Dl Manager\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        launch_command()?
+    );
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(path, entry)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_autostart(_enabled: bool) -> Result<(), Error> {
+    Err(Error::new(
+        std::io::ErrorKind::Unsupported,
+        "starting on login isn't supported on this platform",
+    ))
+}
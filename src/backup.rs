@@ -0,0 +1,127 @@
+use crate::{
+    content_index::{ContentIndex, INDEX_FILE},
+    dl::{
+        disk::set_write_options,
+        file2dl::File2Dl,
+        limiter::set_global_limit,
+        net::{set_net_config, NetConfig},
+        storage::data_file,
+        timeouts::set_global_timeouts,
+    },
+    download_list::{files_from_entries, list_entries, ListEntry},
+    server::auth::{set_api_tokens, set_server_secret},
+    DownloadManager, FDl, Settings,
+};
+use std::{
+    fs::{read, write, File},
+    io::{Read, Write},
+    path::Path,
+    sync::atomic::Ordering::Relaxed,
+};
+use zip::{write::SimpleFileOptions, ZipArchive, ZipWriter};
+
+const SETTINGS_ENTRY: &str = "settings.json";
+const DOWNLOADS_ENTRY: &str = "downloads.json";
+const INDEX_ENTRY: &str = "content_index.json";
+
+pub struct Backup {
+    pub settings: Settings,
+    pub files: Vec<File2Dl>,
+    pub index: Option<Vec<u8>>,
+}
+
+//settings (rules and tokens included), the download list and the content index in a single zip
+pub fn export_backup(interface: &DownloadManager, path: &Path) -> Result<(), std::io::Error> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default();
+    zip.start_file(SETTINGS_ENTRY, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&interface.settings)?)?;
+    zip.start_file(DOWNLOADS_ENTRY, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&list_entries(&interface.files))?)?;
+    if data_file(INDEX_FILE).exists() {
+        zip.start_file(INDEX_ENTRY, options)?;
+        zip.write_all(&read(data_file(INDEX_FILE))?)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+pub fn read_backup(path: &Path) -> Result<Backup, std::io::Error> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    let settings: Settings = serde_json::from_slice(&read_entry(&mut zip, SETTINGS_ENTRY)?)?;
+    let entries: Vec<ListEntry> = serde_json::from_slice(&read_entry(&mut zip, DOWNLOADS_ENTRY)?)?;
+    let files = files_from_entries(entries, &settings.dl_dir)?;
+    let index = read_entry(&mut zip, INDEX_ENTRY).ok();
+    Ok(Backup {
+        settings,
+        files,
+        index,
+    })
+}
+
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, std::io::Error> {
+    let mut entry = zip.by_name(name)?;
+    let mut buf = Vec::new();
+    entry.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+//settings are replaced outright while downloads are merged like an imported list, returns (added, skipped)
+pub fn restore_backup(
+    interface: &mut DownloadManager,
+    mut backup: Backup,
+) -> Result<(usize, usize), std::io::Error> {
+    //the extension and the cli hold the secret in use, the backup's one is only taken through import_server_secret
+    backup.settings.server_secret = interface.settings.server_secret.clone();
+    backup.settings.save()?;
+    set_api_tokens(&backup.settings.api_tokens);
+    set_global_timeouts(backup.settings.timeouts);
+    set_write_options(backup.settings.write_options);
+    set_global_limit(backup.settings.global_limit, backup.settings.limit_split);
+    {
+        let _guard = interface.runtime.enter();
+        set_net_config(NetConfig::new(
+            &backup.settings.dns,
+            backup.settings.ip_preference,
+            backup.settings.http_version,
+            backup.settings.pool_size,
+            &backup.settings.proxy,
+        )?);
+    }
+    interface.popups.settings = backup.settings.popup();
+    interface.settings = backup.settings;
+    if let Some(index) = backup.index {
+        write(data_file(INDEX_FILE), index)?;
+        interface.content_index = ContentIndex::default();
+    }
+    let mut added = 0;
+    let mut skipped = 0;
+    for file in backup.files {
+        let duplicate = interface
+            .files
+            .iter()
+            .any(|f| f.file.url.link == file.url.link || f.file.same_file(&file));
+        if duplicate {
+            skipped += 1;
+            continue;
+        }
+        let complete = file.complete.load(Relaxed);
+        interface.files.push(FDl {
+            file,
+            initial_status: complete,
+            ..Default::default()
+        });
+        added += 1;
+    }
+    Ok((added, skipped))
+}
+
+pub fn import_server_secret(
+    interface: &mut DownloadManager,
+    secret: String,
+) -> Result<(), std::io::Error> {
+    interface.settings.server_secret = secret;
+    interface.settings.save()?;
+    set_server_secret(&interface.settings.server_secret);
+    Ok(())
+}
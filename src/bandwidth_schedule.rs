@@ -0,0 +1,55 @@
+use crate::{
+    colors::CYAN,
+    dl::{
+        eta::format_size,
+        limiter::{global_limit, set_global_limit},
+    },
+    i18n::{tr, trf},
+    speed_rules::hour_in_window,
+    DownloadManager,
+};
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BandwidthSlot {
+    //bytes per sec, 0 means full speed
+    pub limit: usize,
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl BandwidthSlot {
+    pub fn is_active(&self, hour: u32) -> bool {
+        hour_in_window(self.start_hour, self.end_hour, hour)
+    }
+}
+
+//the first matching slot wins, outside every slot the cap from the settings applies
+pub fn scheduled_limit(schedule: &[BandwidthSlot], fallback: usize, hour: u32) -> usize {
+    schedule
+        .iter()
+        .find(|slot| slot.is_active(hour))
+        .map_or(fallback, |slot| slot.limit)
+}
+
+pub fn apply_bandwidth_schedule(interface: &mut DownloadManager) {
+    let hour = Local::now().hour();
+    let settings = &interface.settings;
+    let limit = scheduled_limit(&settings.bandwidth_schedule, settings.global_limit, hour);
+    let (current, split) = global_limit();
+    if (current, split) == (limit, settings.limit_split) {
+        return;
+    }
+    set_global_limit(limit, settings.limit_split);
+    //only the split changed
+    if current == limit {
+        return;
+    }
+    let text = if limit == 0 {
+        tr("Bandwidth limit lifted").to_string()
+    } else {
+        trf("Bandwidth limited to {}/s", &[&format_size(limit)])
+    };
+    interface.toasts.push(text, *CYAN);
+}
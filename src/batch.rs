@@ -0,0 +1,217 @@
+use crate::{
+    colors::{CYAN, RED},
+    content_index::{file_path, index_file},
+    dl::{
+        disk::normalize_dir,
+        file2dl::{generate_name_on_disk, FileKey},
+        metadata::remove_metadata,
+    },
+    verification::verify_file,
+    DownloadManager,
+};
+use chrono::Local;
+use native_dialog::FileDialog;
+use std::{
+    fs::{copy, remove_file, rename},
+    path::Path,
+    sync::atomic::Ordering::Relaxed,
+    time::Instant,
+};
+
+//what the menu bar and the row menu can do to every selected row at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionAction {
+    Pause,
+    Resume,
+    SetSpeed,
+    SetTags,
+    MoveTo,
+    Verify,
+}
+
+impl SelectionAction {
+    pub const ALL: [Self; 6] = [
+        Self::Pause,
+        Self::Resume,
+        Self::SetSpeed,
+        Self::SetTags,
+        Self::MoveTo,
+        Self::Verify,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Pause => "Pause selected",
+            Self::Resume => "Resume selected",
+            Self::SetSpeed => "Set speed limit for selected",
+            Self::SetTags => "Set tags for selected",
+            Self::MoveTo => "Move selected to folder",
+            Self::Verify => "Verify selected",
+        }
+    }
+}
+
+//queued rows are taken out of the queue too, or they'd start as soon as the rest stopped
+pub fn pause_all(interface: &mut DownloadManager) {
+    for fdl in interface.files.iter_mut() {
+        fdl.file.running.store(false, Relaxed);
+        fdl.file.queued.store(false, Relaxed);
+    }
+}
+
+pub fn resume_all(interface: &mut DownloadManager) {
+    for fdl in interface.files.iter_mut() {
+        //cancelled rows are only brought back through their restart button
+        if fdl.file.cancelled.load(Relaxed) {
+            continue;
+        }
+        fdl.file.running.store(true, Relaxed);
+    }
+}
+
+pub fn apply_to_selected(interface: &mut DownloadManager, action: SelectionAction) {
+    match action {
+        SelectionAction::Pause => {
+            for fdl in interface.files.iter_mut().filter(|f| f.selected) {
+                fdl.file.running.store(false, Relaxed);
+                fdl.file.queued.store(false, Relaxed);
+            }
+        }
+        SelectionAction::Resume => {
+            for fdl in interface.files.iter_mut().filter(|f| f.selected) {
+                let file = &fdl.file;
+                //cancelled rows are only brought back through their restart button
+                if file.complete.load(Relaxed) || file.cancelled.load(Relaxed) {
+                    continue;
+                }
+                file.running.store(true, Relaxed);
+                fdl.toggled_at = Instant::now();
+            }
+        }
+        SelectionAction::SetSpeed => interface.popups.speed.show = true,
+        SelectionAction::SetTags => {
+            interface.popups.tags.key = FileKey::default();
+            interface.popups.tags.text = String::default();
+            interface.popups.tags.show = true;
+        }
+        SelectionAction::MoveTo => {
+            let path = FileDialog::new()
+                .set_location(&interface.settings.dl_dir)
+                .show_open_single_dir();
+            if let Ok(Some(path)) = path {
+                move_selected(interface, &path.to_string_lossy());
+            }
+        }
+        SelectionAction::Verify => {
+            let keys = interface
+                .files
+                .iter()
+                .filter(|f| f.selected && f.file.complete.load(Relaxed))
+                .map(|f| f.file.key())
+                .collect::<Vec<_>>();
+            for key in keys {
+                verify_file(interface, &key);
+            }
+        }
+    }
+}
+
+//a rename when the folder is on the same drive, a copy otherwise
+fn move_file(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy(from, to)?;
+    remove_file(from)
+}
+
+fn move_selected(interface: &mut DownloadManager, dir: &str) {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    let dir = normalize_dir(dir);
+    let mut moved = Vec::new();
+    let mut any_moved = false;
+    for fdl in interface.files.iter_mut().filter(|f| f.selected) {
+        let task_alive = fdl.task_alive();
+        if fdl.file.dl_dir == dir {
+            continue;
+        }
+        if task_alive || fdl.file.running.load(Relaxed) {
+            let text = format!("Pause {} before moving it", &fdl.file.name_on_disk);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+            continue;
+        }
+        let from = Path::new(&fdl.file.dl_dir).join(&fdl.file.name_on_disk);
+        let result = generate_name_on_disk(&fdl.file.name_on_disk, &dir).and_then(|name| {
+            //nothing was written yet for downloads added paused
+            if from.exists() {
+                move_file(&from, &Path::new(&dir).join(&name))?;
+            }
+            remove_metadata(&fdl.file)?;
+            Ok(name)
+        });
+        let name_on_disk = match result {
+            Ok(name_on_disk) => name_on_disk,
+            Err(e) => {
+                let text = format!("Couldn't move {}: {}", &fdl.file.name_on_disk, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text.clone(), *RED));
+                interface.popups.error.value = text;
+                interface.popups.error.show = true;
+                continue;
+            }
+        };
+        interface
+            .content_index
+            .entries
+            .remove(&file_path(&fdl.file));
+        fdl.file.dl_dir = dir.clone();
+        fdl.file.name_on_disk = name_on_disk;
+        any_moved = true;
+        let (text, color) = match fdl.save_metadata() {
+            Ok(_) => (
+                format!("Moved {} to {}", &fdl.file.name_on_disk, &dir),
+                *CYAN,
+            ),
+            Err(e) => (
+                format!(
+                    "Couldn't save the new folder of {}: {}",
+                    &fdl.file.name_on_disk, e
+                ),
+                *RED,
+            ),
+        };
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, color));
+        if fdl.file.complete.load(Relaxed) {
+            moved.push(fdl.file.clone());
+        }
+    }
+    //only the download folder is read on startup, so others are remembered
+    if any_moved
+        && dir != interface.settings.dl_dir
+        && !interface.settings.moved_dirs.contains(&dir)
+    {
+        interface.settings.moved_dirs.push(dir);
+        if let Err(e) = interface.settings.save() {
+            let text = format!("Couldn't save the settings: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+        }
+    }
+    for file in moved {
+        index_file(interface, &file);
+    }
+}
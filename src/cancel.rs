@@ -0,0 +1,65 @@
+use crate::{
+    colors::{CYAN, RED},
+    dl::file2dl::FileKey,
+    DownloadManager, FDl,
+};
+use chrono::Local;
+use std::{fs::remove_file, io::ErrorKind, path::Path, sync::atomic::Ordering::Relaxed};
+
+//stops the transfer for good, the row stays in the list so it can be restarted later
+pub fn cancel_download(interface: &mut DownloadManager, key: &FileKey, keep_partial: bool) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(key)) else {
+        return;
+    };
+    let file = &fdl.file;
+    file.running.store(false, Relaxed);
+    file.cancelled.store(true, Relaxed);
+    file.bytes_per_sec.store(0, Relaxed);
+    file.smoothed_speed.store(0, Relaxed);
+    if let Some(cancel) = &fdl.cancel {
+        let _ = cancel.send(true);
+    }
+    fdl.discard_partial = !keep_partial;
+    let (text, color) = match fdl.save_metadata() {
+        Ok(_) if keep_partial => (
+            format!("Cancelled, kept partial file: {}", key.name_on_disk),
+            *CYAN,
+        ),
+        Ok(_) => (
+            format!("Cancelled, removing partial file: {}", key.name_on_disk),
+            *CYAN,
+        ),
+        Err(e) => (format!("Couldn't write to file: {:?}", e), *RED),
+    };
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, color));
+}
+
+//the partial file is only removed once the aborted transfer stopped writing to it
+pub fn remove_discarded(fdl: &mut FDl) -> Result<(), std::io::Error> {
+    if !fdl.discard_partial || fdl.task_alive() {
+        return Ok(());
+    }
+    fdl.discard_partial = false;
+    let path = Path::new(&fdl.file.dl_dir).join(&fdl.file.name_on_disk);
+    match remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    fdl.file.size_on_disk.store(0, Relaxed);
+    Ok(())
+}
+
+pub fn restart_cancelled(interface: &DownloadManager, key: &FileKey) -> Result<(), std::io::Error> {
+    let Some(fdl) = interface.files.iter().find(|f| f.file.is(key)) else {
+        return Ok(());
+    };
+    fdl.file.cancelled.store(false, Relaxed);
+    fdl.file.running.store(true, Relaxed);
+    fdl.save_metadata()
+}
@@ -0,0 +1,147 @@
+use crate::{
+    dl::{eta::format_size, limiter::parse_rate},
+    native_messaging::install_native_host,
+    server::control::DownloadSummary,
+    Settings,
+};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{header::CONTENT_TYPE, Client, RequestBuilder, Response};
+
+const USAGE: &str = "usage: gui add <url> | list | pause <id> | resume <id> | limit <id> <rate>
+       gui install-native-host [chrome extension id]
+  ids are the first column of list, rates look like 500K, 2M or 0 for unlimited
+  the server secret is read from the settings, RUSTY_DL_TOKEN sends an api token instead";
+
+//a subcommand talks to the running window and exits, None when the app should start normally
+pub fn run_cli() -> Option<i32> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let command = args.first()?;
+    let commands = [
+        "add",
+        "list",
+        "pause",
+        "resume",
+        "limit",
+        "install-native-host",
+        "help",
+    ];
+    if !commands.contains(&command.as_str()) {
+        return None;
+    }
+    //release builds on windows have no console of their own, the output goes to the one it was started from
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Some(1);
+        }
+    };
+    let code = match runtime.block_on(run(&args)) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    };
+    Some(code)
+}
+
+fn download_url(server: &str, id: &str, action: &str) -> String {
+    let id = utf8_percent_encode(id, NON_ALPHANUMERIC);
+    format!("{}/downloads/{}/{}", server, id, action)
+}
+
+async fn send(request: RequestBuilder, token: &str) -> Result<Response, String> {
+    let response = request
+        .header("x-api-token", token)
+        .send()
+        .await
+        .map_err(|_| String::from("Rusty Dl Manager isn't running"))?;
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    match response.text().await {
+        Ok(text) if !text.is_empty() => Err(text),
+        _ => Err(format!("The server answered {}", status)),
+    }
+}
+
+async fn print_rows(response: Response) -> Result<(), String> {
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    let rows = serde_json::from_str::<Vec<DownloadSummary>>(&text).map_err(|e| e.to_string())?;
+    for row in rows {
+        let progress = match row.size {
+            0 => String::from("?"),
+            size => format!("{}%", row.downloaded * 100 / size),
+        };
+        let limit = match row.limit {
+            0 => String::from("unlimited"),
+            limit => format!("{}/s", format_size(limit)),
+        };
+        println!(
+            "{}  {:<12}{:>6}{:>12}/s  limit {:<12}{}",
+            row.id,
+            row.status,
+            progress,
+            format_size(row.bytes_per_sec),
+            limit,
+            row.name
+        );
+    }
+    Ok(())
+}
+
+async fn run(args: &[String]) -> Result<(), String> {
+    let client = Client::new();
+    let (server, secret) = Settings::local_server();
+    let token = std::env::var("RUSTY_DL_TOKEN").unwrap_or(secret);
+    let arg = |idx: usize| args.get(idx).map(String::as_str).ok_or(USAGE);
+    match args[0].as_str() {
+        "list" => {
+            let request = client.get(format!("{}/downloads", server));
+            print_rows(send(request, &token).await?).await
+        }
+        "add" => {
+            let body = serde_json::json!({ "url": arg(1)? }).to_string();
+            let request = client
+                .post(format!("{}/downloads", server))
+                .header(CONTENT_TYPE, "application/json")
+                .body(body);
+            print_rows(send(request, &token).await?).await
+        }
+        "pause" | "resume" => {
+            let request = client.post(download_url(&server, arg(1)?, &args[0]));
+            print_rows(send(request, &token).await?).await
+        }
+        "limit" => {
+            let rate = parse_rate(arg(2)?)?;
+            let url = download_url(&server, arg(1)?, "limit");
+            let request = client.post(url).query(&[("bytes_per_sec", rate)]);
+            print_rows(send(request, &token).await?).await
+        }
+        "install-native-host" => {
+            let chrome_extension = args.get(1).map(String::as_str);
+            let written = install_native_host(chrome_extension).map_err(|e| e.to_string())?;
+            for path in written {
+                println!("Wrote {}", path.display());
+            }
+            if chrome_extension.is_none() {
+                println!("Pass the extension's id from chrome://extensions to set up Chrome too");
+            }
+            Ok(())
+        }
+        _ => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{colors::RED, tray::Message, DownloadManager, FDl};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::atomic::Ordering::Relaxed,
+    time::{Duration, Instant},
+};
+
+//what the window's close button does
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CloseAction {
+    #[default]
+    Ask,
+    Tray,
+    Exit,
+}
+
+impl CloseAction {
+    pub const ALL: [Self; 3] = [Self::Ask, Self::Tray, Self::Exit];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Ask => "Ask every time",
+            Self::Tray => "Close to tray",
+            Self::Exit => "Exit application",
+        }
+    }
+}
+
+pub fn close_requested(interface: &mut DownloadManager) {
+    match interface.settings.close_action {
+        CloseAction::Ask => {
+            interface.popups.close.remember = false;
+            interface.popups.close.show = true;
+        }
+        CloseAction::Tray => hide_window(interface),
+        CloseAction::Exit => quit(interface),
+    }
+}
+
+//downloads keep going, the tray brings the window back
+pub fn hide_window(interface: &mut DownloadManager) {
+    interface.popups.download.show = false;
+    interface.popups.duplicate.show = false;
+    interface.popups.cancel.show = false;
+    interface.popups.tags.show = false;
+    interface.popups.note.show = false;
+    interface.popups.update_url.show = false;
+    interface.popups.columns.show = false;
+    interface.popups.theme_editor.show = false;
+    interface.popups.scan_warning.show = false;
+    interface.popups.confirm.show = false;
+    interface.popups.error.show = false;
+    interface.popups.plot.show = false;
+    interface.popups.speed.show = false;
+    interface.popups.rules.show = false;
+    interface.popups.schedule.show = false;
+    interface.popups.tokens.show = false;
+    interface.popups.history.show = false;
+    interface.popups.statistics.show = false;
+    interface.popups.close.show = false;
+    //a download from the browser stays in its popup until the window is back
+    if let Some(tray) = interface.tray_menu.as_mut() {
+        tray.message = Message::None;
+    }
+    interface.show_window = false;
+    if let Err(e) = interface.settings.save() {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!("Couldn't save the window size: {}", e);
+        interface.popups.log.logs.push((formatted_time, text, *RED));
+    }
+}
+
+//stops every transfer and writes everything down before the process goes away
+pub fn quit(interface: &mut DownloadManager) -> ! {
+    for fdl in interface.files.iter() {
+        fdl.file.running.store(false, Relaxed);
+        if let Some(cancel) = &fdl.cancel {
+            let _ = cancel.send(true);
+        }
+    }
+    //the transfers get a moment to write out what they hold before the files are let go
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while Instant::now() < deadline && interface.files.iter().any(FDl::task_alive) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    //there's nowhere left to report a failure
+    for fdl in interface.files.iter() {
+        fdl.save_metadata().unwrap_or_default();
+    }
+    interface.settings.save().unwrap_or_default();
+    std::process::exit(0)
+}
@@ -1,12 +1,61 @@
 use egui_sfml::egui::Color32;
-use lazy_static::lazy_static;
+use std::{ops::Deref, sync::RwLock};
 
-lazy_static! {
-    pub static ref CYAN: Color32 = Color32::from_hex("#a4b9ef").expect("Bad Hex");
-    pub static ref PURPLE: Color32 = Color32::from_hex("#1b1824").expect("Bad Hex");
-    pub static ref DARKER_PURPLE: Color32 = Color32::from_hex("#111017").expect("Bad Hex");
-    pub static ref DARK_INNER: Color32 = Color32::from_hex("#1e1e28").expect("Bad Hex");
-    pub static ref GRAY: Color32 = Color32::from_hex("#808080").expect("Bad Hex");
-    pub static ref GREEN: Color32 = Color32::from_hex("#b1e4ac").expect("Bad Hex");
-    pub static ref RED: Color32 = Color32::from_hex("#dc8d8a").expect("Bad Hex");
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub cyan: Color32,
+    pub purple: Color32,
+    pub darker_purple: Color32,
+    pub dark_inner: Color32,
+    pub gray: Color32,
+    pub green: Color32,
+    pub red: Color32,
 }
+
+//the scheme the app has always shipped with
+pub static PURPLE_PALETTE: Palette = Palette {
+    cyan: Color32::from_rgb(0xa4, 0xb9, 0xef),
+    purple: Color32::from_rgb(0x1b, 0x18, 0x24),
+    darker_purple: Color32::from_rgb(0x11, 0x10, 0x17),
+    dark_inner: Color32::from_rgb(0x1e, 0x1e, 0x28),
+    gray: Color32::from_rgb(0x80, 0x80, 0x80),
+    green: Color32::from_rgb(0xb1, 0xe4, 0xac),
+    red: Color32::from_rgb(0xdc, 0x8d, 0x8a),
+};
+
+impl Default for Palette {
+    fn default() -> Self {
+        PURPLE_PALETTE
+    }
+}
+
+static CURRENT: RwLock<&'static Palette> = RwLock::new(&PURPLE_PALETTE);
+
+pub fn set_palette(palette: &'static Palette) {
+    if let Ok(mut locked) = CURRENT.write() {
+        *locked = palette;
+    }
+}
+
+pub fn palette() -> &'static Palette {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+//one color of whichever palette is current, read on every use so switching themes shows up on the next frame
+pub struct Swatch(fn(&Palette) -> &Color32);
+
+impl Deref for Swatch {
+    type Target = Color32;
+
+    fn deref(&self) -> &Color32 {
+        (self.0)(palette())
+    }
+}
+
+pub static CYAN: Swatch = Swatch(|p| &p.cyan);
+pub static PURPLE: Swatch = Swatch(|p| &p.purple);
+pub static DARKER_PURPLE: Swatch = Swatch(|p| &p.darker_purple);
+pub static DARK_INNER: Swatch = Swatch(|p| &p.dark_inner);
+pub static GRAY: Swatch = Swatch(|p| &p.gray);
+pub static GREEN: Swatch = Swatch(|p| &p.green);
+pub static RED: Swatch = Swatch(|p| &p.red);
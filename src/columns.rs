@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColumnKind {
+    Filename,
+    Url,
+    Progress,
+    Speed,
+    Eta,
+    Remaining,
+    Added,
+    OnSave,
+    Status,
+}
+
+impl ColumnKind {
+    pub const ALL: [Self; 9] = [
+        Self::Filename,
+        Self::Url,
+        Self::Progress,
+        Self::Speed,
+        Self::Eta,
+        Self::Remaining,
+        Self::Added,
+        Self::OnSave,
+        Self::Status,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Filename => "Filename",
+            Self::Url => "Url",
+            Self::Progress => "Progress",
+            Self::Speed => "Speed",
+            Self::Eta => "Time left",
+            Self::Remaining => "Remaining",
+            Self::Added => "Added",
+            Self::OnSave => "On save",
+            Self::Status => "Status",
+        }
+    }
+
+    fn default_width(&self) -> f32 {
+        match self {
+            Self::Filename => 0.1855,
+            Self::Url => 0.2,
+            Self::Progress => 0.255,
+            Self::Speed => 0.15,
+            Self::Eta => 0.1,
+            Self::Remaining => 0.1,
+            Self::Added => 0.12,
+            Self::OnSave => 0.17,
+            Self::Status => 0.1995,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TableColumn {
+    pub kind: ColumnKind,
+    pub visible: bool,
+    //share of the table's width so the layout survives a different window size
+    pub width: f32,
+}
+
+impl TableColumn {
+    fn new(kind: ColumnKind) -> Self {
+        Self {
+            kind,
+            visible: !matches!(
+                kind,
+                ColumnKind::Url | ColumnKind::Eta | ColumnKind::Remaining | ColumnKind::Added
+            ),
+            width: kind.default_width(),
+        }
+    }
+}
+
+pub fn default_columns() -> Vec<TableColumn> {
+    ColumnKind::ALL.into_iter().map(TableColumn::new).collect()
+}
+
+//drops repeated columns from a hand edited config and appends the ones newer versions added
+pub fn normalize_columns(columns: &mut Vec<TableColumn>) {
+    let mut seen = Vec::new();
+    columns.retain(|column| {
+        let first = !seen.contains(&column.kind);
+        seen.push(column.kind);
+        first
+    });
+    for kind in ColumnKind::ALL {
+        if !seen.contains(&kind) {
+            columns.push(TableColumn::new(kind));
+        }
+    }
+    for column in columns.iter_mut() {
+        column.width = column.width.clamp(0.03, 1.0);
+    }
+    //the name is what the row's menus hang off, so it can't be hidden
+    if let Some(column) = columns.iter_mut().find(|c| c.kind == ColumnKind::Filename) {
+        column.visible = true;
+    }
+}
+
+pub fn visible_columns(columns: &[TableColumn]) -> Vec<TableColumn> {
+    columns.iter().filter(|c| c.visible).copied().collect()
+}
+
+//takes the widths the table was drawn with after the user dragged a column edge,
+//true when anything changed and the settings need writing
+pub fn store_widths(columns: &mut [TableColumn], widths: &[f32], total: f32) -> bool {
+    if total <= 0.0 {
+        return false;
+    }
+    let mut changed = false;
+    for (column, width) in columns.iter_mut().filter(|c| c.visible).zip(widths) {
+        let share = (width / total).clamp(0.03, 1.0);
+        if (share - column.width).abs() > 0.002 {
+            column.width = share;
+            changed = true;
+        }
+    }
+    changed
+}
+
+pub fn move_column(columns: &mut [TableColumn], idx: usize, up: bool) {
+    match up {
+        true if idx > 0 => columns.swap(idx, idx - 1),
+        false if idx + 1 < columns.len() => columns.swap(idx, idx + 1),
+        _ => {}
+    }
+}
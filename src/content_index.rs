@@ -0,0 +1,206 @@
+use crate::{
+    colors::RED,
+    dl::{file2dl::File2Dl, storage::data_file},
+    DownloadManager,
+};
+use chrono::Local;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+    process::Command,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+pub const INDEX_FILE: &str = "content_index.json";
+const ZIP_EXTS: [&str; 7] = [".zip", ".jar", ".apk", ".aar", ".war", ".whl", ".xpi"];
+const MEDIA_EXTS: [&str; 16] = [
+    ".mp4", ".mkv", ".webm", ".avi", ".mov", ".m4v", ".flv", ".wmv", ".mpg", ".mpeg", ".mp3",
+    ".flac", ".ogg", ".m4a", ".wav", ".opus",
+];
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub members: Vec<String>,
+    pub media: Option<String>,
+}
+
+type Indexed = (String, Result<IndexEntry, String>);
+
+pub struct ContentIndex {
+    pub entries: HashMap<String, IndexEntry>,
+    pub channel: (Sender<Indexed>, Receiver<Indexed>),
+}
+
+impl Default for ContentIndex {
+    fn default() -> Self {
+        //entries for files that were deleted since the last run are dropped
+        let mut entries = Self::parse().unwrap_or_default();
+        entries.retain(|path, _| Path::new(path).exists());
+        Self {
+            entries,
+            channel: channel(),
+        }
+    }
+}
+
+impl ContentIndex {
+    fn parse() -> Result<HashMap<String, IndexEntry>, std::io::Error> {
+        let mut buffer = String::new();
+        File::open(data_file(INDEX_FILE))?.read_to_string(&mut buffer)?;
+        Ok(serde_json::from_str(&buffer)?)
+    }
+
+    fn save(&self) -> Result<(), std::io::Error> {
+        let mut file = File::create(data_file(INDEX_FILE))?;
+        file.write_all(serde_json::to_string(&self.entries)?.as_bytes())
+    }
+
+    //"contains:<name>" looks inside archives, anything else matches the media summary
+    pub fn matches(&self, path: &str, query: &str) -> bool {
+        let Some(entry) = self.entries.get(path) else {
+            return false;
+        };
+        match query.strip_prefix("contains:") {
+            Some(member) => {
+                let member = member.trim();
+                !member.is_empty()
+                    && entry
+                        .members
+                        .iter()
+                        .any(|m| m.to_lowercase().contains(member))
+            }
+            None => entry
+                .media
+                .as_ref()
+                .is_some_and(|m| m.to_lowercase().contains(query)),
+        }
+    }
+}
+
+pub fn file_path(file: &File2Dl) -> String {
+    Path::new(&file.dl_dir)
+        .join(&file.name_on_disk)
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn is_indexable(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    ZIP_EXTS
+        .iter()
+        .chain(MEDIA_EXTS.iter())
+        .chain([".tar", ".tar.gz", ".tgz"].iter())
+        .any(|ext| name.ends_with(ext))
+}
+
+pub fn index_file(interface: &mut DownloadManager, file: &File2Dl) {
+    let path = file_path(file);
+    if !is_indexable(&file.name_on_disk) || interface.content_index.entries.contains_key(&path) {
+        return;
+    }
+    let tx = interface.content_index.channel.0.clone();
+    interface.runtime.spawn_blocking(move || {
+        let result = build_entry(Path::new(&path)).map_err(|e| e.to_string());
+        tx.send((path, result)).unwrap();
+    });
+}
+
+pub fn check_content_index(interface: &mut DownloadManager) {
+    let mut changed = false;
+    while let Ok((path, result)) = interface.content_index.channel.1.try_recv() {
+        match result {
+            Ok(entry) => {
+                interface.content_index.entries.insert(path, entry);
+                changed = true;
+            }
+            Err(e) => {
+                let now = Local::now();
+                let formatted_time = now.format("%H:%M:%S").to_string();
+                let text = format!("Couldn't index {}: {}", path, e);
+                interface.popups.log.logs.push((formatted_time, text, *RED));
+            }
+        }
+    }
+    if changed {
+        if let Err(e) = interface.content_index.save() {
+            let now = Local::now();
+            let formatted_time = now.format("%H:%M:%S").to_string();
+            let text = format!("Couldn't write to file: {:?}", e);
+            interface.popups.log.logs.push((formatted_time, text, *RED));
+        }
+    }
+}
+
+fn build_entry(path: &Path) -> Result<IndexEntry, std::io::Error> {
+    let name = path.to_string_lossy().to_lowercase();
+    let mut entry = IndexEntry::default();
+    if ZIP_EXTS.iter().any(|ext| name.ends_with(ext)) {
+        let archive = zip::ZipArchive::new(File::open(path)?)?;
+        entry.members = archive.file_names().map(String::from).collect();
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        entry.members = tar_members(GzDecoder::new(File::open(path)?))?;
+    } else if name.ends_with(".tar") {
+        entry.members = tar_members(File::open(path)?)?;
+    } else {
+        entry.media = probe_media(path);
+    }
+    Ok(entry)
+}
+
+fn tar_members<R: Read>(reader: R) -> Result<Vec<String>, std::io::Error> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for member in archive.entries()? {
+        let member = member?;
+        members.push(member.path()?.to_string_lossy().to_string());
+    }
+    Ok(members)
+}
+
+//relies on ffprobe being on the PATH, media files are simply left without a summary otherwise
+fn probe_media(path: &Path) -> Option<String> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration:stream=width,height",
+            "-of",
+            "json",
+        ])
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let probe: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let duration = probe["format"]["duration"]
+        .as_str()
+        .and_then(|d| d.parse::<f64>().ok())
+        .map(|d| d as u64);
+    let resolution = probe["streams"].as_array().and_then(|streams| {
+        streams.iter().find_map(|s| {
+            let width = s["width"].as_u64()?;
+            let height = s["height"].as_u64()?;
+            Some(format!("{}x{}", width, height))
+        })
+    });
+    let mut summary = Vec::new();
+    if let Some(secs) = duration {
+        summary.push(format!(
+            "{:02}:{:02}:{:02}",
+            secs / 3600,
+            (secs % 3600) / 60,
+            secs % 60
+        ));
+    }
+    if let Some(resolution) = resolution {
+        summary.push(resolution);
+    }
+    (!summary.is_empty()).then(|| summary.join(" "))
+}
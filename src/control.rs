@@ -0,0 +1,336 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    content_index::file_path,
+    dl::{file2dl::File2Dl, metadata::remove_metadata, store::Outcome, tls::TlsOptions},
+    download_mechanism::{exceeds_size_limit, SizeGuard},
+    extern_windows::set_speed,
+    history::record_history,
+    server::{
+        control::{
+            download_id, Command, ControlError, ControlReply, DownloadSummary, CONTROL_STATE,
+        },
+        events::{events, DownloadEvent},
+    },
+    DownloadManager, FDl,
+};
+use chrono::Local;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::remove_file,
+    io::ErrorKind,
+    path::Path,
+    sync::{
+        atomic::Ordering::Relaxed,
+        mpsc::{channel, Receiver, Sender},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+
+type Reply = oneshot::Sender<ControlReply>;
+type Probed = (Result<File2Dl, String>, Reply);
+
+//downloads added from the command line or the api, probed off the ui thread
+pub struct Control {
+    channel: (Sender<Probed>, Receiver<Probed>),
+    //rows waiting for their transfer to stop before they're removed, and whether the file goes too
+    removals: Vec<(String, bool, Reply)>,
+    //what the websockets were last told about each row, and when
+    sent: HashMap<String, (DownloadSummary, Instant)>,
+    subscribers: usize,
+}
+
+impl Default for Control {
+    fn default() -> Self {
+        Self {
+            channel: channel(),
+            removals: Vec::new(),
+            sent: HashMap::new(),
+            subscribers: 0,
+        }
+    }
+}
+
+fn status(fdl: &FDl) -> &'static str {
+    let file = &fdl.file;
+    if file.complete.load(Relaxed) {
+        "complete"
+    } else if file.cancelled.load(Relaxed) {
+        "cancelled"
+    } else if fdl.has_error || file.failed.load(Relaxed) {
+        "failed"
+    } else if file.running.load(Relaxed) {
+        "downloading"
+    } else if file.queued.load(Relaxed) {
+        "queued"
+    } else {
+        "paused"
+    }
+}
+
+fn row_id(fdl: &FDl) -> String {
+    download_id(&fdl.file.dl_dir, &fdl.file.name_on_disk)
+}
+
+fn summary(fdl: &FDl) -> DownloadSummary {
+    let file = &fdl.file;
+    DownloadSummary {
+        id: row_id(fdl),
+        name: file.name_on_disk.clone(),
+        link: file.url.link.clone(),
+        dir: file.dl_dir.clone(),
+        status: status(fdl).to_string(),
+        size: file.url.content_length,
+        downloaded: file.size_on_disk.load(Relaxed),
+        bytes_per_sec: file.bytes_per_sec.load(Relaxed),
+        limit: fdl.speed_before_rule.unwrap_or(file.speed.load(Relaxed)),
+    }
+}
+
+//tells the connected websockets what changed, nothing is built while none are
+fn publish_events(interface: &mut DownloadManager) {
+    let sender = events();
+    let subscribers = sender.receiver_count();
+    //someone new connected, everyone is sent the whole list again
+    if subscribers > interface.control.subscribers {
+        interface.control.sent.clear();
+    }
+    interface.control.subscribers = subscribers;
+    if subscribers == 0 {
+        return;
+    }
+    let sent = &mut interface.control.sent;
+    let mut present = HashSet::new();
+    for fdl in interface.files.iter() {
+        let current = summary(fdl);
+        present.insert(current.id.clone());
+        let event = match sent.get(&current.id) {
+            None => DownloadEvent::State(current.clone()),
+            Some((last, _)) if last.status != current.status => {
+                DownloadEvent::State(current.clone())
+            }
+            Some((last, at))
+                if at.elapsed() >= Duration::from_secs(1)
+                    && (last.downloaded != current.downloaded
+                        || last.bytes_per_sec != current.bytes_per_sec
+                        || last.limit != current.limit) =>
+            {
+                DownloadEvent::Progress(current.clone())
+            }
+            _ => continue,
+        };
+        sent.insert(current.id.clone(), (current, Instant::now()));
+        let _ = sender.send(event);
+    }
+    sent.retain(|id, _| {
+        let kept = present.contains(id);
+        if !kept {
+            let _ = sender.send(DownloadEvent::Removed { id: id.clone() });
+        }
+        kept
+    });
+}
+
+pub fn check_control(interface: &mut DownloadManager) {
+    publish_events(interface);
+    while let Ok((probed, reply)) = interface.control.channel.1.try_recv() {
+        //the caller may have timed out already
+        let _ = reply.send(push_probed(interface, probed));
+    }
+    let removals = std::mem::take(&mut interface.control.removals);
+    for (id, delete_file, reply) in removals {
+        let task_alive = interface
+            .files
+            .iter()
+            .find(|f| row_id(f) == id)
+            .is_some_and(FDl::task_alive);
+        if task_alive {
+            interface.control.removals.push((id, delete_file, reply));
+            continue;
+        }
+        let _ = reply.send(remove(interface, &id, delete_file));
+    }
+    let requests = match CONTROL_STATE.try_lock() {
+        Ok(mut locked) => std::mem::take(&mut *locked),
+        Err(_) => return,
+    };
+    for request in requests {
+        match request.command {
+            Command::Add(link) => add(interface, link, request.reply),
+            Command::Remove(id, delete_file) => {
+                stop_for_removal(interface, id, delete_file, request.reply)
+            }
+            command => {
+                let _ = request.reply.send(run_command(interface, command));
+            }
+        }
+    }
+}
+
+fn push_probed(interface: &mut DownloadManager, probed: Result<File2Dl, String>) -> ControlReply {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    let mut file = match probed {
+        Ok(file) => file,
+        Err(e) => {
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, e.clone(), *RED));
+            interface.popups.log.has_error = true;
+            return Err(ControlError::Rejected(e));
+        }
+    };
+    if exceeds_size_limit(interface.settings.max_size_gb, file.url.content_length) {
+        let text = format!(
+            "{} is over the {} GB limit",
+            &file.url.link, interface.settings.max_size_gb
+        );
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text.clone(), *RED));
+        if interface.settings.size_guard == SizeGuard::Block {
+            return Err(ControlError::Rejected(text));
+        }
+    }
+    let text = format!("Added through the api: {}", &file.url.link);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *GREEN));
+    interface.claim_name(&mut file);
+    file.toggle_status();
+    let fdl = FDl {
+        file,
+        ..Default::default()
+    };
+    let rows = vec![summary(&fdl)];
+    interface.files.push(fdl);
+    Ok(rows)
+}
+
+fn run_command(interface: &mut DownloadManager, command: Command) -> ControlReply {
+    match command {
+        Command::List => Ok(interface.files.iter().map(summary).collect()),
+        Command::Pause(id) => with_row(interface, &id, |fdl| {
+            fdl.file.running.store(false, Relaxed);
+            fdl.file.queued.store(false, Relaxed);
+            Ok(format!("Paused {} through the api", &fdl.file.name_on_disk))
+        }),
+        Command::Resume(id) => with_row(interface, &id, |fdl| {
+            let name = &fdl.file.name_on_disk;
+            //cancelled rows are only brought back through their restart button
+            if fdl.file.complete.load(Relaxed) || fdl.file.cancelled.load(Relaxed) {
+                let text = format!("{} can't be resumed, it's {}", name, status(fdl));
+                return Err(ControlError::Rejected(text));
+            }
+            fdl.file.running.store(true, Relaxed);
+            Ok(format!("Resumed {} through the api", name))
+        }),
+        Command::Limit(id, speed) => with_row(interface, &id, |fdl| {
+            set_speed(fdl, speed)
+                .map_err(|e| ControlError::Failed(format!("Couldn't write to file: {}", e)))?;
+            let name = &fdl.file.name_on_disk;
+            Ok(format!("Set the speed limit of {} through the api", name))
+        }),
+        //answered on their own, once the probe or the transfer is done
+        Command::Add(_) | Command::Remove(..) => Ok(Vec::new()),
+    }
+}
+
+fn with_row(
+    interface: &mut DownloadManager,
+    id: &str,
+    action: impl FnOnce(&mut FDl) -> Result<String, ControlError>,
+) -> ControlReply {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    let Some(fdl) = interface.files.iter_mut().find(|f| row_id(f) == id) else {
+        return Err(ControlError::NotFound(format!(
+            "No download with id {}",
+            id
+        )));
+    };
+    let text = action(fdl)?;
+    let rows = vec![summary(fdl)];
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+    Ok(rows)
+}
+
+fn add(interface: &mut DownloadManager, link: String, reply: Reply) {
+    let dl_dir = interface.settings.dl_dir.clone();
+    let tls = TlsOptions {
+        ca_cert: interface.settings.ca_cert.clone(),
+        accept_invalid_certs: false,
+    };
+    let tx = interface.control.channel.0.clone();
+    interface.runtime.spawn(async move {
+        let probed = File2Dl::new(&link, &dl_dir, tls)
+            .await
+            .map_err(|e| format!("{}: {}", &link, e));
+        tx.send((probed, reply)).unwrap_or_default();
+    });
+}
+
+//the transfer is told to stop, the row goes once it let go of the file
+fn stop_for_removal(interface: &mut DownloadManager, id: String, delete_file: bool, reply: Reply) {
+    let Some(fdl) = interface.files.iter_mut().find(|f| row_id(f) == id) else {
+        let text = format!("No download with id {}", id);
+        let _ = reply.send(Err(ControlError::NotFound(text)));
+        return;
+    };
+    fdl.file.running.store(false, Relaxed);
+    fdl.file.queued.store(false, Relaxed);
+    if let Some(cancel) = &fdl.cancel {
+        let _ = cancel.send(true);
+    }
+    interface.control.removals.push((id, delete_file, reply));
+}
+
+fn remove(interface: &mut DownloadManager, id: &str, delete_file: bool) -> ControlReply {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    let Some(idx) = interface.files.iter().position(|f| row_id(f) == id) else {
+        return Err(ControlError::NotFound(format!(
+            "No download with id {}",
+            id
+        )));
+    };
+    let fdl = &interface.files[idx];
+    let name = fdl.file.name_on_disk.clone();
+    if delete_file {
+        let path = Path::new(&fdl.file.dl_dir).join(&fdl.file.name_on_disk);
+        match remove_file(&path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                let text = format!("Couldn't delete {}: {}", path.display(), e);
+                return Err(ControlError::Failed(text));
+            }
+            _ => record_history(&mut interface.popups.log, fdl, Outcome::Deleted),
+        }
+    }
+    remove_metadata(&fdl.file).map_err(|e| {
+        ControlError::Failed(format!("Couldn't remove the record of {}: {}", name, e))
+    })?;
+    let rows = vec![summary(fdl)];
+    interface
+        .content_index
+        .entries
+        .remove(&file_path(&fdl.file));
+    interface.files.remove(idx);
+    let text = if delete_file {
+        format!("Deleted {} through the api", name)
+    } else {
+        format!("Removed {} from the list through the api", name)
+    };
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+    Ok(rows)
+}
@@ -0,0 +1,270 @@
+use crate::{
+    colors::{CYAN, DARKER_PURPLE, DARK_INNER, GRAY, GREEN, RED},
+    dl::{
+        eta::{format_eta, format_size},
+        file2dl::FileKey,
+    },
+    table::{edit_tags, tag_chips},
+    DownloadManager,
+};
+use chrono::{DateTime, Local};
+use egui_aesthetix::{themes::TokyoNight, Aesthetix};
+use egui_sfml::egui::{
+    Button, CollapsingHeader, Context, Frame, Grid, Id, Label, ProgressBar, RichText, ScrollArea,
+    SidePanel, TextEdit, Ui,
+};
+use std::{path::Path, sync::atomic::Ordering::Relaxed, time::Instant};
+
+#[derive(Default)]
+pub struct Details {
+    pub show: bool,
+    //row the note below was loaded from, it's reloaded once another row is picked
+    key: FileKey,
+    note: String,
+}
+
+//the row clicked last when it's still selected, otherwise the first selected one
+fn current_row(interface: &DownloadManager) -> Option<usize> {
+    let anchor = interface.select.anchor.as_ref();
+    interface
+        .files
+        .iter()
+        .position(|f| f.selected && anchor.is_some_and(|anchor| f.file.is(anchor)))
+        .or_else(|| interface.files.iter().position(|f| f.selected))
+}
+
+pub fn show_details(interface: &mut DownloadManager, key: &FileKey) {
+    for fdl in interface.files.iter_mut() {
+        fdl.selected = fdl.file.is(key);
+    }
+    interface.select.anchor = Some(key.clone());
+    interface.details.show = true;
+}
+
+pub fn lay_details_panel(interface: &mut DownloadManager, ctx: &Context) {
+    SidePanel::right(Id::new("details"))
+        .frame(
+            Frame::none()
+                .fill(*DARKER_PURPLE)
+                .inner_margin(TokyoNight.margin_style()),
+        )
+        .default_width(280.0)
+        .resizable(true)
+        .show_separator_line(true)
+        .show_animated(ctx, interface.details.show, |ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(*CYAN, RichText::new("Details").strong().size(15.0));
+                ui.with_layout(
+                    egui_sfml::egui::Layout::right_to_left(egui_sfml::egui::Align::Center),
+                    |ui| {
+                        let text = RichText::new(egui_phosphor::regular::X)
+                            .size(15.0)
+                            .color(*CYAN);
+                        if ui.add(Button::new(text).frame(false)).clicked() {
+                            interface.details.show = false;
+                        }
+                    },
+                );
+            });
+            ui.separator();
+            let Some(idx) = current_row(interface) else {
+                ui.colored_label(*GRAY, "Select a download to see its details");
+                return;
+            };
+            if !interface.files[idx].file.is(&interface.details.key) {
+                interface.details.note = interface.files[idx].file.note();
+                interface.details.key = interface.files[idx].file.key();
+            }
+            ScrollArea::vertical().show(ui, |ui| {
+                details_content(interface, idx, ui);
+            });
+        });
+}
+
+fn section(ui: &mut Ui, title: &str) {
+    ui.add_space(8.0);
+    ui.colored_label(*CYAN, RichText::new(title).strong());
+}
+
+fn since(at: Instant) -> String {
+    format!("{} ago", format_eta(at.elapsed()))
+}
+
+fn details_content(interface: &mut DownloadManager, idx: usize, ui: &mut Ui) {
+    let fdl = &interface.files[idx];
+    let file = fdl.file.clone();
+    ui.add(Label::new(RichText::new(&file.name_on_disk).strong().size(15.0)).wrap());
+
+    section(ui, "Url");
+    ui.add(Label::new(RichText::new(&file.url.link).color(*GRAY)).wrap());
+    if file.url.redirects.len() > 1 {
+        section(ui, "Redirects");
+        for hop in file.url.redirects.iter() {
+            let text = format!("{} {}", egui_phosphor::regular::ARROW_RIGHT, hop);
+            ui.add(Label::new(RichText::new(text).color(*GRAY)).wrap());
+        }
+    }
+    if !file.page_url.is_empty() {
+        section(ui, "Source page");
+        ui.add(Label::new(RichText::new(&file.page_url).color(*GRAY)).wrap());
+    }
+
+    section(ui, "Transfer");
+    let size = file.size_on_disk.load(Relaxed);
+    let total = file.url.content_length;
+    //transfers run over a single connection, so the one segment covers the whole file
+    let fraction = if total > 0 {
+        size as f32 / total as f32
+    } else {
+        0.0
+    };
+    let text = match total {
+        0 => format!("Segment 1: {} / ?", format_size(size)),
+        _ => format!("Segment 1: {} / {}", format_size(size), format_size(total)),
+    };
+    ui.scope(|ui| {
+        ui.visuals_mut().extreme_bg_color = *GRAY;
+        ui.visuals_mut().selection.bg_fill = *CYAN;
+        ui.visuals_mut().override_text_color = Some(*DARK_INNER);
+        ui.add(ProgressBar::new(fraction).text(text));
+    });
+    Grid::new("details_transfer").show(ui, |ui| {
+        ui.colored_label(*GRAY, "Resumable");
+        ui.label(if file.url.range_support { "Yes" } else { "No" });
+        ui.end_row();
+        let protocol = file.protocol.lock().map(|p| p.clone()).unwrap_or_default();
+        if !protocol.is_empty() {
+            ui.colored_label(*GRAY, "Protocol");
+            ui.label(protocol);
+            ui.end_row();
+        }
+    });
+
+    section(ui, "Checksum");
+    if file.checksum.is_empty() {
+        ui.colored_label(*GRAY, "None given");
+    } else {
+        ui.add(Label::new(RichText::new(&file.checksum).color(*GRAY)).wrap());
+        let (text, color) = if fdl.verifying.is_some() {
+            ("Verifying", *GRAY)
+        } else if file.verify_failed.load(Relaxed) {
+            ("Failed verification", *RED)
+        } else if file.complete.load(Relaxed) && file.verify {
+            ("Verified", *GREEN)
+        } else {
+            ("Checked once the download finishes", *GRAY)
+        };
+        ui.colored_label(color, text);
+    }
+
+    section(ui, "Timestamps");
+    Grid::new("details_times").show(ui, |ui| {
+        let added = DateTime::from_timestamp(file.added_at, 0)
+            .filter(|_| file.added_at > 0)
+            .map(|time| {
+                time.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            });
+        if let Some(added) = added {
+            ui.colored_label(*GRAY, "Added");
+            ui.label(added);
+            ui.end_row();
+        }
+        if let Some(started_at) = fdl.started_at {
+            ui.colored_label(*GRAY, "Started");
+            ui.label(since(started_at));
+            ui.end_row();
+        }
+        if let Some(completed_at) = fdl.completed_at {
+            ui.colored_label(*GRAY, "Completed");
+            ui.label(since(completed_at));
+            ui.end_row();
+        }
+        let modified = std::fs::metadata(Path::new(&file.dl_dir).join(&file.name_on_disk))
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(|time| {
+                DateTime::<Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            });
+        if let Some(modified) = modified.filter(|_| size > 0) {
+            ui.colored_label(*GRAY, "Written");
+            ui.label(modified);
+            ui.end_row();
+        }
+    });
+
+    section(ui, "Errors");
+    let logged = interface
+        .popups
+        .log
+        .logs
+        .iter()
+        .filter(|(_, text, color)| *color == *RED && text.contains(&file.name_on_disk))
+        .collect::<Vec<_>>();
+    if fdl.error.is_none() && logged.is_empty() {
+        ui.colored_label(*GRAY, "None");
+    }
+    if let Some(error) = fdl.error.as_ref() {
+        ui.add(Label::new(RichText::new(error).color(*RED)).wrap());
+    }
+    for (time, text, _) in logged.iter().rev().take(10) {
+        let text = format!("{} {}", time, text);
+        ui.add(Label::new(RichText::new(text).color(*RED)).wrap());
+    }
+
+    if !file.url.headers.is_empty() {
+        ui.add_space(8.0);
+        let title = RichText::new("Response headers").color(*CYAN).strong();
+        CollapsingHeader::new(title)
+            .id_salt("details_headers")
+            .show(ui, |ui| {
+                Grid::new("details_headers_grid").show(ui, |ui| {
+                    for (name, value) in file.url.headers.iter() {
+                        ui.colored_label(*GRAY, name);
+                        ui.add(Label::new(value).truncate());
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    section(ui, "Tags");
+    ui.horizontal_wrapped(|ui| {
+        tag_chips(&file, ui);
+        let text = RichText::new(egui_phosphor::regular::PENCIL_SIMPLE).color(*CYAN);
+        if ui.add(Button::new(text).frame(false)).clicked() {
+            edit_tags(interface, &file);
+        }
+    });
+
+    section(ui, "Note");
+    ui.scope(|ui| {
+        ui.visuals_mut().extreme_bg_color = *CYAN;
+        ui.visuals_mut().override_text_color = Some(*DARK_INNER);
+        let hint = RichText::new("Why it was downloaded, a license key...").color(*GRAY);
+        let note = TextEdit::multiline(&mut interface.details.note)
+            .desired_rows(3)
+            .hint_text(hint);
+        ui.add_sized((ui.available_width(), 60.0), note);
+    });
+    let changed = interface.details.note.trim() != file.note();
+    ui.visuals_mut().override_text_color = Some(*DARKER_PURPLE);
+    let butt = Button::new("Save note").fill(*CYAN);
+    if ui.add_enabled(changed, butt).clicked() {
+        file.set_note(&interface.details.note);
+        if let Err(e) = interface.files[idx].save_metadata() {
+            let formatted_time = Local::now().format("%H:%M:%S").to_string();
+            let text = format!("Couldn't save the note: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text.clone(), *RED));
+            interface.popups.error.value = text;
+            interface.popups.error.show = true;
+        }
+    }
+}
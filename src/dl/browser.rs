@@ -0,0 +1,52 @@
+use reqwest::{
+    header::{COOKIE, REFERER, USER_AGENT},
+    RequestBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+pub const CHROME_AGENT: &str = r#"Mozilla/5.0 (Windows; U; Windows NT 10.5; Win64; x64; en-US) AppleWebKit/537.33 (KHTML, like Gecko) Chrome/50.0.2124.268 Safari/536"#;
+
+//what the browser sent the link with, for servers that only hand the file to the session that asked for it
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserContext {
+    pub cookies: String,
+    pub referrer: String,
+    pub user_agent: String,
+}
+
+impl BrowserContext {
+    //adds the browser's headers to a request, the chrome agent stands in when it sent none
+    pub fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        let user_agent = match self.user_agent.trim() {
+            "" => CHROME_AGENT,
+            user_agent => user_agent,
+        };
+        let mut request = request.header(USER_AGENT, user_agent);
+        let cookies = self.cookies.trim();
+        if !cookies.is_empty() {
+            request = request.header(COOKIE, cookies);
+        }
+        let referrer = self.referrer.trim();
+        if !referrer.is_empty() {
+            request = request.header(REFERER, referrer);
+        }
+        request
+    }
+
+    //cookies and the referrer were sent for the old link's site, a link on another host keeps only the agent
+    pub fn for_new_link(&self, old_link: &str, new_link: &str) -> Self {
+        let host = |link: &str| {
+            url::Url::parse(link)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_lowercase))
+        };
+        if host(old_link).is_some() && host(old_link) == host(new_link) {
+            return self.clone();
+        }
+        Self {
+            user_agent: self.user_agent.clone(),
+            ..Self::default()
+        }
+    }
+}
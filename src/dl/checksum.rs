@@ -0,0 +1,109 @@
+use super::{errors::ChecksumError, net::build_client, tls::TlsOptions};
+use md5::Md5;
+use reqwest::Client;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    time::Duration,
+};
+
+const SUMS_FILES: [&str; 3] = ["SHA256SUMS", "sha256sums.txt", "SHA256SUMS.txt"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgo {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgo {
+    //the algorithm is picked from the length of the hex digest
+    pub fn detect(hash: &str) -> Option<Self> {
+        let hash = hash.trim();
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match hash.len() {
+            32 => Some(Self::Md5),
+            40 => Some(Self::Sha1),
+            64 => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+        }
+    }
+}
+
+//hashed counts the bytes read so far so the ui can show how far along it is
+pub fn hash_file(
+    path: &Path,
+    algo: HashAlgo,
+    hashed: &AtomicUsize,
+) -> Result<String, std::io::Error> {
+    match algo {
+        HashAlgo::Md5 => digest_file::<Md5>(path, hashed),
+        HashAlgo::Sha1 => digest_file::<Sha1>(path, hashed),
+        HashAlgo::Sha256 => digest_file::<Sha256>(path, hashed),
+    }
+}
+
+fn digest_file<D: Digest>(path: &Path, hashed: &AtomicUsize) -> Result<String, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        hashed.fetch_add(read, Relaxed);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+//looks for a published checksum file next to the download and returns the hash listed for it
+pub async fn fetch_published_sha256(
+    link: &str,
+    filename: &str,
+    tls: &TlsOptions,
+) -> Result<Option<String>, ChecksumError> {
+    let base = url::Url::parse(link)?;
+    let client = build_client(Client::builder().timeout(Duration::from_secs(15)), tls)?.build()?;
+    for sums_file in SUMS_FILES {
+        let sums_url = base.join(sums_file)?;
+        let res = client.get(sums_url).send().await?;
+        if !res.status().is_success() {
+            continue;
+        }
+        let body = res.text().await?;
+        if let Some(hash) = find_checksum(&body, filename) {
+            return Ok(Some(hash));
+        }
+    }
+    Ok(None)
+}
+
+//supports both the coreutils "<hash> *name" layout and the BSD "SHA256 (name) = <hash>" one
+pub fn find_checksum(sums: &str, filename: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("SHA256 (") {
+            let (name, hash) = rest.split_once(") = ")?;
+            return (name == filename).then(|| hash.trim().to_lowercase());
+        }
+        let (hash, name) = line.split_once(char::is_whitespace)?;
+        let name = name.trim().trim_start_matches('*');
+        (name == filename && hash.len() == 64).then(|| hash.to_lowercase())
+    })
+}
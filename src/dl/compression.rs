@@ -0,0 +1,23 @@
+use reqwest::ClientBuilder;
+
+//archives are left alone, a server that labels a .gz as gzip encoded would otherwise get it unpacked on the way in
+const COMPRESSED_EXTS: [&str; 10] = [
+    "gz", "tgz", "br", "zip", "xz", "bz2", "zst", "7z", "rar", "lz",
+];
+
+//only fresh transfers ask for compression, ranges always refer to the identity encoding
+pub fn wants_compression(name: &str, size_on_disk: usize) -> bool {
+    if size_on_disk > 0 {
+        return false;
+    }
+    let ext = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+    !COMPRESSED_EXTS.contains(&ext.as_str())
+}
+
+//the stream is decoded before it reaches the file, so progress counts the bytes written against the probed size
+pub fn apply(builder: ClientBuilder, enabled: bool) -> ClientBuilder {
+    builder.gzip(enabled).brotli(enabled)
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        RwLock,
+    },
+    time::Duration,
+};
+
+static WRITE_OPTIONS: RwLock<WriteOptions> = RwLock::new(WriteOptions::DEFAULT);
+//set once on startup when the app runs from its own folder
+static PORTABLE: AtomicBool = AtomicBool::new(false);
+
+//how often the periodic policy flushes to disk
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FsyncPolicy {
+    //leaves it to the os to write the data out
+    Never,
+    #[default]
+    OnCompletion,
+    //also syncs every SYNC_INTERVAL so less is lost on a power cut
+    Periodic,
+}
+
+impl FsyncPolicy {
+    pub const ALL: [Self; 3] = [Self::Never, Self::OnCompletion, Self::Periodic];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Never => "Never fsync",
+            Self::OnCompletion => "Fsync when done",
+            Self::Periodic => "Fsync every 10s",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteOptions {
+    pub buffer_kb: usize,
+    pub fsync: FsyncPolicy,
+}
+
+impl WriteOptions {
+    pub const DEFAULT: Self = Self {
+        buffer_kb: 256,
+        fsync: FsyncPolicy::OnCompletion,
+    };
+
+    //a zero sized buffer would make every write a syscall again, so one chunk's worth is the floor
+    pub fn buffer_bytes(&self) -> usize {
+        self.buffer_kb.max(8) * 1024
+    }
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub fn set_portable(portable: bool) {
+    PORTABLE.store(portable, Relaxed);
+}
+
+pub fn is_portable() -> bool {
+    PORTABLE.load(Relaxed)
+}
+
+//records are keyed by folder, so the same folder has to be spelled the same whatever the working dir,
+//in portable mode folders inside the app's own stay relative so the drive can be mounted anywhere
+pub fn normalize_dir(dir: &str) -> String {
+    let Ok(absolute) = std::path::absolute(dir) else {
+        return dir.to_string();
+    };
+    let relative = std::env::current_dir()
+        .ok()
+        .filter(|_| is_portable())
+        .and_then(|base| absolute.strip_prefix(base).ok().map(Path::to_path_buf));
+    match relative {
+        Some(relative) if relative.as_os_str().is_empty() => String::from("."),
+        Some(relative) => relative.to_string_lossy().to_string(),
+        None => absolute.to_string_lossy().to_string(),
+    }
+}
+
+pub fn set_write_options(options: WriteOptions) {
+    if let Ok(mut locked) = WRITE_OPTIONS.write() {
+        *locked = options;
+    }
+}
+
+pub fn write_options() -> WriteOptions {
+    WRITE_OPTIONS
+        .read()
+        .map(|locked| *locked)
+        .unwrap_or_default()
+}
@@ -7,8 +7,12 @@ pub enum UrlError {
     Generic(#[from] std::io::Error),
     #[error("Request Error: {0}")]
     RequestError(#[from] reqwest::Error),
-    #[error("Invalid Url")]
-    InvalidUrl,
+    #[error("Invalid Url: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    #[error("Unsupported scheme: {0} (only http and https are supported)")]
+    UnsupportedScheme(String),
+    #[error("Invalid Url: missing host")]
+    MissingHost,
 }
 
 #[derive(Debug, Error)]
@@ -21,4 +25,53 @@ pub enum File2DlError {
     ChannelRecvError(#[from] RecvError),
     #[error("Watch Channel Sending Failed: {0}")]
     ChannelSendError(#[from] SendError<bool>),
+    #[error("Server responded with status {0}")]
+    HttpStatus(u16),
+    #[error("Server ignored the range request")]
+    RangeIgnored,
+    #[error("No data received for {0} seconds")]
+    Stalled(u64),
+}
+
+impl File2DlError {
+    //client errors and ignored ranges won't fix themselves, server errors might
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::HttpStatus(code) => *code >= 500 || *code == 408 || *code == 429,
+            Self::RangeIgnored => false,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ChecksumError {
+    #[error("Io Error: {0}")]
+    Generic(#[from] std::io::Error),
+    #[error("Request Error: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Invalid Url: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+}
+
+#[derive(Debug, Error)]
+pub enum ScanError {
+    #[error("Io Error: {0}")]
+    Generic(#[from] std::io::Error),
+    #[error("Request Error: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+    #[error("VirusTotal responded with status {0}")]
+    HttpStatus(u16),
+}
+
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    #[error("Io Error: {0}")]
+    Generic(#[from] std::io::Error),
+    #[error("Invalid metadata: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Written by a newer version (format {0})")]
+    NewerVersion(u64),
 }
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+//weight of the newest speed sample, lower keeps the estimate steadier
+const ALPHA: f64 = 0.2;
+
+//folds a new one second sample into the running average, the first sample is taken as is
+pub fn smooth(previous: usize, sample: usize) -> usize {
+    if previous == 0 {
+        return sample;
+    }
+    (ALPHA * sample as f64 + (1.0 - ALPHA) * previous as f64).round() as usize
+}
+
+//none while the speed or the remaining size is unknown
+pub fn time_left(remaining: usize, speed: usize) -> Option<Duration> {
+    if speed == 0 || remaining == 0 {
+        return None;
+    }
+    Some(Duration::from_secs(remaining.div_ceil(speed) as u64))
+}
+
+pub fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+//binary units with one decimal past plain bytes, a trailing .0 is dropped, e.g. 12.3 MB or 700 MB
+pub fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{} B", bytes);
+    }
+    let size = format!("{:.1}", size);
+    format!("{} {}", size.trim_end_matches(".0"), UNITS[unit])
+}
@@ -1,25 +1,34 @@
 use super::{
-    errors::{File2DlError, UrlError},
-    metadata::{init_metadata, MetaData},
+    browser::BrowserContext,
+    compression::wants_compression,
+    disk::{normalize_dir, write_options, FsyncPolicy, SYNC_INTERVAL},
+    errors::{File2DlError, MetadataError, UrlError},
+    eta::{smooth, time_left},
+    limiter::{throttle_transfer, ActiveTransfer, RateLimiter},
+    metadata::{init_metadata, parse_metadata, quarantine, read_metadata, MetaData},
+    net::shared_client,
+    store::{adopt_records, insert_record, records},
+    timeouts::{resolve_timeouts, Timeouts},
+    tls::TlsOptions,
     url::Url,
 };
 use futures::StreamExt;
-use reqwest::{header::RANGE, redirect::Policy, Client, ClientBuilder, Error, Response};
+use reqwest::{header::RANGE, Client, Error, Response, StatusCode};
 use std::sync::atomic::Ordering::Relaxed;
 use std::{
-    fs::{create_dir, metadata, read_dir, File},
-    io::Read,
+    fs::{create_dir_all, metadata, read_dir, remove_file},
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicBool, AtomicUsize},
-        Arc,
+        atomic::{AtomicBool, AtomicI64, AtomicUsize},
+        Arc, Mutex,
     },
     time::Duration,
 };
 use tokio::{
     fs::OpenOptions,
-    io::AsyncWriteExt,
-    time::{sleep, Instant},
+    io::{AsyncWriteExt, BufWriter},
+    sync::watch,
+    time::{sleep, timeout, Instant},
 };
 
 #[derive(Debug, Default, Clone)]
@@ -30,17 +39,62 @@ pub struct File2Dl {
     pub size_on_disk: Arc<AtomicUsize>,
     pub dl_dir: String,
     pub bytes_per_sec: Arc<AtomicUsize>,
+    //exponentially weighted bytes per sec, steadier than bytes_per_sec for estimating time left
+    pub smoothed_speed: Arc<AtomicUsize>,
     pub running: Arc<AtomicBool>,
     pub complete: Arc<AtomicBool>,
+    pub failed: Arc<AtomicBool>,
+    pub cancelled: Arc<AtomicBool>,
+    pub page_title: String,
+    pub page_url: String,
+    pub refresh_hours: u64,
+    pub tls: TlsOptions,
+    //cookies, referrer and user agent of the browser the link came from, empty for every other link
+    pub browser: BrowserContext,
+    pub timeouts: Option<Timeouts>,
+    //expected md5, sha1 or sha256 in hex, empty when none was given
+    pub checksum: String,
+    pub verify: bool,
+    pub verify_failed: Arc<AtomicBool>,
+    //http version the server answered with on the last transfer, empty until one starts
+    pub protocol: Arc<Mutex<String>>,
+    //labels set by the user, shared with the transfer's copy so it never writes back stale ones
+    pub tags: Arc<Mutex<Vec<String>>>,
+    //free text the user keeps with the download, shared the same way
+    pub note: Arc<Mutex<String>>,
+    //unix time in seconds the download was queued, 0 for ones queued before it was recorded
+    pub added_at: i64,
+    //place in the queue of unfinished downloads, lower goes first
+    pub position: Arc<AtomicI64>,
+    //waiting for every other download to finish before it starts
+    pub queued: Arc<AtomicBool>,
+}
+
+//names are only unique within a folder, a download is told apart by both
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct FileKey {
+    pub dl_dir: String,
+    pub name_on_disk: String,
 }
 
 impl File2Dl {
-    pub async fn new(link: &str, download_path: &str) -> Result<Self, UrlError> {
-        let url = Url::new(link).await?;
+    pub async fn new(link: &str, download_path: &str, tls: TlsOptions) -> Result<Self, UrlError> {
+        Self::with_browser(link, download_path, tls, BrowserContext::default()).await
+    }
+
+    pub async fn with_browser(
+        link: &str,
+        download_path: &str,
+        tls: TlsOptions,
+        browser: BrowserContext,
+    ) -> Result<Self, UrlError> {
+        let url = Url::with_browser(link, &tls, &browser).await?;
+        let download_path = &normalize_dir(download_path);
         if !Path::new(download_path).exists() {
-            create_dir(download_path)?;
+            create_dir_all(download_path)?;
         }
         let name_on_disk = generate_name_on_disk(&url.filename, download_path)?;
+        let added_at = chrono::Utc::now().timestamp();
         Ok(Self {
             url,
             name_on_disk,
@@ -48,133 +102,438 @@ impl File2Dl {
             size_on_disk: Arc::new(AtomicUsize::new(0)),
             dl_dir: download_path.to_string(),
             bytes_per_sec: Arc::new(AtomicUsize::new(0)),
+            smoothed_speed: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicBool::new(false)),
             complete: Arc::new(AtomicBool::new(false)),
+            failed: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            page_title: String::new(),
+            page_url: String::new(),
+            refresh_hours: 0,
+            tls,
+            browser,
+            timeouts: None,
+            checksum: String::new(),
+            verify: false,
+            verify_failed: Arc::new(AtomicBool::new(false)),
+            protocol: Arc::default(),
+            tags: Arc::default(),
+            note: Arc::default(),
+            added_at,
+            position: Arc::new(AtomicI64::new(added_at)),
+            queued: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    pub fn key(&self) -> FileKey {
+        FileKey {
+            dl_dir: self.dl_dir.clone(),
+            name_on_disk: self.name_on_disk.clone(),
+        }
+    }
+
+    pub fn is(&self, key: &FileKey) -> bool {
+        self.name_on_disk == key.name_on_disk && self.dl_dir == key.dl_dir
+    }
+
+    pub fn same_file(&self, other: &File2Dl) -> bool {
+        self.name_on_disk == other.name_on_disk && self.dl_dir == other.dl_dir
+    }
+
     pub fn toggle_status(&self) {
         let status = self.running.load(Relaxed);
         self.running.store(!status, Relaxed);
     }
 
-    pub async fn single_thread_dl(&self) -> Result<(), File2DlError> {
-        let client = ClientBuilder::new().redirect(Policy::limited(15)).build()?;
-        let res = init_res(self, &client).await?;
+    pub fn tags(&self) -> Vec<String> {
+        self.tags
+            .lock()
+            .map(|tags| tags.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_tags(&self, tags: Vec<String>) {
+        if let Ok(mut locked) = self.tags.lock() {
+            *locked = tags;
+        }
+    }
+
+    pub fn note(&self) -> String {
+        self.note
+            .lock()
+            .map(|note| note.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_note(&self, note: &str) {
+        if let Ok(mut locked) = self.note.lock() {
+            *locked = note.trim().to_string();
+        }
+    }
+
+    //none when the size is unknown or nothing is moving
+    pub fn eta(&self) -> Option<Duration> {
+        if !self.running.load(Relaxed) || self.complete.load(Relaxed) {
+            return None;
+        }
+        time_left(self.remaining()?, self.smoothed_speed.load(Relaxed))
+    }
+
+    //none when the server never said how big the file is
+    pub fn remaining(&self) -> Option<usize> {
+        if self.url.content_length == 0 {
+            return None;
+        }
+        Some(
+            self.url
+                .content_length
+                .saturating_sub(self.size_on_disk.load(Relaxed)),
+        )
+    }
+
+    fn fail(&self) {
+        self.failed.store(true, Relaxed);
+        self.running.store(false, Relaxed);
+        self.bytes_per_sec.store(0, Relaxed);
+        self.smoothed_speed.store(0, Relaxed);
+    }
+
+    //only ranged transfers are retried, those stay running so run_downloads doesn't cancel the next attempt
+    fn refuse(&self, e: File2DlError) -> File2DlError {
+        if !e.is_retryable() || !self.url.range_support {
+            self.fail();
+        } else {
+            self.bytes_per_sec.store(0, Relaxed);
+            self.smoothed_speed.store(0, Relaxed);
+        }
+        e
+    }
+
+    //tries again after retry_interval for as long as the error might go away, until cancel fires
+    pub async fn dl_with_retries(
+        &self,
+        mut cancel: watch::Receiver<bool>,
+        retry_interval: Duration,
+        on_error: impl Fn(&File2DlError),
+    ) {
+        loop {
+            match self.single_thread_dl(cancel.clone()).await {
+                Ok(_) => break,
+                Err(e) => {
+                    on_error(&e);
+                    if !e.is_retryable() {
+                        break;
+                    }
+                }
+            }
+            tokio::select! {
+                _ = sleep(retry_interval) => {}
+                _ = cancel.changed() => break,
+            }
+        }
+    }
+
+    //returns once the file is complete or as soon as cancel fires, a cancelled transfer is left as is on disk
+    pub async fn single_thread_dl(
+        &self,
+        mut cancel: watch::Receiver<bool>,
+    ) -> Result<(), File2DlError> {
+        let timeouts = resolve_timeouts(self.timeouts);
+        let compressed = wants_compression(&self.name_on_disk, self.size_on_disk.load(Relaxed));
+        //uncompressed transfers reuse the connection the probe left in the pool
+        let client =
+            shared_client::<File2DlError>(&self.url.link, &self.tls, timeouts, compressed)?;
+        let res = init_res(self, &client, compressed).await?;
+        //error pages must never end up in the target file
+        let status = res.status();
+        if !status.is_success() {
+            return Err(self.refuse(File2DlError::HttpStatus(status.as_u16())));
+        }
+        if self.url.range_support
+            && self.size_on_disk.load(Relaxed) > 0
+            && status != StatusCode::PARTIAL_CONTENT
+        {
+            return Err(self.refuse(File2DlError::RangeIgnored));
+        }
+        if let Ok(mut protocol) = self.protocol.lock() {
+            *protocol = format!("{:?}", res.version());
+        }
+        self.failed.store(false, Relaxed);
         init_metadata(self, &self.dl_dir)?;
         let mut stream = res.bytes_stream();
         let file_path = Path::new(&self.dl_dir).join(&self.name_on_disk);
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(file_path)
-            .await?;
+        //without range support the server sends everything again, so whatever is on disk goes
+        let file = if self.url.range_support {
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(file_path)
+                .await?
+        } else {
+            self.size_on_disk.store(0, Relaxed);
+            OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .create(true)
+                .open(file_path)
+                .await?
+        };
+        //size_on_disk counts buffered bytes too, so every way out of the loop flushes first
+        let write_options = write_options();
+        let mut file = BufWriter::with_capacity(write_options.buffer_bytes(), file);
+        let mut last_sync = Instant::now();
 
         let mut accumulated_bytes = 0usize;
         let mut start_time = Instant::now();
 
-        while let Some(packed_chunk) = stream.next().await {
-            if !self.running.load(Relaxed) {
-                self.bytes_per_sec.store(0, Relaxed);
-                sleep(Duration::from_secs(2)).await;
-                continue;
-            }
+        let stall_limit = timeouts.stall();
+        let limiter = RateLimiter::default();
+        let _active = ActiveTransfer::start();
+        loop {
+            //a connection that stays open without sending anything is dropped so the retry can resume it
+            let next = async {
+                match stall_limit {
+                    Some(limit) => timeout(limit, stream.next())
+                        .await
+                        .map_err(|_| File2DlError::Stalled(limit.as_secs())),
+                    None => Ok(stream.next().await),
+                }
+            };
+            let next = tokio::select! {
+                next = next => next,
+                _ = cancel.changed() => {
+                    self.bytes_per_sec.store(0, Relaxed);
+                    self.smoothed_speed.store(0, Relaxed);
+                    file.flush().await?;
+                    return Ok(());
+                }
+            };
+            let next = match next {
+                Ok(next) => next,
+                Err(e) => {
+                    self.bytes_per_sec.store(0, Relaxed);
+                    self.smoothed_speed.store(0, Relaxed);
+                    file.flush().await?;
+                    return Err(e);
+                }
+            };
+            let Some(packed_chunk) = next else {
+                break;
+            };
 
-            let chunk = packed_chunk?;
+            let chunk = match packed_chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    file.flush().await?;
+                    return Err(e.into());
+                }
+            };
+            throttle_transfer(&limiter, chunk.len(), self.speed.load(Relaxed)).await;
             file.write_all(&chunk).await?;
             self.size_on_disk.fetch_add(chunk.len(), Relaxed);
             accumulated_bytes += chunk.len();
 
-            if start_time.elapsed() >= Duration::from_secs(1) {
-                self.bytes_per_sec.store(accumulated_bytes, Relaxed);
+            //the meter is kept apart from the limiter so throttling never skews the shown rate
+            let elapsed = start_time.elapsed();
+            if elapsed >= Duration::from_secs(1) {
+                let rate = accumulated_bytes as f64 / elapsed.as_secs_f64();
+                self.bytes_per_sec.store(rate as usize, Relaxed);
+                let smoothed = smooth(self.smoothed_speed.load(Relaxed), rate as usize);
+                self.smoothed_speed.store(smoothed, Relaxed);
                 accumulated_bytes = 0;
                 start_time = Instant::now();
             }
-
-            let speed_limit = self.speed.load(Relaxed);
-            if speed_limit > 0 && accumulated_bytes >= speed_limit {
-                let elapsed = start_time.elapsed();
-                if elapsed < Duration::from_secs(1) {
-                    sleep(Duration::from_secs(1) - elapsed).await;
-                }
-                accumulated_bytes = 0;
-                start_time = Instant::now();
+            if write_options.fsync == FsyncPolicy::Periodic && last_sync.elapsed() >= SYNC_INTERVAL
+            {
+                file.flush().await?;
+                file.get_ref().sync_data().await?;
+                last_sync = Instant::now();
             }
         }
 
+        file.flush().await?;
+        if write_options.fsync != FsyncPolicy::Never {
+            file.get_ref().sync_all().await?;
+        }
         self.complete.store(true, Relaxed);
         self.running.store(false, Relaxed);
         self.bytes_per_sec.store(0, Relaxed);
+        self.smoothed_speed.store(0, Relaxed);
         Ok(())
     }
-    pub fn from(dir: &str) -> Result<Vec<File2Dl>, std::io::Error> {
-        get_metadata_files(dir)?
-            .into_iter()
-            .map(|entry| {
-                let m_data: MetaData = {
-                    let path = Path::new(dir).join(&entry);
-                    let mut buf = String::new();
-                    File::open(&path)?.read_to_string(&mut buf)?;
-                    serde_json::from_str(&buf)?
-                };
-                let size_on_disk = {
-                    let file_path = Path::new(dir).join(&m_data.name_on_disk);
-                    get_file_size(&file_path)?
-                };
+    //records that can't be loaded are reported instead of failing the whole folder
+    pub fn from(dir: &str) -> Result<(Vec<File2Dl>, Vec<String>), std::io::Error> {
+        let dir = &normalize_dir(dir);
+        adopt_records(dir)?;
+        let mut problems = import_metadata_files(dir)?;
+        let mut files = Vec::new();
+        for (name_on_disk, data) in records(dir)? {
+            //left in the store, a newer version that wrote it can still load it
+            let loaded = parse_metadata(&data)
+                .map_err(|e| e.to_string())
+                .and_then(|m_data| Self::from_metadata(m_data, dir).map_err(|e| e.to_string()));
+            match loaded {
+                Ok(file) => files.push(file),
+                Err(e) => problems.push(format!("Couldn't load {}: {}", name_on_disk, e)),
+            }
+        }
+        Ok((files, problems))
+    }
 
-                let f2dl = {
-                    let url = Url {
-                        link: m_data.link,
-                        filename: m_data.url_name,
-                        content_length: m_data.content_length,
-                        range_support: m_data.range_support,
-                    };
-                    let name_on_disk = {
-                        if m_data.range_support {
-                            m_data.name_on_disk
-                        } else {
-                            generate_name_on_disk(&m_data.name_on_disk, dir)?
-                        }
-                    };
-                    let is_complete = size_on_disk == m_data.content_length;
-                    File2Dl {
-                        url,
-                        dl_dir: dir.to_string(),
-                        speed: Arc::new(AtomicUsize::new(m_data.speed)),
-                        bytes_per_sec: Arc::new(AtomicUsize::new(0)),
-                        name_on_disk,
-                        size_on_disk: Arc::new(AtomicUsize::new(size_on_disk)),
-                        running: Arc::new(AtomicBool::new(false)),
-                        complete: Arc::new(AtomicBool::new(is_complete)),
-                    }
-                };
-                Ok(f2dl)
-            })
-            .collect()
+    fn from_metadata(m_data: MetaData, dir: &str) -> Result<Self, std::io::Error> {
+        let size_on_disk = {
+            let file_path = Path::new(dir).join(&m_data.name_on_disk);
+            match get_file_size(&file_path) {
+                //a queued row is recorded before its transfer ever created the file
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && m_data.queued => 0,
+                size => size?,
+            }
+        };
+
+        let f2dl = {
+            let url = Url {
+                link: m_data.link,
+                filename: m_data.url_name,
+                content_length: m_data.content_length,
+                range_support: m_data.range_support,
+                last_modified: m_data.last_modified,
+                etag: m_data.etag,
+                ..Default::default()
+            };
+            let name_on_disk = {
+                if m_data.range_support {
+                    m_data.name_on_disk
+                } else {
+                    generate_name_on_disk(&m_data.name_on_disk, dir)?
+                }
+            };
+            let is_complete = size_on_disk == m_data.content_length;
+            File2Dl {
+                url,
+                dl_dir: dir.to_string(),
+                speed: Arc::new(AtomicUsize::new(m_data.speed)),
+                bytes_per_sec: Arc::new(AtomicUsize::new(0)),
+                smoothed_speed: Arc::new(AtomicUsize::new(0)),
+                name_on_disk,
+                size_on_disk: Arc::new(AtomicUsize::new(size_on_disk)),
+                running: Arc::new(AtomicBool::new(false)),
+                complete: Arc::new(AtomicBool::new(is_complete)),
+                failed: Arc::new(AtomicBool::new(false)),
+                cancelled: Arc::new(AtomicBool::new(m_data.cancelled && !is_complete)),
+                page_title: m_data.page_title,
+                page_url: m_data.page_url,
+                refresh_hours: m_data.refresh_hours,
+                tls: m_data.tls,
+                browser: m_data.browser,
+                timeouts: m_data.timeouts,
+                checksum: m_data.checksum,
+                verify: m_data.verify,
+                verify_failed: Arc::new(AtomicBool::new(m_data.verify_failed && is_complete)),
+                protocol: Arc::default(),
+                tags: Arc::new(Mutex::new(m_data.tags)),
+                note: Arc::new(Mutex::new(m_data.note)),
+                added_at: m_data.added_at,
+                //records from before the queue could be reordered go by when they were added
+                position: Arc::new(AtomicI64::new(match m_data.position {
+                    0 => m_data.added_at,
+                    position => position,
+                })),
+                queued: Arc::new(AtomicBool::new(m_data.queued && !is_complete)),
+            }
+        };
+        Ok(f2dl)
     }
 }
 
-fn generate_name_on_disk(init: &str, download_path: &str) -> Result<String, std::io::Error> {
+pub fn generate_name_on_disk(init: &str, download_path: &str) -> Result<String, std::io::Error> {
+    generate_free_name(init, download_path, |_| false)
+}
+
+//rows that haven't written anything yet aren't on disk, taken says which other names are spoken for
+pub fn generate_free_name(
+    init: &str,
+    download_path: &str,
+    taken: impl Fn(&str) -> bool,
+) -> Result<String, std::io::Error> {
     let path = std::path::Path::new(download_path);
+    let init = sanitize_filename(init);
     let (name, ext) = {
-        let file = Path::new(init);
+        let file = Path::new(&init);
         (
             file.file_stem().unwrap_or_default().to_string_lossy(),
             file.extension().unwrap_or_default().to_string_lossy(),
         )
     };
-    let mut init = init.to_string();
+    let mut candidate = init.clone();
     let mut idx = 1;
-    while path.join(&init).exists() {
-        init = format!("{name}_{idx}.{ext}");
+    while path.join(&candidate).exists() || taken(&candidate) {
+        candidate = if ext.is_empty() {
+            format!("{name}_{idx}")
+        } else {
+            format!("{name}_{idx}.{ext}")
+        };
         idx += 1;
     }
-    Ok(init)
+    Ok(candidate)
+}
+
+//comma separated tags as typed by the user, blanks and repeats (ignoring case) are dropped
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
 }
-async fn init_res(f: &File2Dl, client: &Client) -> Result<Response, Error> {
-    if f.url.range_support {
-        return client
-            .get(&f.url.link)
+
+//makes header or url supplied names safe on every platform while keeping the extension intact
+pub fn sanitize_filename(name: &str) -> String {
+    const MAX_LEN: usize = 200;
+    const RESERVED: [&str; 22] = [
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    let mut sanitized = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .trim_end_matches(['.', ' '])
+        .trim_start_matches('.')
+        .to_string();
+    if sanitized.is_empty() {
+        sanitized = String::from("download");
+    }
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        sanitized = format!("_{}", sanitized);
+    }
+    if sanitized.len() > MAX_LEN {
+        let ext = Path::new(&sanitized)
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let mut cut = MAX_LEN.saturating_sub(ext.len());
+        while !sanitized.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        sanitized = format!("{}{}", &sanitized[..cut], ext);
+    }
+    sanitized
+}
+async fn init_res(f: &File2Dl, client: &Client, compressed: bool) -> Result<Response, Error> {
+    //a range over a compressed body would be a range of the encoded bytes, a fresh transfer doesn't need one,
+    //and one bounded by a stale size would cut off a file that grew since it was probed
+    if f.url.range_support && !compressed && f.size_on_disk.load(Relaxed) > 0 {
+        return f
+            .browser
+            .apply(client.get(&f.url.link))
             .header(
                 RANGE,
                 format!(
@@ -186,7 +545,48 @@ async fn init_res(f: &File2Dl, client: &Client) -> Result<Response, Error> {
             .send()
             .await;
     }
-    client.get(&f.url.link).send().await
+    f.browser.apply(client.get(&f.url.link)).send().await
+}
+
+//moves the .metadl files older versions kept next to the downloads into the store
+fn import_metadata_files(dir: &str) -> Result<Vec<String>, std::io::Error> {
+    let mut problems = Vec::new();
+    for entry in get_metadata_files(dir)? {
+        let path = Path::new(dir).join(&entry);
+        let m_data = match read_metadata(&path) {
+            Ok(m_data) => m_data,
+            //left in place, the newer version that wrote it can still load it
+            Err(e @ MetadataError::NewerVersion(_)) => {
+                problems.push(format!("Skipped {}: {}", entry, e));
+                continue;
+            }
+            Err(e) => {
+                let text = match quarantine(&path) {
+                    Ok(moved) => format!(
+                        "Moved unreadable {} to {}: {}",
+                        entry,
+                        moved.to_string_lossy(),
+                        e
+                    ),
+                    Err(moving) => format!(
+                        "Couldn't read {}: {}, nor move it aside: {}",
+                        entry, e, moving
+                    ),
+                };
+                problems.push(text);
+                continue;
+            }
+        };
+        //the file is only removed once its record is safely stored
+        let imported = serde_json::to_string(&m_data)
+            .map_err(std::io::Error::from)
+            .and_then(|data| insert_record(dir, &m_data.name_on_disk, &data))
+            .and_then(|_| remove_file(&path));
+        if let Err(e) = imported {
+            problems.push(format!("Couldn't import {}: {}", entry, e));
+        }
+    }
+    Ok(problems)
 }
 
 fn get_metadata_files(dir: &str) -> Result<Vec<String>, std::io::Error> {
@@ -206,7 +606,7 @@ fn get_metadata_files(dir: &str) -> Result<Vec<String>, std::io::Error> {
 }
 
 fn get_file_size(path: &PathBuf) -> Result<usize, std::io::Error> {
-    #[cfg(target_os = "linux")]
+    #[cfg(unix)]
     {
         use std::os::unix::fs::MetadataExt;
         let metadata = metadata(path)?;
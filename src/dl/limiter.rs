@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        LazyLock, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+static GLOBAL_LIMIT: RwLock<(usize, LimitSplit)> = RwLock::new((0, LimitSplit::Proportional));
+static GLOBAL_LIMITER: LazyLock<RateLimiter> = LazyLock::new(RateLimiter::default);
+static ACTIVE_TRANSFERS: AtomicUsize = AtomicUsize::new(0);
+
+//how many seconds worth of bytes can pile up while a transfer is idle
+const BURST_SECS: f64 = 0.25;
+
+#[derive(Debug)]
+pub struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            tokens: 0.0,
+            last_refill: now,
+        }
+    }
+
+    //takes bytes out of the bucket and returns how long the caller has to wait before reading more
+    //the bucket may go into debt so chunks bigger than the burst still get through at the right pace
+    pub fn take(&mut self, bytes: usize, rate: usize, now: Instant) -> Duration {
+        let rate = rate as f64;
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * rate).min(rate * BURST_SECS);
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-self.tokens / rate)
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    bucket: Mutex<TokenBucket>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket::new(Instant::now())),
+        }
+    }
+}
+
+impl RateLimiter {
+    //rate is in bytes per sec and read on every chunk so limit changes apply right away, 0 is unlimited
+    pub async fn throttle(&self, bytes: usize, rate: usize) {
+        let delay = {
+            let Ok(mut bucket) = self.bucket.lock() else {
+                return;
+            };
+            if rate == 0 {
+                //keeps the bucket empty so lifting the limit doesn't leave a burst behind
+                *bucket = TokenBucket::new(Instant::now());
+                return;
+            }
+            bucket.take(bytes, rate, Instant::now())
+        };
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LimitSplit {
+    //every transfer draws from one shared bucket, faster servers get a bigger share
+    #[default]
+    Proportional,
+    //every running transfer gets the same slice of the cap
+    Equal,
+}
+
+impl LimitSplit {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Proportional => "Proportional",
+            Self::Equal => "Equal",
+        }
+    }
+}
+
+//bytes per sec, 0 lifts the cap
+pub fn set_global_limit(limit: usize, split: LimitSplit) {
+    if let Ok(mut locked) = GLOBAL_LIMIT.write() {
+        *locked = (limit, split);
+    }
+}
+
+pub fn global_limit() -> (usize, LimitSplit) {
+    GLOBAL_LIMIT
+        .read()
+        .map(|locked| *locked)
+        .unwrap_or_default()
+}
+
+//the smaller of two limits where 0 means unlimited
+pub fn combine_limits(a: usize, b: usize) -> usize {
+    match (a, b) {
+        (0, limit) | (limit, 0) => limit,
+        (a, b) => a.min(b),
+    }
+}
+
+//each running transfer's slice of the cap, 0 when there's no cap
+pub fn equal_share(cap: usize, transfers: usize) -> usize {
+    let share = cap / transfers.max(1);
+    //a cap smaller than the number of transfers still has to limit something
+    if cap > 0 {
+        share.max(1)
+    } else {
+        0
+    }
+}
+
+//counts a transfer towards the equal split for as long as its connection is open
+pub struct ActiveTransfer;
+
+impl ActiveTransfer {
+    pub fn start() -> Self {
+        ACTIVE_TRANSFERS.fetch_add(1, Relaxed);
+        Self
+    }
+}
+
+impl Drop for ActiveTransfer {
+    fn drop(&mut self) {
+        ACTIVE_TRANSFERS.fetch_sub(1, Relaxed);
+    }
+}
+
+//waits on the download's own limit and on the global cap
+pub async fn throttle_transfer(limiter: &RateLimiter, bytes: usize, own_limit: usize) {
+    let (cap, split) = global_limit();
+    match split {
+        LimitSplit::Proportional => {
+            limiter.throttle(bytes, own_limit).await;
+            GLOBAL_LIMITER.throttle(bytes, cap).await;
+        }
+        LimitSplit::Equal => {
+            let share = equal_share(cap, ACTIVE_TRANSFERS.load(Relaxed));
+            limiter
+                .throttle(bytes, combine_limits(own_limit, share))
+                .await;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SpeedUnit {
+    KBs,
+    #[default]
+    MBs,
+    Mbits,
+}
+
+impl SpeedUnit {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::KBs => "KB/s",
+            Self::MBs => "MB/s",
+            Self::Mbits => "Mbit/s",
+        }
+    }
+
+    fn bytes_per_unit(&self) -> f64 {
+        match self {
+            Self::KBs => 1024.0,
+            Self::MBs => 1024.0 * 1024.0,
+            Self::Mbits => 1_000_000.0 / 8.0,
+        }
+    }
+}
+
+//turns a user typed limit into bytes per sec, 0 lifts the limit
+pub fn parse_speed(input: &str, unit: SpeedUnit) -> Result<usize, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(String::from("Enter a speed"));
+    }
+    match input.parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => {
+            Ok((value * unit.bytes_per_unit()).round() as usize)
+        }
+        _ => Err(format!("Not a valid speed: {}", input)),
+    }
+}
+
+//a limit typed with its unit, like 500K, 2MB/s or 8Mbit, MB/s when it has none
+pub fn parse_rate(input: &str) -> Result<usize, String> {
+    let lowered = input.trim().to_lowercase();
+    let lowered = lowered.strip_suffix("/s").unwrap_or(&lowered);
+    let split = lowered
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(lowered.len());
+    let (value, unit) = lowered.split_at(split);
+    let unit = match unit {
+        "k" | "kb" => SpeedUnit::KBs,
+        "" | "m" | "mb" => SpeedUnit::MBs,
+        "mbit" => SpeedUnit::Mbits,
+        _ => return Err(format!("Not a valid speed: {}", input.trim())),
+    };
+    parse_speed(value, unit)
+}
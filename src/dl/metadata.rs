@@ -1,32 +1,142 @@
-use std::{fs::File, path::Path};
+use std::{
+    fs::{rename, File},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering::Relaxed,
+};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use super::file2dl::File2Dl;
+use super::{
+    browser::BrowserContext,
+    errors::MetadataError,
+    file2dl::File2Dl,
+    store::{remove_record, save_record},
+    timeouts::Timeouts,
+    tls::TlsOptions,
+};
+
+//bump together with a new entry in MIGRATIONS whenever a field changes meaning or gets renamed
+pub const METADATA_VERSION: u64 = 1;
+
+//entry n upgrades a format n file to n + 1
+const MIGRATIONS: [fn(&mut Value); METADATA_VERSION as usize] = [from_unversioned];
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MetaData {
+    //missing in files written before versioning, which are format 0
+    #[serde(default)]
+    pub version: u64,
     pub link: String,
     pub name_on_disk: String,
     pub speed: usize,
     pub url_name: String,
     pub content_length: usize,
     pub range_support: bool,
+    #[serde(default)]
+    pub page_title: String,
+    #[serde(default)]
+    pub page_url: String,
+    #[serde(default)]
+    pub refresh_hours: u64,
+    #[serde(default)]
+    pub tls: TlsOptions,
+    #[serde(default)]
+    pub browser: BrowserContext,
+    #[serde(default)]
+    pub timeouts: Option<Timeouts>,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub checksum: String,
+    #[serde(default)]
+    pub verify: bool,
+    #[serde(default)]
+    pub verify_failed: bool,
+    #[serde(default)]
+    pub last_modified: String,
+    #[serde(default)]
+    pub etag: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub added_at: i64,
+    #[serde(default)]
+    pub position: i64,
+    #[serde(default)]
+    pub queued: bool,
 }
 
+//the record goes into the download store, a single upsert so it's never left half written
 pub fn init_metadata(f: &File2Dl, dl_path: &str) -> Result<(), std::io::Error> {
-    let meta_filename = format!(".{}.metadl", &f.name_on_disk);
-    let path = Path::new(dl_path).join(meta_filename);
-    let file = File::create(&path)?;
     let meta_data = MetaData {
+        version: METADATA_VERSION,
         link: f.url.link.clone(),
         name_on_disk: f.name_on_disk.clone(),
-        speed: f.speed.load(std::sync::atomic::Ordering::Relaxed),
+        speed: f.speed.load(Relaxed),
         url_name: f.url.filename.clone(),
         content_length: f.url.content_length,
         range_support: f.url.range_support,
+        page_title: f.page_title.clone(),
+        page_url: f.page_url.clone(),
+        refresh_hours: f.refresh_hours,
+        tls: f.tls.clone(),
+        browser: f.browser.clone(),
+        timeouts: f.timeouts,
+        cancelled: f.cancelled.load(Relaxed),
+        checksum: f.checksum.clone(),
+        verify: f.verify,
+        verify_failed: f.verify_failed.load(Relaxed),
+        last_modified: f.url.last_modified.clone(),
+        etag: f.url.etag.clone(),
+        tags: f.tags(),
+        note: f.note(),
+        added_at: f.added_at,
+        position: f.position.load(Relaxed),
+        queued: f.queued.load(Relaxed),
     };
-    serde_json::to_writer(file, &meta_data)?;
+    save_record(
+        dl_path,
+        &f.name_on_disk,
+        &serde_json::to_string(&meta_data)?,
+    )
+}
+
+pub fn remove_metadata(f: &File2Dl) -> Result<(), std::io::Error> {
+    remove_record(&f.dl_dir, &f.name_on_disk)
+}
+
+//files written before versioning only lack fields that have serde defaults
+fn from_unversioned(_: &mut Value) {}
+
+pub fn parse_metadata(json: &str) -> Result<MetaData, MetadataError> {
+    let mut value: Value = serde_json::from_str(json)?;
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > METADATA_VERSION {
+        return Err(MetadataError::NewerVersion(version));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut value);
+    }
+    value["version"] = METADATA_VERSION.into();
+    Ok(serde_json::from_value(value)?)
+}
+
+//only the .metadl files from before the download store are still read from disk
+pub fn read_metadata(path: &Path) -> Result<MetaData, MetadataError> {
+    let mut buf = String::new();
+    File::open(path)?.read_to_string(&mut buf)?;
+    parse_metadata(&buf)
+}
 
-    Ok(())
+//moves unreadable metadata out of the way so the rest of the folder still loads
+pub fn quarantine(path: &Path) -> Result<PathBuf, std::io::Error> {
+    let mut quarantined = path.as_os_str().to_owned();
+    quarantined.push(".broken");
+    let quarantined = PathBuf::from(quarantined);
+    rename(path, &quarantined)?;
+    Ok(quarantined)
 }
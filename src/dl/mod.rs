@@ -1,5 +1,18 @@
+pub mod browser;
+pub mod checksum;
+pub mod compression;
+pub mod disk;
 pub mod errors;
+pub mod eta;
 pub mod file2dl;
 mod lib;
+pub mod limiter;
 pub mod metadata;
+pub mod net;
+pub mod stats;
+pub mod storage;
+pub mod store;
+pub mod timeouts;
+pub mod tls;
 pub mod url;
+pub mod virustotal;
@@ -0,0 +1,203 @@
+use crate::dl::store::{HistoryEntry, Outcome};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub const ALL: [Self; 3] = [Self::Day, Self::Week, Self::Month];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Day => "Per day",
+            Self::Week => "Per week",
+            Self::Month => "Per month",
+        }
+    }
+
+    //first day of the period the date falls in, weeks start on monday
+    fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            Self::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn format(&self, start: NaiveDate) -> String {
+        match self {
+            Self::Day => start.format("%Y-%m-%d").to_string(),
+            Self::Week => start.format("Week of %Y-%m-%d").to_string(),
+            Self::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Totals {
+    pub bytes: usize,
+    pub count: usize,
+    //seconds spent transferring
+    pub duration: u64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &HistoryEntry) {
+        self.bytes += entry.size;
+        self.count += 1;
+        self.duration += entry.duration;
+    }
+
+    pub fn average_speed(&self) -> usize {
+        self.bytes / self.duration.max(1) as usize
+    }
+}
+
+//deleted entries are left out so a file that finished and was later removed isn't counted twice
+fn completed(entries: &[HistoryEntry]) -> impl Iterator<Item = &HistoryEntry> {
+    entries
+        .iter()
+        .filter(|entry| entry.outcome == Outcome::Completed)
+}
+
+pub fn overall(entries: &[HistoryEntry]) -> Totals {
+    let mut totals = Totals::default();
+    completed(entries).for_each(|entry| totals.add(entry));
+    totals
+}
+
+//oldest period first, periods without any downloads are skipped
+pub fn per_period(entries: &[HistoryEntry], period: Period) -> Vec<(String, Totals)> {
+    let mut periods: HashMap<NaiveDate, Totals> = HashMap::new();
+    for entry in completed(entries) {
+        let Some(finished) = DateTime::from_timestamp(entry.finished_at, 0) else {
+            continue;
+        };
+        let start = period.start_of(finished.with_timezone(&Local).date_naive());
+        periods.entry(start).or_default().add(entry);
+    }
+    let mut periods = periods.into_iter().collect::<Vec<_>>();
+    periods.sort_by_key(|(start, _)| *start);
+    periods
+        .into_iter()
+        .map(|(start, totals)| (period.format(start), totals))
+        .collect()
+}
+
+//biggest host first
+pub fn per_host(entries: &[HistoryEntry]) -> Vec<(String, Totals)> {
+    let mut hosts: HashMap<String, Totals> = HashMap::new();
+    for entry in completed(entries) {
+        let host = url::Url::parse(&entry.link)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+            .unwrap_or_else(|| String::from("unknown"));
+        hosts.entry(host).or_default().add(entry);
+    }
+    let mut hosts = hosts.into_iter().collect::<Vec<_>>();
+    hosts.sort_by(|(a_host, a), (b_host, b)| b.bytes.cmp(&a.bytes).then(a_host.cmp(b_host)));
+    hosts
+}
+
+#[derive(Debug, Serialize)]
+struct Row {
+    kind: &'static str,
+    name: String,
+    downloads: usize,
+    bytes: usize,
+    seconds: u64,
+    average_speed: usize,
+}
+
+impl Row {
+    fn new(kind: &'static str, name: String, totals: Totals) -> Self {
+        Self {
+            kind,
+            name,
+            downloads: totals.count,
+            bytes: totals.bytes,
+            seconds: totals.duration,
+            average_speed: totals.average_speed(),
+        }
+    }
+}
+
+//every grouping the statistics window offers, flattened into one list
+fn report(entries: &[HistoryEntry]) -> Vec<Row> {
+    let mut rows = vec![Row::new("total", String::from("all"), overall(entries))];
+    let periods = [
+        ("day", Period::Day),
+        ("week", Period::Week),
+        ("month", Period::Month),
+    ];
+    for (kind, period) in periods {
+        rows.extend(
+            per_period(entries, period)
+                .into_iter()
+                .map(|(name, totals)| Row::new(kind, name, totals)),
+        );
+    }
+    rows.extend(
+        per_host(entries)
+            .into_iter()
+            .map(|(name, totals)| Row::new("host", name, totals)),
+    );
+    rows
+}
+
+pub fn statistics_json(entries: &[HistoryEntry]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&report(entries))
+}
+
+pub fn statistics_csv(entries: &[HistoryEntry]) -> String {
+    let mut csv = String::from("kind,name,downloads,bytes,seconds,average_speed\n");
+    for row in report(entries) {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.kind,
+            csv_field(&row.name),
+            row.downloads,
+            row.bytes,
+            row.seconds,
+            row.average_speed
+        ));
+    }
+    csv
+}
+
+pub fn history_csv(entries: &[HistoryEntry]) -> String {
+    let mut csv =
+        String::from("finished_at,outcome,name_on_disk,link,size,seconds,average_speed\n");
+    for entry in entries {
+        let finished = DateTime::from_timestamp(entry.finished_at, 0)
+            .map(|at| at.with_timezone(&Local).to_rfc3339())
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{:?},{},{},{},{},{}\n",
+            finished,
+            entry.outcome,
+            csv_field(&entry.name_on_disk),
+            csv_field(&entry.link),
+            entry.size,
+            entry.duration,
+            entry.average_speed()
+        ));
+    }
+    csv
+}
+
+//quotes fields that would otherwise break the row apart
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    fs::{copy, create_dir_all, remove_file, rename},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+#[cfg(target_os = "linux")]
+const APP_DIR: &str = "rusty-dl-manager";
+#[cfg(not(target_os = "linux"))]
+const APP_DIR: &str = "Rusty Dl Manager";
+
+//where settings, the database and the rest of the app's state live, apart from the downloads
+static DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+//xdg data home on linux, roaming appdata on windows, application support on macos
+pub fn platform_data_dir() -> Option<PathBuf> {
+    let dir = dirs_next::data_dir()?.join(APP_DIR);
+    create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+pub fn set_data_dir(dir: PathBuf) {
+    if let Ok(mut locked) = DATA_DIR.write() {
+        *locked = Some(dir);
+    }
+}
+
+//without a data dir (portable mode) state files sit in the working dir
+pub fn data_file(name: &str) -> PathBuf {
+    match DATA_DIR.read().ok().and_then(|locked| locked.clone()) {
+        Some(dir) => dir.join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+//older versions kept their state in the working dir, it's moved over on the first start,
+//a file that's already in the data dir is never overwritten
+pub fn migrate_state_files(names: &[&str]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for name in names {
+        let old = Path::new(name);
+        let new = data_file(name);
+        if !old.is_file() || new.exists() {
+            continue;
+        }
+        if let Err(e) = move_file(old, &new) {
+            problems.push(format!(
+                "Couldn't move {} to {}: {}",
+                name,
+                new.display(),
+                e
+            ));
+        }
+    }
+    problems
+}
+
+//rename fails across drives, so it falls back to copying
+fn move_file(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy(from, to)?;
+    remove_file(from)
+}
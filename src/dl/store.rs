@@ -0,0 +1,200 @@
+use crate::dl::{disk::normalize_dir, storage::data_file};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::{LazyLock, Mutex},
+};
+
+const DB_FILE: &str = "downloads.db";
+//the database along with its write ahead log
+pub const DB_FILES: [&str; 3] = [DB_FILE, "downloads.db-wal", "downloads.db-shm"];
+
+//opened on first use and shared by the ui and the transfer tasks
+static STORE: LazyLock<Mutex<Option<Connection>>> = LazyLock::new(Mutex::default);
+
+fn open() -> rusqlite::Result<Connection> {
+    let conn = Connection::open(data_file(DB_FILE))?;
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+        CREATE TABLE IF NOT EXISTS downloads (
+            dl_dir TEXT NOT NULL,
+            name_on_disk TEXT NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (dl_dir, name_on_disk)
+        );
+        CREATE TABLE IF NOT EXISTS history (
+            id INTEGER PRIMARY KEY,
+            link TEXT NOT NULL,
+            name_on_disk TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            duration INTEGER NOT NULL,
+            finished_at INTEGER NOT NULL,
+            outcome TEXT NOT NULL
+        );",
+    )?;
+    Ok(conn)
+}
+
+fn with_store<T>(
+    task: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+) -> Result<T, std::io::Error> {
+    let mut locked = STORE.lock().unwrap_or_else(|e| e.into_inner());
+    let conn = match &mut *locked {
+        Some(conn) => conn,
+        slot @ None => slot.insert(open().map_err(std::io::Error::other)?),
+    };
+    task(conn).map_err(std::io::Error::other)
+}
+
+//data is the record's metadata json, versioned the same way the .metadl files were
+pub fn save_record(dl_dir: &str, name_on_disk: &str, data: &str) -> Result<(), std::io::Error> {
+    with_store(|conn| {
+        conn.execute(
+            "INSERT INTO downloads (dl_dir, name_on_disk, data) VALUES (?1, ?2, ?3)
+            ON CONFLICT (dl_dir, name_on_disk) DO UPDATE SET data = excluded.data",
+            params![dl_dir, name_on_disk, data],
+        )?;
+        Ok(())
+    })
+}
+
+//used when importing, a record that's already stored is newer than the file and wins
+pub fn insert_record(dl_dir: &str, name_on_disk: &str, data: &str) -> Result<(), std::io::Error> {
+    with_store(|conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO downloads (dl_dir, name_on_disk, data) VALUES (?1, ?2, ?3)",
+            params![dl_dir, name_on_disk, data],
+        )?;
+        Ok(())
+    })
+}
+
+pub fn remove_record(dl_dir: &str, name_on_disk: &str) -> Result<(), std::io::Error> {
+    with_store(|conn| {
+        conn.execute(
+            "DELETE FROM downloads WHERE dl_dir = ?1 AND name_on_disk = ?2",
+            params![dl_dir, name_on_disk],
+        )?;
+        Ok(())
+    })
+}
+
+//records stored under another spelling of the folder, relative from older versions or absolute from before
+//portable mode was turned on, are moved under the normalized one
+pub fn adopt_records(dl_dir: &str) -> Result<(), std::io::Error> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare("SELECT DISTINCT dl_dir FROM downloads")?;
+        let dirs = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for old in dirs
+            .iter()
+            .filter(|dir| *dir != dl_dir && Path::new(&normalize_dir(dir)) == Path::new(dl_dir))
+        {
+            conn.execute(
+                "UPDATE OR IGNORE downloads SET dl_dir = ?2 WHERE dl_dir = ?1",
+                params![old, dl_dir],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+//name on disk and metadata json of every download in the folder
+pub fn records(dl_dir: &str) -> Result<Vec<(String, String)>, std::io::Error> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT name_on_disk, data FROM downloads WHERE dl_dir = ?1 ORDER BY name_on_disk",
+        )?;
+        let rows = stmt.query_map(params![dl_dir], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    Completed,
+    Deleted,
+}
+
+impl Outcome {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Completed => "completed",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    fn parse(text: &str) -> Self {
+        match text {
+            "deleted" => Self::Deleted,
+            _ => Self::Completed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub link: String,
+    pub name_on_disk: String,
+    pub size: usize,
+    //seconds from the first request to completion or deletion
+    pub duration: u64,
+    //unix time in seconds
+    pub finished_at: i64,
+    pub outcome: Outcome,
+}
+
+impl HistoryEntry {
+    pub fn average_speed(&self) -> usize {
+        self.size / self.duration.max(1) as usize
+    }
+
+    //case insensitive match on the name or the url
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.name_on_disk.to_lowercase().contains(&query)
+            || self.link.to_lowercase().contains(&query)
+    }
+}
+
+pub fn add_history(entry: &HistoryEntry) -> Result<(), std::io::Error> {
+    with_store(|conn| {
+        conn.execute(
+            "INSERT INTO history (link, name_on_disk, size, duration, finished_at, outcome)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                entry.link,
+                entry.name_on_disk,
+                entry.size as i64,
+                entry.duration as i64,
+                entry.finished_at,
+                entry.outcome.as_str()
+            ],
+        )?;
+        Ok(())
+    })
+}
+
+//newest first
+pub fn history() -> Result<Vec<HistoryEntry>, std::io::Error> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT link, name_on_disk, size, duration, finished_at, outcome
+            FROM history ORDER BY finished_at DESC, id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(HistoryEntry {
+                link: row.get(0)?,
+                name_on_disk: row.get(1)?,
+                size: row.get::<_, i64>(2)? as usize,
+                duration: row.get::<_, i64>(3)? as u64,
+                finished_at: row.get(4)?,
+                outcome: Outcome::parse(&row.get::<_, String>(5)?),
+            })
+        })?;
+        rows.collect()
+    })
+}
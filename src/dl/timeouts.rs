@@ -0,0 +1,80 @@
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+use std::{sync::RwLock, time::Duration};
+
+static GLOBAL_TIMEOUTS: RwLock<Timeouts> = RwLock::new(Timeouts::DEFAULT);
+
+//all values are in seconds, 0 disables the timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeouts {
+    pub connect_secs: u64,
+    pub read_secs: u64,
+    pub stall_secs: u64,
+}
+
+impl Timeouts {
+    pub const DEFAULT: Self = Self {
+        connect_secs: 15,
+        read_secs: 60,
+        stall_secs: 30,
+    };
+
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if self.connect_secs > 0 {
+            builder = builder.connect_timeout(Duration::from_secs(self.connect_secs));
+        }
+        if self.read_secs > 0 {
+            builder = builder.read_timeout(Duration::from_secs(self.read_secs));
+        }
+        builder
+    }
+
+    pub fn stall(&self) -> Option<Duration> {
+        (self.stall_secs > 0).then(|| Duration::from_secs(self.stall_secs))
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub fn set_global_timeouts(timeouts: Timeouts) {
+    if let Ok(mut locked) = GLOBAL_TIMEOUTS.write() {
+        *locked = timeouts;
+    }
+}
+
+pub fn global_timeouts() -> Timeouts {
+    GLOBAL_TIMEOUTS
+        .read()
+        .map(|locked| *locked)
+        .unwrap_or_default()
+}
+
+//downloads without their own timeouts follow the settings
+pub fn resolve_timeouts(own: Option<Timeouts>) -> Timeouts {
+    own.unwrap_or_else(global_timeouts)
+}
+
+//parses connect, read and stall fields from the ui, empty fields keep the fallback's value
+pub fn parse_timeouts(
+    fields: [&str; 3],
+    fallback: Timeouts,
+) -> Result<Timeouts, std::num::ParseIntError> {
+    let parse = |field: &str, default: u64| {
+        let field = field.trim();
+        if field.is_empty() {
+            Ok(default)
+        } else {
+            field.parse::<u64>()
+        }
+    };
+    Ok(Timeouts {
+        connect_secs: parse(fields[0], fallback.connect_secs)?,
+        read_secs: parse(fields[1], fallback.read_secs)?,
+        stall_secs: parse(fields[2], fallback.stall_secs)?,
+    })
+}
@@ -0,0 +1,30 @@
+use reqwest::{Certificate, ClientBuilder};
+use serde::{Deserialize, Serialize};
+use std::{fs::read, path::Path};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TlsOptions {
+    pub ca_cert: String,
+    pub accept_invalid_certs: bool,
+}
+
+impl TlsOptions {
+    //adds the custom root certificate (PEM or DER) and the invalid certificate override to a client
+    pub fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder, std::io::Error> {
+        let mut builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        let ca_cert = self.ca_cert.trim();
+        if !ca_cert.is_empty() {
+            let bytes = read(Path::new(ca_cert))?;
+            let cert = Certificate::from_pem(&bytes)
+                .or_else(|_| Certificate::from_der(&bytes))
+                .map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid CA certificate {}: {}", ca_cert, e),
+                    )
+                })?;
+            builder = builder.add_root_certificate(cert);
+        }
+        Ok(builder)
+    }
+}
@@ -1,18 +1,23 @@
-use std::time::Duration;
-
-use super::errors::UrlError;
+use super::{
+    browser::BrowserContext,
+    errors::UrlError,
+    net::{shared_client, track_redirects},
+    timeouts::global_timeouts,
+    tls::TlsOptions,
+};
 use content_disposition::parse_content_disposition;
+use percent_encoding::percent_decode_str;
 use regex::Regex;
 use reqwest::{
     header::{
-        HeaderMap, ACCEPT_RANGES, CONNECTION, CONTENT_DISPOSITION, CONTENT_LENGTH, RANGE,
-        USER_AGENT,
+        HeaderMap, HeaderName, ACCEPT_RANGES, CONNECTION, CONTENT_DISPOSITION, CONTENT_LENGTH,
+        ETAG, LAST_MODIFIED, RANGE,
     },
-    Client, ClientBuilder,
+    Client,
 };
+use unicode_normalization::UnicodeNormalization;
 
-const FILENAME_RE: &str = r#"^[\w\s,-]+(\.[\w-]+)*\.[A-Za-z0-9]{2,4}$"#;
-const CHROME_AGENT: &str = r#"Mozilla/5.0 (Windows; U; Windows NT 10.5; Win64; x64; en-US) AppleWebKit/537.33 (KHTML, like Gecko) Chrome/50.0.2124.268 Safari/536"#;
+const FILENAME_RE: &str = r#"^[^.\s].*\.[\p{L}\p{N}_-]{1,16}$"#;
 
 #[derive(Debug, Default, Clone)]
 pub struct Url {
@@ -20,35 +25,43 @@ pub struct Url {
     pub filename: String,
     pub content_length: usize,
     pub range_support: bool,
+    //validators used to tell whether the remote file changed, empty when the server sent none
+    pub last_modified: String,
+    pub etag: String,
+    //what the probe saw this session, empty for files loaded from the store
+    pub redirects: Vec<String>,
+    pub headers: Vec<(String, String)>,
 }
 
 impl Url {
-    pub async fn new(link: &str) -> Result<Self, UrlError> {
-        //self explanatory
-        if url::Url::parse(link).is_err() {
-            return Err(UrlError::InvalidUrl);
-        }
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(15))
-            .build()?;
-        let head_request = client
-            .head(link)
-            .header(USER_AGENT, CHROME_AGENT)
-            .header(CONNECTION, "keep-alive")
-            .send()
-            .await;
-        let res = {
-            if let Ok(r) = head_request {
-                r
-            } else {
-                client
-                    .get(link)
-                    .header(USER_AGENT, CHROME_AGENT)
-                    .header(CONNECTION, "keep-alive")
-                    .send()
-                    .await?
+    //probes the way the browser that sent the link would have asked
+    pub async fn with_browser(
+        link: &str,
+        tls: &TlsOptions,
+        browser: &BrowserContext,
+    ) -> Result<Self, UrlError> {
+        Self::is_valid_url(link)?;
+        //the probe asks for the identity encoding so the content length is the size that ends up on disk
+        let client = shared_client::<UrlError>(link, tls, global_timeouts(), false)?;
+        let (res, redirects) = track_redirects(async {
+            let head_request = browser
+                .apply(client.head(link))
+                .header(CONNECTION, "keep-alive")
+                .send()
+                .await;
+            match head_request {
+                Ok(r) => Ok(r),
+                Err(_) => {
+                    browser
+                        .apply(client.get(link))
+                        .header(CONNECTION, "keep-alive")
+                        .send()
+                        .await
+                }
             }
-        };
+        })
+        .await;
+        let res = res?;
         let headers = res.headers().to_owned();
         drop(res);
         //parses content length header else content length is 0
@@ -60,21 +73,47 @@ impl Url {
         //if header accept ranges exist then there is range support , else manually try a request with range
         let range_support = headers
             .accept_ranges()
-            .unwrap_or(manual_range_test(&client, link).await);
+            .unwrap_or(manual_range_test(&client, link, browser).await);
         let link = link.to_owned();
         Ok(Self {
             link,
             filename,
             content_length,
             range_support,
+            last_modified: headers.text(LAST_MODIFIED).unwrap_or_default(),
+            etag: headers.text(ETAG).unwrap_or_default(),
+            redirects,
+            headers: headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+                    (name.to_string(), value)
+                })
+                .collect(),
         })
     }
 }
 
+impl Url {
+    //accepts any http(s) url with a host, ports, ip literals and query strings included
+    pub fn is_valid_url(link: &str) -> Result<url::Url, UrlError> {
+        let parsed = url::Url::parse(link.trim())?;
+        match parsed.scheme() {
+            "http" | "https" => {}
+            scheme => return Err(UrlError::UnsupportedScheme(scheme.to_string())),
+        }
+        if parsed.host().is_none() {
+            return Err(UrlError::MissingHost);
+        }
+        Ok(parsed)
+    }
+}
+
 pub trait ParseHeaders {
     fn content_length(&self) -> Option<usize>;
     fn accept_ranges(&self) -> Option<bool>;
     fn content_dispo(&self) -> Option<String>;
+    fn text(&self, name: HeaderName) -> Option<String>;
 }
 
 impl ParseHeaders for HeaderMap {
@@ -97,28 +136,86 @@ impl ParseHeaders for HeaderMap {
         //parses content disposition from header
         let content_dispo = self.get(CONTENT_DISPOSITION)?;
         let header_value = content_dispo.to_str().unwrap_or_default();
+        //the extended filename* parameter takes precedence over the plain one (RFC 6266)
+        if let Some(fname) = parse_ext_filename(header_value) {
+            return Some(fname);
+        }
         let dis = parse_content_disposition(header_value);
         if let Some(fname) = dis.filename_full() {
             return Some(fname);
         }
         None
     }
+    fn text(&self, name: HeaderName) -> Option<String> {
+        let value = self.get(name)?.to_str().ok()?.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    }
+}
+
+//lists what differs between the stored info and a fresh probe, validators the old side lacks are ignored
+pub fn remote_changes(stored: &Url, probed: &Url) -> Vec<String> {
+    let mut changes = Vec::new();
+    if !stored.etag.is_empty() && stored.etag != probed.etag {
+        changes.push(String::from("ETag changed"));
+    }
+    if !stored.last_modified.is_empty() && stored.last_modified != probed.last_modified {
+        changes.push(format!("modified {}", probed.last_modified));
+    }
+    if stored.content_length > 0 && stored.content_length != probed.content_length {
+        changes.push(format!(
+            "size went from {} to {} bytes",
+            stored.content_length, probed.content_length
+        ));
+    }
+    changes
+}
+
+pub fn parse_ext_filename(header_value: &str) -> Option<String> {
+    //parses an RFC 5987 value such as filename*=UTF-8'en'%E2%82%AC%20rates.pdf
+    let value = header_value.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("filename*")
+            .then(|| value.trim().trim_matches('"'))
+    })?;
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?;
+    let bytes = percent_decode_str(encoded).collect::<Vec<u8>>();
+    let name = if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()?
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        //latin-1 bytes map one to one onto the first 256 code points
+        bytes.into_iter().map(char::from).collect()
+    } else {
+        return None;
+    };
+    let name = name.trim().nfc().collect::<String>();
+    if name.is_empty() {
+        return None;
+    }
+    Some(name)
 }
 
-async fn manual_range_test(client: &Client, link: &str) -> bool {
+async fn manual_range_test(client: &Client, link: &str, browser: &BrowserContext) -> bool {
     //test range support through sending a ranged request (fallback for the header parse)
-    match client.get(link).header(RANGE, "bytes=0-1").send().await {
+    let request = browser.apply(client.get(link)).header(RANGE, "bytes=0-1");
+    match request.send().await {
         Ok(res) => res.bytes().await.map_or(false, |bytes| bytes.len() == 1),
         Err(_) => false,
     }
 }
 
-fn parse_name_from_url(link: &str) -> Option<String> {
-    //self explanatory
-    let last_segment = link.split('/').last()?.trim();
+pub fn parse_name_from_url(link: &str) -> Option<String> {
+    //takes the last path segment (query and fragment excluded), decodes it and normalizes it to NFC
+    let parsed = url::Url::parse(link).ok()?;
+    let last_segment = parsed.path_segments()?.next_back()?;
+    let decoded = percent_decode_str(last_segment).decode_utf8_lossy();
+    let name = decoded.trim().nfc().collect::<String>();
     let re = Regex::new(FILENAME_RE).expect("Invalid filename regex");
-    if re.is_match(last_segment) {
-        return Some(last_segment.to_string());
+    if re.is_match(&name) {
+        return Some(name);
     }
     None
 }
@@ -0,0 +1,51 @@
+use super::{errors::ScanError, net::build_client, tls::TlsOptions};
+use reqwest::{Client, StatusCode};
+use std::time::Duration;
+
+const FILES_URL: &str = "https://www.virustotal.com/api/v3/files/";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanVerdict {
+    Clean,
+    Flagged { malicious: u64, suspicious: u64 },
+    //virustotal has never seen the file
+    Unknown,
+}
+
+//only the hash is sent, the file itself never leaves the machine
+pub async fn lookup_sha256(sha256: &str, api_key: &str) -> Result<ScanVerdict, ScanError> {
+    let client = build_client(
+        Client::builder().timeout(Duration::from_secs(30)),
+        &TlsOptions::default(),
+    )?
+    .build()?;
+    let res = client
+        .get(format!("{}{}", FILES_URL, sha256))
+        .header("x-apikey", api_key)
+        .send()
+        .await?;
+    match res.status() {
+        StatusCode::NOT_FOUND => Ok(ScanVerdict::Unknown),
+        status if status.is_success() => {
+            let report = serde_json::from_slice(&res.bytes().await?)?;
+            Ok(parse_report(&report))
+        }
+        status => Err(ScanError::HttpStatus(status.as_u16())),
+    }
+}
+
+pub fn parse_report(report: &serde_json::Value) -> ScanVerdict {
+    let stats = &report["data"]["attributes"]["last_analysis_stats"];
+    let malicious = stats["malicious"].as_u64().unwrap_or_default();
+    let suspicious = stats["suspicious"].as_u64().unwrap_or_default();
+    if malicious > 0 || suspicious > 0 {
+        ScanVerdict::Flagged {
+            malicious,
+            suspicious,
+        }
+    } else if stats.is_object() {
+        ScanVerdict::Clean
+    } else {
+        ScanVerdict::Unknown
+    }
+}
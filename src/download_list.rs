@@ -0,0 +1,208 @@
+use crate::{
+    dl::{
+        browser::BrowserContext,
+        disk::normalize_dir,
+        file2dl::{generate_name_on_disk, File2Dl},
+        timeouts::Timeouts,
+        tls::TlsOptions,
+        url::Url,
+    },
+    FDl,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering::Relaxed;
+use std::{
+    fs::{create_dir_all, metadata, File},
+    io::Read,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicUsize},
+        Arc, Mutex,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum ListState {
+    Running,
+    Paused,
+    Complete,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListEntry {
+    pub link: String,
+    pub url_name: String,
+    pub dl_dir: String,
+    pub name_on_disk: String,
+    pub content_length: usize,
+    pub size_on_disk: usize,
+    pub range_support: bool,
+    pub speed: usize,
+    pub state: ListState,
+    #[serde(default)]
+    pub page_title: String,
+    #[serde(default)]
+    pub page_url: String,
+    #[serde(default)]
+    pub refresh_hours: u64,
+    #[serde(default)]
+    pub tls: TlsOptions,
+    #[serde(default)]
+    pub timeouts: Option<Timeouts>,
+    #[serde(default)]
+    pub checksum: String,
+    #[serde(default)]
+    pub verify: bool,
+    #[serde(default)]
+    pub last_modified: String,
+    #[serde(default)]
+    pub etag: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub added_at: i64,
+    #[serde(default)]
+    pub position: i64,
+}
+
+impl From<&File2Dl> for ListEntry {
+    fn from(f: &File2Dl) -> Self {
+        let state = if f.complete.load(Relaxed) {
+            ListState::Complete
+        } else if f.cancelled.load(Relaxed) {
+            ListState::Cancelled
+        } else if f.running.load(Relaxed) {
+            ListState::Running
+        } else {
+            ListState::Paused
+        };
+        Self {
+            link: f.url.link.clone(),
+            url_name: f.url.filename.clone(),
+            dl_dir: f.dl_dir.clone(),
+            name_on_disk: f.name_on_disk.clone(),
+            content_length: f.url.content_length,
+            size_on_disk: f.size_on_disk.load(Relaxed),
+            range_support: f.url.range_support,
+            speed: f.speed.load(Relaxed),
+            state,
+            page_title: f.page_title.clone(),
+            page_url: f.page_url.clone(),
+            refresh_hours: f.refresh_hours,
+            tls: f.tls.clone(),
+            timeouts: f.timeouts,
+            checksum: f.checksum.clone(),
+            verify: f.verify,
+            last_modified: f.url.last_modified.clone(),
+            etag: f.url.etag.clone(),
+            tags: f.tags(),
+            note: f.note(),
+            added_at: f.added_at,
+            position: f.position.load(Relaxed),
+        }
+    }
+}
+
+impl ListEntry {
+    //rebuilds the file from an entry, falling back to the current download dir when the exported one doesn't exist on this machine
+    fn into_file(self, fallback_dir: &str) -> Result<File2Dl, std::io::Error> {
+        let dl_dir = if Path::new(&self.dl_dir).is_dir() {
+            normalize_dir(&self.dl_dir)
+        } else {
+            normalize_dir(fallback_dir)
+        };
+        if !Path::new(&dl_dir).exists() {
+            create_dir_all(&dl_dir)?;
+        }
+        let mut size_on_disk = {
+            let file_path = Path::new(&dl_dir).join(&self.name_on_disk);
+            if file_path.exists() {
+                metadata(&file_path)?.len() as usize
+            } else {
+                0
+            }
+        };
+        let mut complete = self.content_length > 0 && size_on_disk == self.content_length;
+        let name_on_disk = if self.range_support || size_on_disk == 0 || complete {
+            self.name_on_disk
+        } else {
+            //partial files that can't be resumed are restarted under a fresh name
+            size_on_disk = 0;
+            complete = false;
+            generate_name_on_disk(&self.name_on_disk, &dl_dir)?
+        };
+        let running = !complete && self.state == ListState::Running;
+        Ok(File2Dl {
+            url: Url {
+                link: self.link,
+                filename: self.url_name,
+                content_length: self.content_length,
+                range_support: self.range_support,
+                last_modified: self.last_modified,
+                etag: self.etag,
+                ..Default::default()
+            },
+            name_on_disk,
+            speed: Arc::new(AtomicUsize::new(self.speed)),
+            size_on_disk: Arc::new(AtomicUsize::new(size_on_disk)),
+            dl_dir,
+            bytes_per_sec: Arc::new(AtomicUsize::new(0)),
+            smoothed_speed: Arc::new(AtomicUsize::new(0)),
+            running: Arc::new(AtomicBool::new(running)),
+            complete: Arc::new(AtomicBool::new(complete)),
+            failed: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(
+                !complete && self.state == ListState::Cancelled,
+            )),
+            page_title: self.page_title,
+            page_url: self.page_url,
+            refresh_hours: self.refresh_hours,
+            tls: self.tls,
+            //the browser's cookies aren't exported with the list
+            browser: BrowserContext::default(),
+            timeouts: self.timeouts,
+            checksum: self.checksum,
+            verify: self.verify,
+            verify_failed: Arc::new(AtomicBool::new(false)),
+            protocol: Arc::default(),
+            tags: Arc::new(Mutex::new(self.tags)),
+            note: Arc::new(Mutex::new(self.note)),
+            added_at: self.added_at,
+            position: Arc::new(AtomicI64::new(match self.position {
+                0 => self.added_at,
+                position => position,
+            })),
+            queued: Arc::new(AtomicBool::new(false)),
+        })
+    }
+}
+
+pub fn list_entries(files: &[FDl]) -> Vec<ListEntry> {
+    files.iter().map(|f| ListEntry::from(&f.file)).collect()
+}
+
+pub fn files_from_entries(
+    entries: Vec<ListEntry>,
+    fallback_dir: &str,
+) -> Result<Vec<File2Dl>, std::io::Error> {
+    entries
+        .into_iter()
+        .map(|entry| entry.into_file(fallback_dir))
+        .collect()
+}
+
+pub fn export_list(files: &[FDl], path: &Path) -> Result<(), std::io::Error> {
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, &list_entries(files))?;
+    Ok(())
+}
+
+pub fn import_list(path: &Path, fallback_dir: &str) -> Result<Vec<File2Dl>, std::io::Error> {
+    let mut buf = String::new();
+    File::open(path)?.read_to_string(&mut buf)?;
+    let entries: Vec<ListEntry> = serde_json::from_str(&buf)?;
+    files_from_entries(entries, fallback_dir)
+}
@@ -1,51 +1,49 @@
 use chrono::Local;
-use notify_rust::Notification;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    colors::{GREEN, RED},
+    cancel::remove_discarded,
+    colors::{CYAN, GREEN, RED},
+    content_index::index_file,
+    dl::{file2dl::File2Dl, store::Outcome},
+    extract::{extract_archive, is_archive},
+    history::record_history,
+    i18n::{tr, trf},
+    intercept::probe_intercepted,
+    notifications::notify,
+    queue::queue_key,
+    scan::scan_file,
     server::interception::SERVER_STATE,
-    DownloadManager,
+    sound::play_completion_sound,
+    taskbar::flash_taskbar,
+    verification::{is_disk_image, verify_checksum, verify_disk_image},
+    DownloadManager, FDl,
 };
-use std::{
-    thread::sleep,
-    time::{Duration, Instant},
-};
-
-trait ConsumingIterator<T> {
-    fn next(&mut self) -> Option<T>;
-}
-
-impl<T> ConsumingIterator<T> for Vec<T> {
-    fn next(&mut self) -> Option<T> {
-        if !self.is_empty() {
-            Some(self.remove(0))
-        } else {
-            None
-        }
-    }
-}
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
+//every link is probed right away, the confirmation popup takes them one at a time
 pub fn check_urls(interface: &mut DownloadManager) {
-    if let Ok(mut locked) = SERVER_STATE.try_lock() {
-        let mut links = locked.clone();
-
-        if !interface.popups.download.show {
-            if let Some(link) = links.next() {
-                interface.popups.download.link = link.clone();
-                let now = Local::now();
-                let formatted_time = now.format("%H:%M:%S").to_string();
-                let text = format!("Received link from server:{}", &link);
-                interface
-                    .popups
-                    .log
-                    .logs
-                    .push((formatted_time, text, *GREEN));
-                interface.popups.download.show = true;
-                interface.show_window = true;
-
-                *locked = links;
-            }
-        }
+    let links = match SERVER_STATE.try_lock() {
+        Ok(mut locked) => std::mem::take(&mut *locked),
+        Err(_) => return,
+    };
+    for intercepted in links {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = if intercepted.page_title.is_empty() {
+            format!("Received link from server:{}", &intercepted.link)
+        } else {
+            format!(
+                "Received link from server:{} ({})",
+                &intercepted.link, &intercepted.page_title
+            )
+        };
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time, text, *GREEN));
+        probe_intercepted(interface, intercepted);
     }
 }
 
@@ -60,53 +58,132 @@ pub fn set_total_bandwidth(interface: &mut DownloadManager) {
         })
         .sum();
     interface.bandwidth.total_bandwidth = size;
-    update_bandwidth_history(interface);
 }
-fn update_bandwidth_history(interface: &mut DownloadManager) {
-    interface
-        .bandwidth
-        .history
-        .push(interface.bandwidth.total_bandwidth);
-    if interface.bandwidth.history.len() > 100 {
-        interface.bandwidth.history.remove(0);
+//queued downloads wait for everything else to finish, then go one at a time in queue order
+fn start_queued(files: &mut [FDl]) {
+    let running = |fdl: &FDl| {
+        !fdl.file.complete.load(std::sync::atomic::Ordering::Relaxed)
+            && fdl.file.running.load(std::sync::atomic::Ordering::Relaxed)
+    };
+    //started by hand before its turn came
+    let queued = |fdl: &FDl| fdl.file.queued.load(std::sync::atomic::Ordering::Relaxed);
+    for fdl in files.iter().filter(|f| queued(f) && running(f)) {
+        fdl.file
+            .queued
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+    if files.iter().any(running) {
+        return;
+    }
+    let next = (0..files.len())
+        .filter(|&idx| queued(&files[idx]))
+        .min_by(|&a, &b| queue_key(&files[a]).cmp(&queue_key(&files[b])));
+    if let Some(idx) = next {
+        files[idx]
+            .file
+            .queued
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+        files[idx].file.toggle_status();
     }
 }
+
 pub fn run_downloads(interface: &mut DownloadManager) {
+    start_queued(&mut interface.files);
     let retry_interval = interface.settings.retry_interval;
     let now = Local::now();
     let formatted_time = now.format("%H:%M:%S").to_string();
+    let mut to_verify = Vec::new();
+    let mut to_extract = Vec::new();
+    let mut to_scan = Vec::new();
+    let mut to_index = Vec::new();
+    let mut finished = 0;
     for fdl in interface.files.iter_mut() {
         let file = &fdl.file;
         let complete = file.complete.load(std::sync::atomic::Ordering::Relaxed);
         let new = fdl.new;
 
+        if complete && fdl.completed_at.is_none() {
+            fdl.completed_at = Some(Instant::now());
+            to_index.push(file.clone());
+            if !fdl.initial_status {
+                record_history(&mut interface.popups.log, fdl, Outcome::Completed);
+                finished += 1;
+            }
+            if !fdl.initial_status
+                && ((file.verify && !file.checksum.is_empty()) || is_disk_image(&file.name_on_disk))
+            {
+                to_verify.push(file.clone());
+            }
+            if !fdl.initial_status
+                && interface.settings.auto_extract
+                && is_archive(&file.name_on_disk)
+            {
+                to_extract.push(file.clone());
+            }
+            if !fdl.initial_status && !interface.settings.virustotal_key.is_empty() {
+                to_scan.push(file.clone());
+            }
+        }
+
         if complete && !fdl.initial_status && !fdl.got_notif {
             if interface.show_window {
                 fdl.got_notif = true;
+                let text = trf("{} finished downloading", &[&file.name_on_disk]);
+                interface.toasts.push(text, *GREEN);
             } else {
-                let text = format!("{} finished downloading", &file.name_on_disk);
+                let text = trf("{} finished downloading", &[&file.name_on_disk]);
                 fdl.got_notif = true;
-                let noti = Notification::new()
-                    .summary("Download complete")
-                    .body(&text)
-                    .icon("/home/numerouscuts/Coding/final-dl-manager/icon.png")
-                    .show();
-                if let Err(e) = noti {
-                    let text = format!("Notification error: {:?}", e);
-                    interface
-                        .popups
-                        .log
-                        .logs
-                        .push((formatted_time.clone(), text, *RED));
-                }
+                notify(
+                    &interface.notifications,
+                    tr("Download complete"),
+                    text,
+                    &file.key(),
+                );
             }
         }
 
+        if complete
+            || file
+                .bytes_per_sec
+                .load(std::sync::atomic::Ordering::Relaxed)
+                > 0
+        {
+            fdl.error = None;
+        }
+
+        if file.failed.load(std::sync::atomic::Ordering::Relaxed) {
+            if file.running.load(std::sync::atomic::Ordering::Relaxed) {
+                //resumed by the user after a failure, so start a fresh attempt
+                file.failed
+                    .store(false, std::sync::atomic::Ordering::Relaxed);
+                fdl.initiated = false;
+            } else {
+                fdl.has_error = true;
+            }
+        }
+
+        if let Err(e) = remove_discarded(fdl) {
+            let text = format!("Couldn't remove partial file: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+        }
+        let file = &fdl.file;
+        let is_running = file.running.load(std::sync::atomic::Ordering::Relaxed);
+        //pausing drops the connection, resuming issues a new ranged request from size_on_disk
+        if fdl.initiated && !is_running {
+            if let Some(cancel) = &fdl.cancel {
+                let _ = cancel.send(true);
+            }
+            fdl.initiated = false;
+        }
+
         if complete || fdl.initiated {
             continue;
         }
 
-        let is_running = file.running.load(std::sync::atomic::Ordering::Relaxed);
         let speed = fdl
             .file
             .bytes_per_sec
@@ -114,6 +191,8 @@ pub fn run_downloads(interface: &mut DownloadManager) {
 
         fdl.has_error = if complete {
             false
+        } else if file.failed.load(std::sync::atomic::Ordering::Relaxed) {
+            true
         } else if is_running {
             speed == 0 && fdl.toggled_at.elapsed() >= Duration::from_secs(5)
         } else {
@@ -121,6 +200,13 @@ pub fn run_downloads(interface: &mut DownloadManager) {
             false
         };
 
+        //the aborted task has to let go of the file before a new one appends to it
+        if !is_running || fdl.task_alive() {
+            continue;
+        }
+
+        let (cancel_tx, cancel) = watch::channel(false);
+        fdl.cancel = Some(cancel_tx);
         let file = file.clone();
         let tx_error = interface.popups.error.channel.0.clone();
         let log_msg = format!("Initiating : {}", &file.url.link);
@@ -131,35 +217,66 @@ pub fn run_downloads(interface: &mut DownloadManager) {
             .push((formatted_time.clone(), log_msg, *GREEN));
         interface.runtime.spawn(async move {
             if file.url.range_support {
-                loop {
-                    match file.single_thread_dl().await {
-                        Ok(_) => break,
-                        Err(e) => {
-                            let error = format!("{}: {:?}\n", file.name_on_disk, e);
-                            tx_error.send(error).unwrap();
-                        }
-                    }
-                    sleep(Duration::from_secs(retry_interval));
-                }
+                file.dl_with_retries(cancel, Duration::from_secs(retry_interval), |e| {
+                    let _ = tx_error.send((file.key(), e.to_string()));
+                })
+                .await;
             } else if new {
-                match file.single_thread_dl().await {
+                match file.single_thread_dl(cancel).await {
                     Ok(_) => {}
                     Err(e) => {
-                        let error = format!("{}: {:?}\n", file.name_on_disk, e);
-                        tx_error.send(error).unwrap();
+                        let _ = tx_error.send((file.key(), e.to_string()));
                     }
                 }
             }
         });
 
         fdl.initiated = true;
+        fdl.started_at.get_or_insert_with(Instant::now);
+        fdl.error = None;
+    }
+    play_completion_sound(interface, finished);
+    flash_taskbar(interface, finished);
+    for file in to_verify {
+        //a checksum entered by the user wins over the published one
+        if file.verify && !file.checksum.is_empty() {
+            verify_checksum(interface, &file);
+        } else {
+            verify_disk_image(interface, &file);
+        }
     }
-    if let Ok(err) = interface.popups.error.channel.1.try_recv() {
+    for file in to_extract {
+        extract_archive(interface, &file);
+    }
+    for file in to_scan {
+        scan_file(interface, &file);
+    }
+    for file in to_index {
+        index_file(interface, &file);
+    }
+    while let Ok((key, err)) = interface.popups.error.channel.1.try_recv() {
+        let name_on_disk = &key.name_on_disk;
+        if let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(&key)) {
+            fdl.error = Some(err.clone());
+            //a failure that ends the download shows in its row, only the ones that get retried are announced
+            let file = &fdl.file;
+            if file.url.range_support && file.running.load(std::sync::atomic::Ordering::Relaxed) {
+                let text = trf(
+                    "{} failed, retrying in {}s",
+                    &[name_on_disk, &interface.settings.retry_interval],
+                );
+                interface.toasts.push(text, *CYAN);
+            } else if !interface.show_window {
+                let text = format!("{}: {}", name_on_disk, err);
+                notify(&interface.notifications, tr("Download failed"), text, &key);
+            }
+        }
+        let text = format!("{}: {}", name_on_disk, err);
         interface
             .popups
             .log
             .logs
-            .push((formatted_time.clone(), err, *RED));
+            .push((formatted_time.clone(), text, *RED));
         interface.popups.log.has_error = true;
     }
 }
@@ -172,3 +289,44 @@ pub enum Actions {
     Shutdown,
     Open,
 }
+
+//what a download does once it's added from the popup
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum StartMode {
+    #[default]
+    Now,
+    Paused,
+    Queued,
+}
+
+impl StartMode {
+    pub const ALL: [Self; 3] = [Self::Now, Self::Paused, Self::Queued];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Now => "Start now",
+            Self::Paused => "Add paused",
+            Self::Queued => "Add to queue",
+        }
+    }
+
+    //starts the new download, puts it in the queue or leaves it paused
+    pub fn apply(&self, file: &File2Dl) {
+        if *self == Self::Now {
+            file.toggle_status();
+        }
+        file.queued
+            .store(*self == Self::Queued, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum SizeGuard {
+    #[default]
+    Warn,
+    Block,
+}
+
+pub fn exceeds_size_limit(max_size_gb: f64, content_length: usize) -> bool {
+    max_size_gb > 0.0 && content_length as f64 > max_size_gb * 1024.0 * 1024.0 * 1024.0
+}
@@ -0,0 +1,198 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    dl::file2dl::{generate_name_on_disk, File2Dl, FileKey},
+    DownloadManager,
+};
+use chrono::Local;
+use flate2::read::GzDecoder;
+use std::{
+    fs::{create_dir_all, metadata, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+};
+
+const ARCHIVE_EXTS: [&str; 5] = [".zip", ".tar.gz", ".tgz", ".tar", ".7z"];
+
+type Extracted = (FileKey, Result<PathBuf, String>);
+
+pub struct Extractions {
+    pub channel: (Sender<Extracted>, Receiver<Extracted>),
+}
+
+impl Default for Extractions {
+    fn default() -> Self {
+        Self { channel: channel() }
+    }
+}
+
+pub fn is_archive(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    ARCHIVE_EXTS.iter().any(|ext| name.ends_with(ext))
+}
+
+//the folder is named after the archive without its extension and never overwrites an existing one
+fn target_dir(dl_dir: &str, name: &str) -> Result<PathBuf, io::Error> {
+    let lower = name.to_lowercase();
+    let stem = ARCHIVE_EXTS
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map(|ext| &name[..name.len() - ext.len()])
+        .unwrap_or(name);
+    let folder = generate_name_on_disk(stem, dl_dir)?;
+    Ok(Path::new(dl_dir).join(folder))
+}
+
+//unpacks the archive next to itself in the background, progress is kept in thousandths
+pub fn extract_archive(interface: &mut DownloadManager, file: &File2Dl) {
+    let Some(fdl) = interface.files.iter_mut().find(|f| f.file.same_file(file)) else {
+        return;
+    };
+    if fdl.extracting.is_some() {
+        return;
+    }
+    let progress = Arc::new(AtomicUsize::new(0));
+    fdl.extracting = Some(progress.clone());
+    fdl.extract_failed = false;
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Extracting: {}", &file.name_on_disk);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+    let tx = interface.extractions.channel.0.clone();
+    let key = file.key();
+    interface.runtime.spawn_blocking(move || {
+        let archive = Path::new(&key.dl_dir).join(&key.name_on_disk);
+        let result = target_dir(&key.dl_dir, &key.name_on_disk)
+            .and_then(|target| {
+                unpack(&archive, &target, &progress)?;
+                Ok(target)
+            })
+            .map_err(|e| e.to_string());
+        let _ = tx.send((key, result));
+    });
+}
+
+fn unpack(archive: &Path, target: &Path, progress: &AtomicUsize) -> Result<(), io::Error> {
+    let name = archive.to_string_lossy().to_lowercase();
+    create_dir_all(target)?;
+    if name.ends_with(".zip") {
+        unpack_zip(archive, target, progress)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        let total = metadata(archive)?.len() as usize;
+        let reader = Counted::new(File::open(archive)?, total, progress);
+        tar::Archive::new(GzDecoder::new(reader)).unpack(target)
+    } else if name.ends_with(".tar") {
+        let total = metadata(archive)?.len() as usize;
+        let reader = Counted::new(File::open(archive)?, total, progress);
+        tar::Archive::new(reader).unpack(target)
+    } else {
+        unpack_7z(archive, target)
+    }
+}
+
+fn unpack_zip(archive: &Path, target: &Path, progress: &AtomicUsize) -> Result<(), io::Error> {
+    let mut zip = zip::ZipArchive::new(File::open(archive)?)?;
+    let total = zip.len().max(1);
+    for idx in 0..zip.len() {
+        let mut member = zip.by_index(idx)?;
+        //members pointing outside the folder are skipped
+        let Some(relative) = member.enclosed_name() else {
+            continue;
+        };
+        let path = target.join(relative);
+        if member.is_dir() {
+            create_dir_all(&path)?;
+        } else {
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)?;
+            }
+            io::copy(&mut member, &mut File::create(&path)?)?;
+        }
+        progress.store((idx + 1) * 1000 / total, Relaxed);
+    }
+    Ok(())
+}
+
+//relies on 7z being on the PATH, it reports no progress so the row just shows it's busy
+fn unpack_7z(archive: &Path, target: &Path) -> Result<(), io::Error> {
+    let output = Command::new("7z")
+        .arg("x")
+        .arg("-y")
+        .arg(format!("-o{}", target.to_string_lossy()))
+        .arg(archive)
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(stderr));
+    }
+    Ok(())
+}
+
+//tracks how much of the archive has been read
+struct Counted<'a, R> {
+    inner: R,
+    read: usize,
+    total: usize,
+    progress: &'a AtomicUsize,
+}
+
+impl<'a, R> Counted<'a, R> {
+    fn new(inner: R, total: usize, progress: &'a AtomicUsize) -> Self {
+        Self {
+            inner,
+            read: 0,
+            total: total.max(1),
+            progress,
+        }
+    }
+}
+
+impl<R: Read> Read for Counted<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.read += read;
+        self.progress
+            .store((self.read * 1000 / self.total).min(1000), Relaxed);
+        Ok(read)
+    }
+}
+
+pub fn check_extractions(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    while let Ok((key, result)) = interface.extractions.channel.1.try_recv() {
+        let name = &key.name_on_disk;
+        if let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(&key)) {
+            fdl.extracting = None;
+            fdl.extract_failed = result.is_err();
+        }
+        match result {
+            Ok(target) => {
+                let text = format!("Extracted {} to {}", name, target.to_string_lossy());
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *GREEN));
+            }
+            Err(e) => {
+                let text = format!("Couldn't extract {}: {}", name, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *RED));
+                interface.popups.log.has_error = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use egui_sfml::sfml::{graphics::RenderWindow, window::VideoMode};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SIZE: (u32, u32) = (860, 480);
+//this much of the window has to be on the screen to still be grabbed by the title bar
+const MIN_VISIBLE: i32 = 100;
+//taskbars and title bars eat into a maximized window's height
+const MAXIMIZED_SLACK: u32 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowGeometry {
+    pub width: u32,
+    pub height: u32,
+    //left to the window manager when none
+    pub position: Option<(i32, i32)>,
+    pub maximized: bool,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_SIZE.0,
+            height: DEFAULT_SIZE.1,
+            position: None,
+            maximized: false,
+        }
+    }
+}
+
+impl WindowGeometry {
+    //sfml only knows the primary screen, a window saved on another monitor starts over
+    fn on_screen(&self, desktop: &VideoMode) -> bool {
+        let Some((x, y)) = self.position else {
+            return false;
+        };
+        self.width > 0
+            && self.height > 0
+            && x + MIN_VISIBLE <= desktop.width as i32
+            && x + self.width as i32 >= MIN_VISIBLE
+            && y >= 0
+            && y + MIN_VISIBLE <= desktop.height as i32
+    }
+}
+
+//sfml can't maximize a window, so a maximized one comes back covering the screen
+pub fn restore_geometry(rw: &mut RenderWindow, geometry: &WindowGeometry) {
+    let desktop = VideoMode::desktop_mode();
+    if geometry.maximized {
+        rw.set_size((desktop.width, desktop.height));
+        rw.set_position((0, 0));
+        return;
+    }
+    if !geometry.on_screen(&desktop) {
+        return;
+    }
+    let width = geometry.width.min(desktop.width);
+    let height = geometry.height.min(desktop.height);
+    rw.set_size((width, height));
+    if let Some(position) = geometry.position {
+        rw.set_position(position);
+    }
+}
+
+//called every frame the window is shown, there's no event for moving it
+pub fn track_geometry(rw: &RenderWindow, geometry: &mut WindowGeometry) {
+    let desktop = VideoMode::desktop_mode();
+    let size = rw.size();
+    geometry.maximized =
+        size.x >= desktop.width && size.y + MAXIMIZED_SLACK >= desktop.height && size.y > 0;
+    //the size it had before being maximized is kept for when it's restored down
+    if geometry.maximized {
+        return;
+    }
+    let position = rw.position();
+    geometry.width = size.x;
+    geometry.height = size.y;
+    geometry.position = Some((position.x, position.y));
+}
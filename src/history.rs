@@ -0,0 +1,150 @@
+use crate::{
+    colors::{GREEN, RED},
+    dl::{
+        stats::{history_csv, statistics_csv, statistics_json},
+        store::{add_history, history, HistoryEntry, Outcome},
+    },
+    popups::LogPopUp,
+    DownloadManager, FDl,
+};
+use chrono::Local;
+use native_dialog::FileDialog;
+use std::{fs::write, path::Path, sync::atomic::Ordering::Relaxed};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Export {
+    History,
+    Statistics,
+}
+
+//the duration only covers this session, rows resumed after a restart start counting again
+pub fn record_history(log: &mut LogPopUp, fdl: &FDl, outcome: Outcome) {
+    let entry = HistoryEntry {
+        link: fdl.file.url.link.clone(),
+        name_on_disk: fdl.file.name_on_disk.clone(),
+        size: fdl.file.size_on_disk.load(Relaxed),
+        duration: fdl
+            .started_at
+            .map(|at| at.elapsed().as_secs())
+            .unwrap_or_default(),
+        finished_at: Local::now().timestamp(),
+        outcome,
+    };
+    if let Err(e) = add_history(&entry) {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!("Couldn't add {} to the history: {}", entry.name_on_disk, e);
+        log.logs.push((formatted_time, text, *RED));
+    }
+}
+
+pub fn open_history(interface: &mut DownloadManager) {
+    if let Some(entries) = load_history(interface) {
+        interface.popups.history.entries = entries;
+        interface.popups.history.show = true;
+    }
+}
+
+pub fn open_statistics(interface: &mut DownloadManager) {
+    if let Some(entries) = load_history(interface) {
+        interface.popups.statistics.entries = entries;
+        interface.popups.statistics.show = true;
+    }
+}
+
+fn load_history(interface: &mut DownloadManager) -> Option<Vec<HistoryEntry>> {
+    match history() {
+        Ok(entries) => Some(entries),
+        Err(e) => {
+            let formatted_time = Local::now().format("%H:%M:%S").to_string();
+            let text = format!("Couldn't read the history: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text.clone(), *RED));
+            interface.popups.error.value = text;
+            interface.popups.error.show = true;
+            None
+        }
+    }
+}
+
+//the format follows the extension picked in the dialog, anything but .csv is written as json
+fn write_export(
+    export: Export,
+    entries: &[HistoryEntry],
+    path: &Path,
+) -> Result<(), std::io::Error> {
+    let csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let contents = match (export, csv) {
+        (Export::History, true) => history_csv(entries),
+        (Export::History, false) => serde_json::to_string_pretty(entries)?,
+        (Export::Statistics, true) => statistics_csv(entries),
+        (Export::Statistics, false) => statistics_json(entries)?,
+    };
+    write(path, contents)
+}
+
+//the history window exports what the search currently shows
+pub fn export_records(interface: &mut DownloadManager, export: Export) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let (filename, entries) = match export {
+        Export::History => {
+            let popup = &interface.popups.history;
+            let entries = popup
+                .entries
+                .iter()
+                .filter(|entry| entry.matches(&popup.search))
+                .cloned()
+                .collect::<Vec<_>>();
+            ("history.csv", entries)
+        }
+        Export::Statistics => (
+            "statistics.csv",
+            interface.popups.statistics.entries.clone(),
+        ),
+    };
+    let path = match FileDialog::new()
+        .set_filename(filename)
+        .add_filter("CSV", &["csv"])
+        .add_filter("JSON", &["json"])
+        .show_save_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            let err = format!("Couldn't open save dialog: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+            return;
+        }
+    };
+    match write_export(export, &entries, &path) {
+        Ok(_) => {
+            let text = format!("Exported {} records to {}", entries.len(), path.display());
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text, *GREEN));
+        }
+        Err(e) => {
+            let err = format!("File Path: {}, Error: {}\n", path.display(), e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+        }
+    }
+}
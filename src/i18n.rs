@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{LazyLock, RwLock},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Self; 2] = [Self::English, Self::Spanish];
+
+    //each language is listed under its own name so it can be found without reading the current one
+    pub fn label(&self) -> &str {
+        match self {
+            Self::English => "English",
+            Self::Spanish => "Español",
+        }
+    }
+
+    fn bundle(&self) -> Option<&'static HashMap<&'static str, &'static str>> {
+        match self {
+            Self::English => None,
+            Self::Spanish => Some(&SPANISH),
+        }
+    }
+}
+
+//the english text doubles as the key, so english needs no bundle of its own
+static SPANISH: LazyLock<HashMap<&'static str, &'static str>> =
+    LazyLock::new(|| parse_bundle(include_str!("../locales/es.txt")));
+
+//one "key = value" pair per line, blank lines and ones starting with # are skipped
+fn parse_bundle(contents: &'static str) -> HashMap<&'static str, &'static str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+static CURRENT: RwLock<Language> = RwLock::new(Language::English);
+
+pub fn set_language(language: Language) {
+    if let Ok(mut locked) = CURRENT.write() {
+        *locked = language;
+    }
+}
+
+pub fn language() -> Language {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+//text without a translation is shown as is
+pub fn tr(key: &str) -> &str {
+    language()
+        .bundle()
+        .and_then(|bundle| bundle.get(key).copied())
+        .unwrap_or(key)
+}
+
+//translates first, then fills every {} with the next argument
+pub fn trf(key: &str, args: &[&dyn Display]) -> String {
+    let mut args = args.iter();
+    let mut parts = tr(key).split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
@@ -0,0 +1,159 @@
+use crate::{
+    dl::storage::data_file,
+    server::{
+        auth::{same_secret, server_secret},
+        interception::{Intercepted, SERVER_STATE},
+    },
+    Settings,
+};
+use std::{
+    fs::{read_to_string, write, File, OpenOptions, TryLockError},
+    io::{BufRead, BufReader, Error, ErrorKind, Write},
+    net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        OnceLock,
+    },
+    time::Duration,
+};
+
+//held by the running instance, so every user and portable copy has its own
+const LOCK_FILE: &str = "instance.lock";
+//port the running instance listens on, a file of its own since windows won't read a locked one
+const PORT_FILE: &str = "instance.port";
+const GREETING: &str = "rusty-dl-manager";
+//sent after the links when the launch wants the window, a daemon started twice doesn't
+const SHOW: &str = "show";
+
+//a later launch asked for the window, the main loop brings it to the front
+static FOCUS_REQUESTED: AtomicBool = AtomicBool::new(false);
+//the lock is let go when the file closes, so it stays open until the process exits
+static LOCK: OnceLock<File> = OnceLock::new();
+
+//links passed on the command line, everything else is a flag
+pub fn cli_links() -> Vec<String> {
+    std::env::args()
+        .skip(1)
+        .filter(|arg| {
+            url::Url::parse(arg).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+        })
+        .collect()
+}
+
+//true when another instance took the links and this one should exit, on an error this one keeps them and runs alone
+pub fn hand_off(links: Vec<String>, show: bool) -> Result<bool, Error> {
+    match listen_or_forward(&links, show) {
+        Ok(true) => Ok(true),
+        result => {
+            queue_links(links);
+            result
+        }
+    }
+}
+
+fn listen_or_forward(links: &[String], show: bool) -> Result<bool, Error> {
+    let lock = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(data_file(LOCK_FILE))?;
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            forward(running_instance()?, links, show)?;
+            return Ok(true);
+        }
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+    //only reachable from this machine, unlike the browser server
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    write(
+        data_file(PORT_FILE),
+        listener.local_addr()?.port().to_string(),
+    )?;
+    let _ = LOCK.set(lock);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            receive(stream).unwrap_or_default();
+        }
+    });
+    Ok(false)
+}
+
+//the port file is written right after the lock is taken, a launch in between waits for it
+fn running_instance() -> Result<SocketAddr, Error> {
+    for _ in 0..20 {
+        let port = read_to_string(data_file(PORT_FILE)).unwrap_or_default();
+        if let Ok(port) = port.trim().parse::<u16>() {
+            return Ok(SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    Err(Error::new(
+        ErrorKind::NotFound,
+        "the running instance didn't say where it listens",
+    ))
+}
+
+fn forward(addr: SocketAddr, links: &[String], show: bool) -> Result<(), Error> {
+    let mut stream = TcpStream::connect_timeout(&addr, Duration::from_secs(2))?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut greeting = String::new();
+    BufReader::new(&stream).read_line(&mut greeting)?;
+    //a port file left by an instance that crashed can point at some other program by now
+    if greeting.trim() != GREETING {
+        return Err(Error::new(ErrorKind::InvalidData, "not an instance"));
+    }
+    //only launches that can read the settings get their links in, like requests to the server
+    writeln!(stream, "{}", Settings::local_server().1)?;
+    for link in links {
+        writeln!(stream, "{}", link)?;
+    }
+    if show {
+        writeln!(stream, "{}", SHOW)?;
+    }
+    stream.shutdown(Shutdown::Write)
+}
+
+fn receive(mut stream: TcpStream) -> Result<(), Error> {
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    writeln!(stream, "{}", GREETING)?;
+    let mut lines = BufReader::new(&stream).lines();
+    let given = lines.next().transpose()?.unwrap_or_default();
+    //the ui sets it while loading, a launch that comes in before that is checked against the file
+    let secret = match server_secret() {
+        secret if secret.is_empty() => Settings::local_server().1,
+        secret => secret,
+    };
+    if secret.is_empty() || !same_secret(&given, &secret) {
+        return Err(Error::new(ErrorKind::PermissionDenied, "wrong secret"));
+    }
+    let (show, links): (Vec<_>, Vec<_>) = lines
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .partition(|line| line == SHOW);
+    queue_links(links);
+    if !show.is_empty() {
+        FOCUS_REQUESTED.store(true, Relaxed);
+    }
+    Ok(())
+}
+
+//picked up by check_urls like links sent from the browser
+fn queue_links(links: Vec<String>) {
+    if let Ok(mut state) = SERVER_STATE.lock() {
+        state.extend(
+            links
+                .into_iter()
+                .filter(|link| !link.trim().is_empty())
+                .map(|link| Intercepted {
+                    link,
+                    ..Default::default()
+                }),
+        );
+    }
+}
+
+pub fn focus_requested() -> bool {
+    FOCUS_REQUESTED.swap(false, Relaxed)
+}
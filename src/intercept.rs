@@ -0,0 +1,175 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    dl::{file2dl::File2Dl, tls::TlsOptions},
+    download_mechanism::{exceeds_size_limit, SizeGuard, StartMode},
+    i18n::tr,
+    server::interception::Intercepted,
+    DownloadManager, FDl, Settings,
+};
+use chrono::Local;
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+type Probed = (Intercepted, Result<File2Dl, String>);
+
+//links from the browser are probed before anyone is asked about them
+pub struct Intercepts {
+    pub channel: (Sender<Probed>, Receiver<Probed>),
+    //probed links waiting their turn in the confirmation popup
+    pub waiting: VecDeque<(Intercepted, File2Dl)>,
+}
+
+impl Default for Intercepts {
+    fn default() -> Self {
+        Self {
+            channel: channel(),
+            waiting: VecDeque::new(),
+        }
+    }
+}
+
+//the host the file comes from, the page url is whatever the extension was told so it's never trusted
+pub fn site(intercepted: &Intercepted) -> String {
+    url::Url::parse(&intercepted.link)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Accept,
+    Ask,
+    Block,
+}
+
+//what happens to a probed link before anyone is asked about it
+pub fn verdict(settings: &Settings, intercepted: &Intercepted, file: &File2Dl) -> Verdict {
+    let too_large = exceeds_size_limit(settings.max_size_gb, file.url.content_length);
+    if too_large && settings.size_guard == SizeGuard::Block {
+        return Verdict::Block;
+    }
+    //a warning about the size is still shown, even for sites that are always accepted
+    if !too_large && settings.trusted_sites.contains(&site(intercepted)) {
+        return Verdict::Accept;
+    }
+    Verdict::Ask
+}
+
+pub fn probe_intercepted(interface: &mut DownloadManager, intercepted: Intercepted) {
+    let dl_dir = interface.settings.dl_dir.clone();
+    let tls = TlsOptions {
+        ca_cert: interface.settings.ca_cert.clone(),
+        accept_invalid_certs: false,
+    };
+    let tx = interface.intercepts.channel.0.clone();
+    interface.runtime.spawn(async move {
+        let browser = intercepted.browser.clone();
+        let probed = File2Dl::with_browser(&intercepted.link, &dl_dir, tls, browser)
+            .await
+            .map_err(|e| e.to_string());
+        tx.send((intercepted, probed)).unwrap_or_default();
+    });
+}
+
+pub fn check_intercepts(interface: &mut DownloadManager) {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    while let Ok((intercepted, probed)) = interface.intercepts.channel.1.try_recv() {
+        let file = match probed {
+            Ok(file) => file,
+            Err(e) => {
+                let text = format!("{}: {}", &intercepted.link, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *RED));
+                interface.popups.log.has_error = true;
+                continue;
+            }
+        };
+        if interface
+            .files
+            .iter()
+            .any(|f| f.file.url.link == file.url.link)
+        {
+            let text = format!("Already in the list: {}", &file.url.link);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *CYAN));
+            continue;
+        }
+        match verdict(&interface.settings, &intercepted, &file) {
+            Verdict::Block => {
+                let text = format!(
+                    "{} is over the {} GB limit",
+                    &file.url.link, interface.settings.max_size_gb
+                );
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *RED));
+            }
+            Verdict::Accept => {
+                let start_mode = interface.settings.intercept_start_mode;
+                accept(interface, intercepted, file, start_mode);
+                interface
+                    .toasts
+                    .push(tr("Download added from the browser"), *GREEN);
+            }
+            Verdict::Ask => interface.intercepts.waiting.push_back((intercepted, file)),
+        }
+    }
+    if interface.popups.intercept.show {
+        return;
+    }
+    if let Some((intercepted, file)) = interface.intercepts.waiting.pop_front() {
+        let popup = &mut interface.popups.intercept;
+        popup.rename = file.name_on_disk.clone();
+        popup.intercepted = intercepted;
+        popup.file = Some(file);
+        popup.always_accept = false;
+        popup.start_mode = interface.settings.intercept_start_mode;
+        popup.show = true;
+        interface.show_window = true;
+        interface
+            .toasts
+            .push(tr("A download from the browser is waiting"), *CYAN);
+    }
+}
+
+//adds the file to the list under a free name, since other links were probed alongside
+pub fn accept(
+    interface: &mut DownloadManager,
+    intercepted: Intercepted,
+    mut file: File2Dl,
+    start_mode: StartMode,
+) {
+    interface.claim_name(&mut file);
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    let text = if intercepted.page_title.is_empty() {
+        format!("Accepted link from server:{}", &file.url.link)
+    } else {
+        format!(
+            "Accepted link from server:{} ({})",
+            &file.url.link, &intercepted.page_title
+        )
+    };
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *GREEN));
+    file.page_title = intercepted.page_title;
+    file.page_url = intercepted.page_url;
+    start_mode.apply(&file);
+    interface.add_row(FDl {
+        file,
+        ..Default::default()
+    });
+}
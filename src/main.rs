@@ -1,7 +1,27 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-use colors::{DARKER_PURPLE, PURPLE};
-use dl::file2dl::File2Dl;
-use download_mechanism::{check_urls, run_downloads, set_total_bandwidth, Actions};
+use api_tokens::check_api_log;
+use bandwidth_schedule::{apply_bandwidth_schedule, BandwidthSlot};
+use chrono::Local;
+use cli::run_cli;
+use close::{close_requested, CloseAction};
+use colors::{DARKER_PURPLE, PURPLE, RED};
+use columns::{default_columns, normalize_columns, TableColumn};
+use content_index::{check_content_index, ContentIndex, INDEX_FILE};
+use control::{check_control, Control};
+use details::{lay_details_panel, Details};
+use dl::{
+    disk::{is_portable, normalize_dir, set_portable, set_write_options, WriteOptions},
+    file2dl::{generate_free_name, File2Dl, FileKey},
+    limiter::{set_global_limit, LimitSplit},
+    metadata::{init_metadata, quarantine},
+    net::{set_net_config, DnsResolver, HttpVersion, IpPreference, NetConfig, DEFAULT_POOL_SIZE},
+    storage::{data_file, migrate_state_files, platform_data_dir, set_data_dir},
+    store::DB_FILES,
+    timeouts::{set_global_timeouts, Timeouts},
+};
+use download_mechanism::{
+    check_urls, run_downloads, set_total_bandwidth, Actions, SizeGuard, StartMode,
+};
 use egui_aesthetix::{themes::TokyoNight, Aesthetix};
 use egui_sfml::{
     egui::{Color32, Context, FontData, FontDefinitions, Id},
@@ -12,65 +32,323 @@ use egui_sfml::{
     SfEgui,
 };
 use extern_windows::Bandwidth;
+use extract::{check_extractions, Extractions};
+use geometry::{restore_geometry, track_geometry, WindowGeometry, DEFAULT_SIZE};
+use i18n::{set_language, Language};
+use instance::{cli_links, focus_requested, hand_off};
+use intercept::{check_intercepts, Intercepts};
+use maintenance::{check_maintenance, Maintenance};
 use menu_bar::init_menu_bar;
+use native_messaging::run_native_host;
+use notifications::{check_notifications, Notifications};
 use popups::*;
+use refresh::{check_refreshes, RefreshSchedule};
+use relink::{check_relinks, Relinks};
+use restart::{check_restarts, Restarts};
+use scan::{check_scans, ScanState, Scans};
+use search::Search;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use server::interception::init_server;
+use server::{
+    auth::{generate_secret, set_api_tokens, set_server_secret, ApiToken},
+    interception::init_server,
+};
+use shortcuts::handle_shortcuts;
 use side_bar::{lay_side_bar_content, Explorer};
+use sound::{Chime, SoundSettings};
+use speed_history::{record_speed, SpeedHistory, HISTORY_FILE};
+use speed_rules::{apply_speed_rules, SpeedRule};
 use status_bar::{check_connection, init_status_bar, update_connected, Connection};
 use std::{
     fs::File,
     io::{Read, Write},
-    path::Path,
-    sync::mpsc::channel,
+    path::PathBuf,
+    sync::{atomic::AtomicUsize, mpsc::channel, Arc},
     time::{Duration, Instant},
 };
-use table::lay_table;
-use tokio::runtime::{self, Runtime};
-use tray::{handle_tray_events, Message, Tray};
+use table::{lay_completed_drawer, lay_status_tabs, lay_table, StatusFilter};
+use taskbar::{update_taskbar, Taskbar};
+use theme::{apply_theme, CustomColors, Theme, ThemeState};
+use toast::{show_toasts, Toasts};
+use tokio::{
+    runtime::{self, Runtime},
+    sync::watch,
+};
+use tray::{handle_tray_events, update_tray, Tray};
+use update_check::{check_updates, UpdateChecks};
+use verification::{check_verifications, Verification};
+use watch_folder::{check_watch_folder, WatchFolder};
 
+mod api_tokens;
+mod autostart;
+mod backup;
+mod bandwidth_schedule;
+mod batch;
+mod cancel;
+mod cli;
+mod close;
 mod colors;
+mod columns;
+mod content_index;
+mod control;
+mod details;
 mod dl;
+mod download_list;
 mod download_mechanism;
 mod extern_windows;
+mod extract;
+mod geometry;
+mod history;
+mod i18n;
+mod instance;
+mod intercept;
+mod maintenance;
 mod menu_bar;
+mod native_messaging;
+mod notifications;
 mod popups;
+mod queue;
+mod refresh;
+mod relink;
+mod restart;
+mod scan;
+mod search;
 mod server;
+mod shortcuts;
 mod side_bar;
+mod sound;
+mod speed_history;
+mod speed_rules;
 mod status_bar;
 mod table;
+mod taskbar;
+mod theme;
+mod toast;
 mod tray;
+mod update_check;
+mod verification;
+mod watch_folder;
+
+const SETTINGS_FILE: &str = "settings.toml";
+const LEGACY_SETTINGS_FILE: &str = "settings.json";
+const PORTABLE_MARKER: &str = "portable";
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 struct Settings {
     retry_interval: u64,
     dl_dir: String,
+    watch_dir: String,
+    drawer_delay: u64,
+    speed_rules: Vec<SpeedRule>,
+    max_size_gb: f64,
+    size_guard: SizeGuard,
+    ca_cert: String,
+    dns: DnsResolver,
+    ip_preference: IpPreference,
+    http_version: HttpVersion,
+    //idle connections kept open per host for reuse
+    pool_size: usize,
+    //every download goes through it when set, empty uses the one in the environment
+    proxy: String,
+    timeouts: Timeouts,
+    write_options: WriteOptions,
+    //bytes per sec shared by all downloads, 0 is unlimited
+    global_limit: usize,
+    limit_split: LimitSplit,
+    bandwidth_schedule: Vec<BandwidthSlot>,
+    //pauses downloads when the connection drops and resumes them once it's back
+    auto_resume: bool,
+    api_tokens: Vec<ApiToken>,
+    //unpacks finished archives into a folder next to them
+    auto_extract: bool,
+    //finished files are looked up on virustotal by hash when set
+    virustotal_key: String,
+    //order, visibility and width of the table's columns
+    columns: Vec<TableColumn>,
+    theme: Theme,
+    custom_colors: CustomColors,
+    language: Language,
+    //size and position of the main window when it was last shown
+    window: WindowGeometry,
+    //confirmations the user ticked "don't ask again" on
+    skip_confirm: Vec<ConfirmKind>,
+    sound: SoundSettings,
+    close_action: CloseAction,
+    //launches straight into the tray, like --hidden
+    start_hidden: bool,
+    //registered to run on login, mirrored in the run key or the autostart folder
+    autostart: bool,
+    //draws the overall progress onto the tray icon
+    tray_progress: bool,
+    //folders downloads were moved to from the list, loaded along with dl_dir
+    moved_dirs: Vec<String>,
+    //port of the local server, only read on start
+    server_port: u16,
+    //opens the local server like a full access token, made on the first start
+    server_secret: String,
+    //hosts whose downloads are added without the confirmation popup
+    trusted_sites: Vec<String>,
+    //what downloads from the browser do once accepted, last picked in the popup
+    intercept_start_mode: StartMode,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             retry_interval: 5,
-            dl_dir: String::from("Downloads"),
+            dl_dir: default_dl_dir(),
+            watch_dir: String::new(),
+            drawer_delay: 10,
+            speed_rules: Vec::new(),
+            max_size_gb: 0.0,
+            size_guard: SizeGuard::Warn,
+            ca_cert: String::new(),
+            dns: DnsResolver::System,
+            ip_preference: IpPreference::Auto,
+            http_version: HttpVersion::Auto,
+            pool_size: DEFAULT_POOL_SIZE,
+            proxy: String::new(),
+            timeouts: Timeouts::default(),
+            write_options: WriteOptions::default(),
+            global_limit: 0,
+            limit_split: LimitSplit::Proportional,
+            bandwidth_schedule: Vec::new(),
+            auto_resume: true,
+            api_tokens: Vec::new(),
+            auto_extract: false,
+            virustotal_key: String::new(),
+            columns: default_columns(),
+            theme: Theme::default(),
+            custom_colors: CustomColors::default(),
+            language: Language::default(),
+            window: WindowGeometry::default(),
+            skip_confirm: Vec::new(),
+            sound: SoundSettings::default(),
+            close_action: CloseAction::default(),
+            start_hidden: false,
+            autostart: false,
+            tray_progress: false,
+            moved_dirs: Vec::new(),
+            server_port: 3000,
+            server_secret: String::new(),
+            trusted_sites: Vec::new(),
+            intercept_start_mode: StartMode::Now,
         }
     }
 }
 
 impl Settings {
-    fn parse() -> Result<Self, std::io::Error> {
-        let path = Path::new("settings.json");
-        let mut buffer = String::new();
-        let mut file = File::open(path)?;
-        file.read_to_string(&mut buffer)?;
-        let settings: Settings = serde_json::from_str(&buffer)?;
+    //older versions kept the settings in json, they're carried over to toml on first start
+    fn load() -> Result<Self, std::io::Error> {
+        if data_file(SETTINGS_FILE).exists() {
+            let buffer = std::fs::read_to_string(data_file(SETTINGS_FILE))?;
+            let mut settings: Settings = toml::from_str(&buffer).map_err(std::io::Error::other)?;
+            settings.dl_dir = normalize_dir(&settings.dl_dir);
+            normalize_columns(&mut settings.columns);
+            //files from before the secret existed get one the first time they're loaded
+            if settings.server_secret.is_empty() {
+                settings.server_secret = generate_secret();
+                settings.save()?;
+            }
+            return Ok(settings);
+        }
+        let mut settings = if data_file(LEGACY_SETTINGS_FILE).exists() {
+            let mut buffer = String::new();
+            File::open(data_file(LEGACY_SETTINGS_FILE))?.read_to_string(&mut buffer)?;
+            let mut settings: Settings = serde_json::from_str(&buffer)?;
+            settings.dl_dir = normalize_dir(&settings.dl_dir);
+            normalize_columns(&mut settings.columns);
+            settings
+        } else {
+            Settings::default()
+        };
+        settings.server_secret = generate_secret();
+        settings.save()?;
+        if data_file(LEGACY_SETTINGS_FILE).exists() {
+            std::fs::remove_file(data_file(LEGACY_SETTINGS_FILE))?;
+        }
         Ok(settings)
     }
+
+    //what the running app saved, for launches that only talk to it and must not write anything
+    fn stored() -> Option<Self> {
+        let buffer = std::fs::read_to_string(data_file(SETTINGS_FILE)).ok()?;
+        toml::from_str(&buffer).ok()
+    }
+
+    //where the running app's server listens and the secret it wants
+    fn local_server() -> (String, String) {
+        let settings = Self::stored().unwrap_or_default();
+        let server = format!("http://127.0.0.1:{}", settings.server_port);
+        (server, settings.server_secret)
+    }
+
+    fn popup(&self) -> SettingsPopUp {
+        SettingsPopUp {
+            show: false,
+            temp_str: self.retry_interval.to_string(),
+            dl_dir: self.dl_dir.clone(),
+            watch_dir: self.watch_dir.clone(),
+            drawer_delay: self.drawer_delay.to_string(),
+            max_size_gb: if self.max_size_gb > 0.0 {
+                self.max_size_gb.to_string()
+            } else {
+                String::default()
+            },
+            size_guard: self.size_guard,
+            ca_cert: self.ca_cert.clone(),
+            dns: self.dns.clone(),
+            ip_preference: self.ip_preference,
+            http_version: self.http_version,
+            pool_size: self.pool_size.to_string(),
+            proxy: self.proxy.clone(),
+            connect_timeout: self.timeouts.connect_secs.to_string(),
+            read_timeout: self.timeouts.read_secs.to_string(),
+            stall_timeout: self.timeouts.stall_secs.to_string(),
+            write_buffer_kb: self.write_options.buffer_kb.to_string(),
+            fsync: self.write_options.fsync,
+            global_limit: if self.global_limit > 0 {
+                (self.global_limit as f64 / (1024.0 * 1024.0)).to_string()
+            } else {
+                String::default()
+            },
+            limit_split: self.limit_split,
+            auto_resume: self.auto_resume,
+            auto_extract: self.auto_extract,
+            virustotal_key: self.virustotal_key.clone(),
+            language: self.language,
+            skip_confirm: self.skip_confirm.clone(),
+            sound: self.sound,
+            close_action: self.close_action,
+            start_hidden: self.start_hidden,
+            autostart: self.autostart,
+            tray_progress: self.tray_progress,
+            server_port: self.server_port.to_string(),
+            trusted_sites: self.trusted_sites.clone(),
+            dns_servers: match &self.dns {
+                DnsResolver::Custom(servers) => servers.clone(),
+                _ => String::default(),
+            },
+            error: String::default(),
+        }
+    }
+
+    //written next to the real file and renamed over it so a crash can't leave it half written
+    fn save(&self) -> Result<(), std::io::Error> {
+        let contents = toml::to_string_pretty(self).map_err(std::io::Error::other)?;
+        let temp = data_file(&format!("{}.tmp", SETTINGS_FILE));
+        let mut file = File::create(&temp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(temp, data_file(SETTINGS_FILE))
+    }
 }
 #[derive(Default)]
 struct Select {
     select_all: bool,
     initial_state: bool,
+    //last row clicked without shift, where shift-click ranges start from
+    anchor: Option<FileKey>,
 }
 
 struct DownloadManager {
@@ -78,23 +356,69 @@ struct DownloadManager {
     files: Vec<FDl>,
     popups: PopUps,
     explorer: Explorer,
-    search: String,
+    search: Search,
+    //which tab above the table is picked, applied on top of the search
+    status_filter: StatusFilter,
     select: Select,
     connection: Connection,
     settings: Settings,
     bandwidth: Bandwidth,
-    tray_menu: Tray,
+    //none in daemon mode or when the desktop has no tray
+    tray_menu: Option<Tray>,
+    watch_folder: WatchFolder,
+    verification: Verification,
+    extractions: Extractions,
+    scans: Scans,
+    update_checks: UpdateChecks,
+    speed_history: SpeedHistory,
+    content_index: ContentIndex,
+    refresh: RefreshSchedule,
+    restarts: Restarts,
+    relinks: Relinks,
+    intercepts: Intercepts,
+    control: Control,
+    maintenance: Maintenance,
+    details: Details,
+    theme: ThemeState,
+    toasts: Toasts,
+    notifications: Notifications,
+    chime: Chime,
+    taskbar: Taskbar,
     show_window: bool,
 }
 
 impl DownloadManager {
+    //links are probed side by side and each probe only looks at the disk, so the name is settled when the row is added
+    fn claim_name(&self, file: &mut File2Dl) {
+        let taken = |name: &str| {
+            self.files
+                .iter()
+                .any(|f| f.file.dl_dir == file.dl_dir && f.file.name_on_disk == name)
+        };
+        if let Ok(name_on_disk) = generate_free_name(&file.name_on_disk, &file.dl_dir, taken) {
+            file.name_on_disk = name_on_disk;
+        }
+    }
+
+    //nothing is written for a row until its transfer starts, a queued one is recorded now so it stays queued
+    fn add_row(&mut self, fdl: FDl) {
+        if fdl.file.queued.load(std::sync::atomic::Ordering::Relaxed) {
+            if let Err(e) = fdl.save_metadata() {
+                let text = format!("Couldn't save {}: {}", &fdl.file.name_on_disk, e);
+                let formatted_time = Local::now().format("%H:%M:%S").to_string();
+                self.popups.log.logs.push((formatted_time, text, *RED));
+            }
+        }
+        self.files.push(fdl);
+    }
+
     fn update(&mut self, ctx: &egui_sfml::egui::Context) {
         if !self.show_window {
             std::thread::sleep(Duration::from_millis(300));
         }
+        apply_theme(self, ctx);
         handle_popups(self, ctx);
-        set_total_bandwidth(self);
-        update_connected(self);
+        handle_shortcuts(self, ctx);
         egui_sfml::egui::TopBottomPanel::top(Id::new("Top"))
             .default_height(40.0)
             .resizable(false)
@@ -123,6 +447,7 @@ impl DownloadManager {
             .show(ctx, |ui| {
                 lay_side_bar_content(self, ui);
             });
+        lay_details_panel(self, ctx);
         egui_sfml::egui::CentralPanel::default()
             .frame(
                 egui_sfml::egui::Frame::none()
@@ -134,8 +459,11 @@ impl DownloadManager {
                     )),
             )
             .show(ctx, |ui| {
+                lay_completed_drawer(self, ui);
+                lay_status_tabs(self, ui);
                 lay_table(self, ui, ctx);
             });
+        show_toasts(self, ctx);
     }
 
     fn default() -> Self {
@@ -143,33 +471,105 @@ impl DownloadManager {
             .enable_all()
             .build()
             .expect("Failed to build runtime");
-        let settings_path = Path::new("settings.json");
-        let settings = if settings_path.exists() {
-            Settings::parse().expect("Couldn't parse settings")
-        } else {
-            let mut file = File::create(settings_path).expect("Couldn't create file");
-            let settings = Settings::default();
-            file.write_all(json!(&settings).to_string().as_bytes())
-                .expect("Couldn't write to file");
-            settings
+        let state_files = [
+            SETTINGS_FILE,
+            LEGACY_SETTINGS_FILE,
+            HISTORY_FILE,
+            INDEX_FILE,
+        ]
+        .into_iter()
+        .chain(DB_FILES)
+        .collect::<Vec<_>>();
+        let mut load_errors = migrate_state_files(&state_files);
+        let defaults = || Settings {
+            server_secret: generate_secret(),
+            ..Settings::default()
         };
-        let files = Self::load_files(&settings).unwrap_or_default();
+        let settings = Settings::load().unwrap_or_else(|e| {
+            //a typo in the hand edited file is moved aside like broken metadata, the app starts on defaults
+            let Some(path) = [SETTINGS_FILE, LEGACY_SETTINGS_FILE]
+                .map(data_file)
+                .into_iter()
+                .find(|path| path.exists())
+            else {
+                load_errors.push(format!("Couldn't load settings: {}", e));
+                return defaults();
+            };
+            let text = match quarantine(&path) {
+                Ok(moved) => format!(
+                    "Moved unreadable {} to {}: {}",
+                    path.display(),
+                    moved.display(),
+                    e
+                ),
+                Err(moving) => format!(
+                    "Couldn't read {}: {}, nor move it aside: {}",
+                    path.display(),
+                    e,
+                    moving
+                ),
+            };
+            load_errors.push(text);
+            Settings::load().unwrap_or_else(|_| defaults())
+        });
+        let files = match Self::load_files(&settings) {
+            Ok((files, problems)) => {
+                load_errors.extend(problems);
+                files
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                load_errors.push(e.to_string());
+                Vec::new()
+            }
+        };
+        set_api_tokens(&settings.api_tokens);
+        set_server_secret(&settings.server_secret);
+        set_global_timeouts(settings.timeouts);
+        set_language(settings.language);
+        set_write_options(settings.write_options);
+        set_global_limit(settings.global_limit, settings.limit_split);
+        {
+            //the resolver has to be created inside the runtime it'll run on
+            let _guard = runtime.enter();
+            set_net_config(
+                NetConfig::new(
+                    &settings.dns,
+                    settings.ip_preference,
+                    settings.http_version,
+                    settings.pool_size,
+                    &settings.proxy,
+                )
+                .unwrap_or_default(),
+            );
+        }
 
         let popups = PopUps {
-            error: Self::create_error_popup(&settings.dl_dir),
+            error: Self::create_error_popup(&load_errors),
             download: DownloadPopUp::default(),
-            settings: SettingsPopUp {
-                show: false,
-                temp_str: settings.retry_interval.to_string(),
-                dl_dir: settings.dl_dir.clone(),
-                error: String::default(),
-            },
+            duplicate: DuplicatePopUp::default(),
+            cancel: CancelPopUp::default(),
+            tags: TagsPopUp::default(),
+            note: NotePopUp::default(),
+            update_url: UpdateUrlPopUp::default(),
+            close: ClosePopUp::default(),
+            columns: ColumnsPopUp::default(),
+            theme_editor: ThemeEditorPopUp::default(),
+            scan_warning: ScanWarningPopUp::default(),
+            intercept: InterceptPopUp::default(),
+            settings: settings.popup(),
             confirm: ConfirmPopUp::default(),
             plot: PLotPopUp::default(),
             speed: EditSpeedPopUp::default(),
-            log: LogPopUp::default(),
+            rules: SpeedRulesPopUp::default(),
+            schedule: BandwidthSchedulePopUp::default(),
+            tokens: ApiTokensPopUp::default(),
+            history: HistoryPopUp::default(),
+            statistics: StatisticsPopUp::default(),
+            log: Self::create_log_popup(load_errors),
         };
         let explorer = Explorer::default();
+        let show_window = !starts_hidden(&settings);
         Self {
             runtime,
             files,
@@ -177,28 +577,72 @@ impl DownloadManager {
             select: Select::default(),
             settings,
             popups,
-            search: String::default(),
+            search: Search::default(),
+            status_filter: StatusFilter::default(),
             connection: Connection::default(),
             bandwidth: Bandwidth::default(),
-            tray_menu: Tray::default(),
-            show_window: true,
+            tray_menu: None,
+            watch_folder: WatchFolder::default(),
+            verification: Verification::default(),
+            extractions: Extractions::default(),
+            scans: Scans::default(),
+            update_checks: UpdateChecks::default(),
+            speed_history: SpeedHistory::default(),
+            content_index: ContentIndex::default(),
+            refresh: RefreshSchedule::default(),
+            restarts: Restarts::default(),
+            relinks: Relinks::default(),
+            intercepts: Intercepts::default(),
+            control: Control::default(),
+            maintenance: Maintenance::default(),
+            details: Details::default(),
+            theme: ThemeState::default(),
+            toasts: Toasts::default(),
+            notifications: Notifications::default(),
+            chime: Chime::default(),
+            taskbar: Taskbar::default(),
+            show_window,
         }
     }
-    fn create_error_popup(dl_dir: &str) -> ErrorPopUp {
-        match File2Dl::from(dl_dir) {
-            Ok(_) => ErrorPopUp::default(),
-            Err(e) if e.kind() != std::io::ErrorKind::NotFound => ErrorPopUp {
-                value: e.to_string(),
-                show: true,
-                channel: channel(),
-            },
-            _ => ErrorPopUp::default(),
+    fn create_error_popup(load_errors: &[String]) -> ErrorPopUp {
+        if load_errors.is_empty() {
+            return ErrorPopUp::default();
+        }
+        ErrorPopUp {
+            value: load_errors.join("\n"),
+            show: true,
+            channel: channel(),
+        }
+    }
+    fn create_log_popup(load_errors: Vec<String>) -> LogPopUp {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        LogPopUp {
+            has_error: !load_errors.is_empty(),
+            logs: load_errors
+                .into_iter()
+                .map(|text| (formatted_time.clone(), text, *RED))
+                .collect(),
+            show: false,
         }
     }
 
-    fn load_files(settings: &Settings) -> Result<Vec<FDl>, std::io::Error> {
-        let files = File2Dl::from(&settings.dl_dir)?;
-        Ok(files
+    //the second half lists the metadata files that couldn't be loaded
+    fn load_files(settings: &Settings) -> Result<(Vec<FDl>, Vec<String>), std::io::Error> {
+        let (mut files, mut problems) = File2Dl::from(&settings.dl_dir)?;
+        for dir in settings
+            .moved_dirs
+            .iter()
+            .filter(|dir| **dir != settings.dl_dir)
+        {
+            match File2Dl::from(dir) {
+                Ok((moved, moved_problems)) => {
+                    files.extend(moved);
+                    problems.extend(moved_problems);
+                }
+                Err(e) => problems.push(format!("Couldn't load the downloads in {}: {}", dir, e)),
+            }
+        }
+        let files = files
             .into_iter()
             .map(|file| {
                 let is_complete = file.complete.load(std::sync::atomic::Ordering::Relaxed);
@@ -212,9 +656,20 @@ impl DownloadManager {
                     initiated: false,
                     selected: false,
                     action_on_save: Actions::default(),
+                    completed_at: None,
+                    speed_before_rule: None,
+                    cancel: None,
+                    discard_partial: false,
+                    verifying: None,
+                    extracting: None,
+                    extract_failed: false,
+                    scan: None,
+                    started_at: None,
+                    error: None,
                 }
             })
-            .collect())
+            .collect();
+        Ok((files, problems))
     }
 }
 
@@ -229,6 +684,66 @@ struct FDl {
     initiated: bool,
     selected: bool,
     action_on_save: Actions,
+    completed_at: Option<Instant>,
+    speed_before_rule: Option<usize>,
+    //aborts the running transfer, kept around so a new one waits for the old one to finish
+    cancel: Option<watch::Sender<bool>>,
+    discard_partial: bool,
+    //bytes hashed so far while the file is being verified
+    verifying: Option<Arc<AtomicUsize>>,
+    //thousandths of the archive unpacked so far
+    extracting: Option<Arc<AtomicUsize>>,
+    extract_failed: bool,
+    scan: Option<ScanState>,
+    //when the first transfer task was spawned this session, for the history
+    started_at: Option<Instant>,
+    //last error the transfer task reported, cleared once bytes flow again
+    error: Option<String>,
+}
+
+impl FDl {
+    //the transfer task drops its end of cancel when it returns, until then it may still write to the file
+    fn task_alive(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| !cancel.is_closed())
+    }
+
+    //a speed rule holds its limit in place of the user's, the user's is what gets saved
+    fn save_metadata(&self) -> Result<(), std::io::Error> {
+        let Some(speed) = self.speed_before_rule else {
+            return init_metadata(&self.file, &self.file.dl_dir);
+        };
+        let file = File2Dl {
+            speed: Arc::new(AtomicUsize::new(speed)),
+            ..self.file.clone()
+        };
+        init_metadata(&file, &file.dl_dir)
+    }
+
+    //files that were already complete on startup go straight to the drawer
+    fn in_drawer(&self, delay: u64) -> bool {
+        //files that failed verification or extraction stay in the table where they can be dealt with,
+        //ones being verified or extracted come back to it for as long as the progress is shown
+        if self.verifying.is_some()
+            || self.extracting.is_some()
+            || self.extract_failed
+            || !self
+                .file
+                .complete
+                .load(std::sync::atomic::Ordering::Relaxed)
+            || self
+                .file
+                .verify_failed
+                .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            return false;
+        }
+        self.initial_status
+            || self
+                .completed_at
+                .is_some_and(|at| at.elapsed() >= Duration::from_secs(delay))
+    }
 }
 
 impl Default for FDl {
@@ -243,12 +758,120 @@ impl Default for FDl {
             initiated: false,
             selected: false,
             action_on_save: Actions::None,
+            completed_at: None,
+            speed_before_rule: None,
+            cancel: None,
+            discard_partial: false,
+            verifying: None,
+            extracting: None,
+            extract_failed: false,
+            scan: None,
+            started_at: None,
+            error: None,
         }
     }
 }
 
+//a "portable" file next to the executable, or --portable, keeps settings, the database and downloads in its folder
+fn portable_dir() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let flagged = std::env::args().any(|arg| arg == "--portable");
+    (flagged || exe_dir.join(PORTABLE_MARKER).exists()).then_some(exe_dir)
+}
+
+//for autostart, the window stays in the tray until it's opened from there
+fn starts_hidden(settings: &Settings) -> bool {
+    settings.start_hidden || std::env::args().any(|arg| arg == "--hidden")
+}
+
+fn enter_portable_dir() -> bool {
+    let Some(dir) = portable_dir() else {
+        return false;
+    };
+    std::env::set_current_dir(dir).is_ok()
+}
+
+//the platform's downloads folder (xdg user dirs, known folders on windows), or one next to the app without it
+fn default_dl_dir() -> String {
+    if is_portable() {
+        return String::from("Downloads");
+    }
+    match dirs_next::download_dir() {
+        Some(dir) => dir.to_string_lossy().to_string(),
+        None => normalize_dir("Downloads"),
+    }
+}
+
 fn main() {
-    let init_size = (860, 480);
+    //portable mode keeps its state in the working dir, which is the executable's folder by now
+    if enter_portable_dir() {
+        set_portable(true);
+    } else if let Some(dir) = platform_data_dir() {
+        set_data_dir(dir);
+    }
+    if let Some(code) = run_cli() {
+        std::process::exit(code);
+    }
+    //started by the browser for its extension, never takes the instance lock
+    if let Some(code) = run_native_host() {
+        std::process::exit(code);
+    }
+    let daemon = std::env::args().any(|arg| arg == "--daemon");
+    //a second launch only passes its links on to the window that's already open
+    let hand_off_error = match hand_off(cli_links(), !daemon) {
+        Ok(true) => return,
+        Ok(false) => None,
+        Err(e) => Some(e),
+    };
+    let mut state = DownloadManager::default();
+    if let Some(e) = hand_off_error {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!(
+            "Couldn't reach or become the running instance, later launches open their own window: {}",
+            e
+        );
+        state
+            .popups
+            .log
+            .logs
+            .push((formatted_time, text.clone(), *RED));
+        let error = &mut state.popups.error;
+        if error.show {
+            error.value.push('\n');
+        }
+        error.value.push_str(&text);
+        error.show = true;
+    }
+    //a headless box has no tray to put the icon in
+    if !daemon {
+        match Tray::new() {
+            Ok(tray) => state.tray_menu = Some(tray),
+            Err(e) => {
+                let formatted_time = Local::now().format("%H:%M:%S").to_string();
+                let text = format!("Couldn't create the tray icon: {}", e);
+                state.popups.log.logs.push((formatted_time, text, *RED));
+            }
+        }
+    }
+    check_connection(&mut state);
+    let port = state.settings.server_port;
+    state.runtime.spawn_blocking(move || {
+        init_server(port).unwrap_or_default();
+    });
+    //--daemon keeps downloading without a window or a tray until another launch asks for one
+    if daemon {
+        state.show_window = false;
+    }
+    let started_hidden = !state.show_window;
+    //a hidden start waits the same way for the tray or a notification, the window is only created
+    //once it's wanted so it never flashes on the screen
+    while !state.show_window {
+        tick(&mut state);
+        if focus_requested() {
+            state.show_window = true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
     let title = "Rusty Dl Manager";
     let win_settings = &ContextSettings {
         depth_bits: 0,
@@ -257,32 +880,23 @@ fn main() {
         ..Default::default()
     };
 
-    let mut rw = RenderWindow::new(init_size, title, Style::DEFAULT, win_settings).unwrap();
+    let mut rw = RenderWindow::new(DEFAULT_SIZE, title, Style::DEFAULT, win_settings).unwrap();
     rw.set_framerate_limit(60);
+    restore_geometry(&mut rw, &state.settings.window);
+    state.taskbar = Taskbar::new(&rw);
 
     let mut sf_egui = SfEgui::new(&rw);
     setup_custom_fonts(sf_egui.context());
 
-    let mut state = DownloadManager::default();
-    check_connection(&mut state);
-
-    state.runtime.spawn_blocking(move || {
-        init_server().unwrap_or_default();
-    });
+    //coming out of a hidden start it should get the focus like one brought back from the tray
+    let mut was_shown = !started_hidden;
     while rw.is_open() {
-        run_downloads(&mut state);
-        handle_tray_events(&mut state);
-        check_urls(&mut state);
+        tick(&mut state);
+        update_taskbar(&mut state);
         while let Some(ev) = rw.poll_event() {
             sf_egui.add_event(&ev);
             if matches!(ev, Event::Closed) {
-                state.popups.download.show = false;
-                state.popups.confirm.show = false;
-                state.popups.error.show = false;
-                state.popups.plot.show = false;
-                state.popups.speed.show = false;
-                state.tray_menu.message = Message::None;
-                state.show_window = false;
+                close_requested(&mut state);
             }
             if let Event::Resized { width, height } = ev {
                 rw.set_view(
@@ -292,11 +906,22 @@ fn main() {
             }
         }
 
+        //launched again while running
+        let focus = focus_requested();
+        if focus {
+            state.show_window = true;
+        }
         if state.show_window {
-            rw.set_visible(true)
+            rw.set_visible(true);
+            //brought back from the tray or a notification, it shouldn't open behind other windows
+            if !was_shown || focus {
+                rw.request_focus();
+            }
+            track_geometry(&rw, &mut state.settings.window);
         } else {
             rw.set_visible(false);
         }
+        was_shown = state.show_window;
 
         let di = sf_egui
             .run(&mut rw, |_rw, ctx| {
@@ -309,6 +934,33 @@ fn main() {
     }
 }
 
+//everything that keeps the downloads and the queue going, with or without a window
+fn tick(state: &mut DownloadManager) {
+    apply_speed_rules(state);
+    apply_bandwidth_schedule(state);
+    run_downloads(state);
+    update_tray(state);
+    handle_tray_events(state);
+    check_notifications(state);
+    check_urls(state);
+    check_intercepts(state);
+    check_watch_folder(state);
+    check_verifications(state);
+    check_extractions(state);
+    check_scans(state);
+    check_updates(state);
+    check_content_index(state);
+    check_refreshes(state);
+    check_restarts(state);
+    check_relinks(state);
+    check_maintenance(state);
+    check_api_log(state);
+    check_control(state);
+    set_total_bandwidth(state);
+    record_speed(state);
+    update_connected(state);
+}
+
 fn setup_custom_fonts(ctx: &Context) {
     let mut fonts = FontDefinitions::default();
     egui_phosphor::add_to_fonts(&mut fonts, egui_phosphor::Variant::Regular);
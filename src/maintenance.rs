@@ -0,0 +1,87 @@
+use crate::{
+    colors::CYAN,
+    dl::file2dl::FileKey,
+    server::maintenance::{MaintenanceRequest, MAINTENANCE_STATE, MAX_PAUSE_MINUTES, PAUSED_UNTIL},
+    DownloadManager,
+};
+use chrono::Local;
+use std::{
+    sync::atomic::Ordering::Relaxed,
+    time::{Duration, Instant, SystemTime},
+};
+
+#[derive(Default)]
+pub struct Maintenance {
+    pub until: Option<Instant>,
+    //only the downloads paused by the window get resumed when it ends
+    pub paused: Vec<FileKey>,
+}
+
+pub fn check_maintenance(interface: &mut DownloadManager) {
+    let requests = match MAINTENANCE_STATE.try_lock() {
+        Ok(mut locked) => std::mem::take(&mut *locked),
+        Err(_) => Vec::new(),
+    };
+    for request in requests {
+        match request {
+            MaintenanceRequest::Pause(minutes) => start_pause(interface, minutes),
+            MaintenanceRequest::Resume => end_pause(interface),
+        }
+    }
+    if interface
+        .maintenance
+        .until
+        .is_some_and(|until| Instant::now() >= until)
+    {
+        end_pause(interface);
+    }
+}
+
+fn start_pause(interface: &mut DownloadManager, minutes: u64) {
+    let minutes = minutes.min(MAX_PAUSE_MINUTES);
+    for fdl in interface.files.iter() {
+        let file = &fdl.file;
+        if file.running.load(Relaxed) && !file.complete.load(Relaxed) {
+            file.running.store(false, Relaxed);
+            interface.maintenance.paused.push(file.key());
+        }
+    }
+    let window = Duration::from_secs(minutes * 60);
+    interface.maintenance.until = Some(Instant::now() + window);
+    if let Ok(mut until) = PAUSED_UNTIL.lock() {
+        *until = Some(SystemTime::now() + window);
+    }
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Maintenance: downloads paused for {} minutes", minutes);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+}
+
+fn end_pause(interface: &mut DownloadManager) {
+    if interface.maintenance.until.take().is_none() {
+        return;
+    }
+    if let Ok(mut until) = PAUSED_UNTIL.lock() {
+        *until = None;
+    }
+    let paused = std::mem::take(&mut interface.maintenance.paused);
+    for fdl in interface
+        .files
+        .iter()
+        .filter(|f| paused.iter().any(|key| f.file.is(key)) && !f.file.cancelled.load(Relaxed))
+    {
+        fdl.file.running.store(true, Relaxed);
+    }
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = String::from("Maintenance: downloads resumed");
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+}
@@ -1,14 +1,27 @@
 use crate::{
-    colors::{CYAN, GREEN, RED},
-    DownloadManager,
+    backup::{export_backup, import_server_secret, read_backup, restore_backup},
+    batch::{apply_to_selected, pause_all, resume_all, SelectionAction},
+    close::quit,
+    colors::{palette, CYAN, GRAY, GREEN, RED},
+    content_index::file_path,
+    dl::{file2dl::File2Dl, metadata::remove_metadata, store::Outcome},
+    download_list::{export_list, import_list},
+    history::{open_history, open_statistics, record_history},
+    i18n::tr,
+    popups::{ask_confirm, ConfirmKind},
+    refresh::{redownload_selected, set_refresh_selected, REFRESH_CHOICES},
+    shortcuts::SHORTCUTS,
+    theme::Theme,
+    DownloadManager, FDl,
 };
 use chrono::Local;
-use egui_sfml::egui::{menu, Color32, RichText};
+use egui_sfml::egui::{menu, Button, Color32, Grid, RichText};
+use native_dialog::FileDialog;
 use std::{fs::remove_file, path::Path};
 
 pub fn init_menu_bar(interface: &mut DownloadManager, ui: &mut egui_sfml::egui::Ui) {
     menu::bar(ui, |ui| {
-        let text = RichText::new("Files").color(*CYAN).strong().size(15.0);
+        let text = RichText::new(tr("Files")).color(*CYAN).strong().size(15.0);
         ui.add_space(5.0);
 
         ui.vertical(|ui| {
@@ -16,24 +29,138 @@ pub fn init_menu_bar(interface: &mut DownloadManager, ui: &mut egui_sfml::egui::
                 ui.menu_button(text, |ui| {
                     file_button_content(interface, ui);
                 });
-                let text = RichText::new("Downloads").color(*CYAN).strong().size(15.0);
+                let text = RichText::new(tr("Downloads"))
+                    .color(*CYAN)
+                    .strong()
+                    .size(15.0);
                 ui.menu_button(text, |ui| {
-                    let text = RichText::new("Resume all").color(*CYAN).strong();
+                    let text = RichText::new(tr("Resume all")).color(*CYAN).strong();
                     if ui.button(text).clicked() {
-                        for core in interface.files.iter_mut() {
-                            core.file
-                                .running
-                                .store(true, std::sync::atomic::Ordering::Relaxed);
+                        resume_all(interface);
+                    }
+                    let text = RichText::new(tr("Pause all")).color(*CYAN).strong();
+                    if ui.button(text).clicked() {
+                        pause_all(interface);
+                    }
+                    let text = RichText::new(tr("Re-download selected (overwrite)"))
+                        .color(*CYAN)
+                        .strong();
+                    if ui.button(text).clicked() {
+                        let text =
+                            tr("This will overwrite the selected complete files with a fresh copy");
+                        ask_confirm(
+                            interface,
+                            None,
+                            *RED,
+                            String::from(text),
+                            Box::new(|| {
+                                Box::new(move |app: &mut DownloadManager| {
+                                    redownload_selected(app);
+                                })
+                            }),
+                        );
+                    }
+                    let text = RichText::new(tr("Refresh selected")).color(*CYAN).strong();
+                    ui.menu_button(text, |ui| {
+                        for (hours, label) in REFRESH_CHOICES {
+                            let text = RichText::new(tr(label)).color(*CYAN).strong();
+                            if ui.button(text).clicked() {
+                                set_refresh_selected(interface, hours);
+                                ui.close_menu();
+                            }
                         }
+                    });
+                    let any_selected = interface.files.iter().any(|f| f.selected);
+                    let text = RichText::new(tr("Selected")).color(*CYAN).strong();
+                    ui.add_enabled_ui(any_selected, |ui| {
+                        ui.menu_button(text, |ui| {
+                            if let Some(action) = selection_menu(ui) {
+                                apply_to_selected(interface, action);
+                            }
+                        });
+                    });
+                    let text = RichText::new(tr("Copy selected urls"))
+                        .color(*CYAN)
+                        .strong();
+                    if ui.add_enabled(any_selected, Button::new(text)).clicked() {
+                        copy_selected(interface, CopyField::Url, ui);
+                        ui.close_menu();
+                    }
+                    let text = RichText::new(tr("Copy selected paths"))
+                        .color(*CYAN)
+                        .strong();
+                    if ui.add_enabled(any_selected, Button::new(text)).clicked() {
+                        copy_selected(interface, CopyField::Path, ui);
+                        ui.close_menu();
+                    }
+                    let text = RichText::new(tr("Speed rules")).color(*CYAN).strong();
+                    if ui.button(text).clicked() {
+                        interface.popups.rules.show = true;
+                    }
+                    let text = RichText::new(tr("Bandwidth schedule"))
+                        .color(*CYAN)
+                        .strong();
+                    if ui.button(text).clicked() {
+                        interface.popups.schedule.show = true;
                     }
-                    let text = RichText::new("Pause all").color(*CYAN).strong();
+                    let text = RichText::new(tr("Details panel")).color(*CYAN).strong();
+                    ui.checkbox(&mut interface.details.show, text);
+                    let text = RichText::new(tr("Columns")).color(*CYAN).strong();
                     if ui.button(text).clicked() {
-                        for core in interface.files.iter_mut() {
-                            core.file
-                                .running
-                                .store(false, std::sync::atomic::Ordering::Relaxed);
+                        interface.popups.columns.show = true;
+                        ui.close_menu();
+                    }
+                    let text = RichText::new(tr("History")).color(*CYAN).strong();
+                    if ui.button(text).clicked() {
+                        open_history(interface);
+                        ui.close_menu();
+                    }
+                    let text = RichText::new(tr("Statistics")).color(*CYAN).strong();
+                    if ui.button(text).clicked() {
+                        open_statistics(interface);
+                        ui.close_menu();
+                    }
+                });
+                let text = RichText::new(tr("Server")).color(*CYAN).strong().size(15.0);
+                ui.menu_button(text, |ui| {
+                    let text = RichText::new(tr("API tokens")).color(*CYAN).strong();
+                    if ui.button(text).clicked() {
+                        interface.popups.tokens.show = true;
+                    }
+                });
+                let text = RichText::new(tr("Theme")).color(*CYAN).strong().size(15.0);
+                ui.menu_button(text, |ui| {
+                    for theme in Theme::ALL {
+                        let text = RichText::new(tr(theme.label())).color(*CYAN).strong();
+                        let picked = interface.settings.theme == theme;
+                        if ui.selectable_label(picked, text).clicked() && !picked {
+                            set_theme(interface, theme);
+                            ui.close_menu();
                         }
                     }
+                    ui.separator();
+                    let text = RichText::new(tr("Edit custom theme")).color(*CYAN).strong();
+                    if ui.button(text).clicked() {
+                        //starts from what's on screen so tweaking the current theme is one step
+                        interface.popups.theme_editor.palette = *palette();
+                        interface.popups.theme_editor.show = true;
+                        ui.close_menu();
+                    }
+                });
+                let text = RichText::new(tr("Help")).color(*CYAN).strong().size(15.0);
+                ui.menu_button(text, |ui| {
+                    let text = RichText::new(tr("Keyboard shortcuts"))
+                        .color(*CYAN)
+                        .strong();
+                    ui.label(text);
+                    ui.separator();
+                    Grid::new("shortcuts").show(ui, |ui| {
+                        for (shortcut, action) in SHORTCUTS {
+                            ui.colored_label(*GRAY, ui.ctx().format_shortcut(&shortcut));
+                            ui.colored_label(*CYAN, tr(action));
+                            ui.end_row();
+                        }
+                    });
                 });
             });
             ui.add_space(7.0);
@@ -41,82 +168,413 @@ pub fn init_menu_bar(interface: &mut DownloadManager, ui: &mut egui_sfml::egui::
     });
 }
 
+//applied on the next frame
+fn set_theme(interface: &mut DownloadManager, theme: Theme) {
+    interface.settings.theme = theme;
+    if let Err(e) = interface.settings.save() {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!("Couldn't save the theme: {}", e);
+        interface.popups.log.logs.push((formatted_time, text, *RED));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyField {
+    Url,
+    Path,
+}
+
+impl CopyField {
+    pub fn of(&self, file: &File2Dl) -> String {
+        match self {
+            Self::Url => file.url.link.clone(),
+            Self::Path => file_path(file),
+        }
+    }
+}
+
+//shared by the menu bar and the row menu
+pub fn selection_menu(ui: &mut egui_sfml::egui::Ui) -> Option<SelectionAction> {
+    let mut action = None;
+    for selection in SelectionAction::ALL {
+        let text = RichText::new(tr(selection.label())).color(*CYAN).strong();
+        if ui.button(text).clicked() {
+            action = Some(selection);
+            ui.close_menu();
+        }
+    }
+    action
+}
+
+//one line per selected row
+pub fn copy_selected(interface: &DownloadManager, field: CopyField, ui: &egui_sfml::egui::Ui) {
+    let text = interface
+        .files
+        .iter()
+        .filter(|f| f.selected)
+        .map(|f| field.of(&f.file))
+        .collect::<Vec<_>>()
+        .join("\n");
+    ui.output_mut(|o| o.copied_text = text);
+}
+
+pub fn confirm_remove_selected(interface: &mut DownloadManager) {
+    ask_confirm(
+        interface,
+        Some(ConfirmKind::RemoveFromList),
+        Color32::GREEN,
+        String::from(tr("This will remove files selected from list")),
+        Box::new(|| {
+            Box::new(move |app: &mut DownloadManager| {
+                app.files.retain(|core| !core.selected);
+            })
+        }),
+    );
+}
+
 fn file_button_content(interface: &mut DownloadManager, ui: &mut egui_sfml::egui::Ui) {
-    let text = RichText::new("Remove selected from list")
+    let text = RichText::new(tr("Remove selected from list"))
         .color(*CYAN)
         .strong();
     if ui.button(text).clicked() {
-        interface.popups.confirm.color = Color32::GREEN;
-        interface.popups.confirm.task = Box::new(|| {
-            Box::new(move |app: &mut DownloadManager| {
-                app.files.retain(|core| !core.selected);
-            })
-        });
-        interface.popups.confirm.show = true;
-        interface.popups.confirm.text = String::from("This will remove files selected from list")
+        confirm_remove_selected(interface);
     }
-    let text = RichText::new("Remove selected from disk")
+    let text = RichText::new(tr("Remove selected from disk"))
         .color(*CYAN)
         .strong();
     if ui.button(text).clicked() {
-        interface.popups.confirm.color = *RED;
-        interface.popups.confirm.task = Box::new(|| {
-            Box::new(move |app: &mut DownloadManager| {
-                remove_selected_from_disk(app);
-            })
-        });
-        interface.popups.confirm.show = true;
-        interface.popups.confirm.text = String::from("This will remove selected files from disk")
+        ask_confirm(
+            interface,
+            Some(ConfirmKind::RemoveFromDisk),
+            *RED,
+            String::from(tr("This will remove selected files from disk")),
+            Box::new(|| {
+                Box::new(move |app: &mut DownloadManager| {
+                    remove_selected_from_disk(app);
+                })
+            }),
+        );
     }
-    let text = RichText::new("Remove all from list").color(*CYAN).strong();
+    let text = RichText::new(tr("Remove all from list"))
+        .color(*CYAN)
+        .strong();
     if ui.button(text).clicked() {
-        interface.popups.confirm.color = *GREEN;
-        interface.popups.confirm.task = Box::new(|| {
-            Box::new(move |app: &mut DownloadManager| {
-                app.files.clear();
-            })
-        });
-        interface.popups.confirm.show = true;
-        interface.popups.confirm.text = "This will not delete files from disk".to_string();
+        ask_confirm(
+            interface,
+            Some(ConfirmKind::RemoveFromList),
+            *GREEN,
+            tr("This will not delete files from disk").to_string(),
+            Box::new(|| {
+                Box::new(move |app: &mut DownloadManager| {
+                    app.files.clear();
+                })
+            }),
+        );
     }
-    let text = RichText::new("Remove all from disk").color(*CYAN).strong();
+    let text = RichText::new(tr("Remove all from disk"))
+        .color(*CYAN)
+        .strong();
     if ui.button(text).clicked() {
-        interface.popups.confirm.color = *RED;
-        interface.popups.confirm.task = Box::new(|| {
-            Box::new(move |app: &mut DownloadManager| {
-                delete_all_files_from_disk(app);
-            })
-        });
-        interface.popups.confirm.show = true;
-        interface.popups.confirm.text = String::from("This will remove all files from disk")
+        ask_confirm(
+            interface,
+            Some(ConfirmKind::RemoveFromDisk),
+            *RED,
+            String::from(tr("This will remove all files from disk")),
+            Box::new(|| {
+                Box::new(move |app: &mut DownloadManager| {
+                    delete_all_files_from_disk(app);
+                })
+            }),
+        );
     }
-    let text = RichText::new("Remove complete from list")
+    let text = RichText::new(tr("Remove complete from list"))
         .color(*CYAN)
         .strong();
     if ui.button(text).clicked() {
-        interface.popups.confirm.color = *GREEN;
-        interface.popups.confirm.task = Box::new(|| {
-            Box::new(move |app: &mut DownloadManager| {
-                delete_complete_from_list(app);
-            })
-        });
-        interface.popups.confirm.show = true;
-        interface.popups.confirm.text =
-            String::from("This will remove all complete files from list")
+        ask_confirm(
+            interface,
+            Some(ConfirmKind::RemoveFromList),
+            *GREEN,
+            String::from(tr("This will remove all complete files from list")),
+            Box::new(|| {
+                Box::new(move |app: &mut DownloadManager| {
+                    delete_complete_from_list(app);
+                })
+            }),
+        );
     }
-    let text = RichText::new("Remove complete from disk")
+    let text = RichText::new(tr("Remove complete from disk"))
         .color(*CYAN)
         .strong();
     if ui.button(text).clicked() {
-        interface.popups.confirm.color = *RED;
-        interface.popups.confirm.task = Box::new(|| {
-            Box::new(move |app: &mut DownloadManager| {
-                delete_complete_from_disk(app);
-            })
-        });
-        interface.popups.confirm.show = true;
-        interface.popups.confirm.text =
-            String::from("This will remove all complete files from disk")
+        ask_confirm(
+            interface,
+            Some(ConfirmKind::RemoveFromDisk),
+            *RED,
+            String::from(tr("This will remove all complete files from disk")),
+            Box::new(|| {
+                Box::new(move |app: &mut DownloadManager| {
+                    delete_complete_from_disk(app);
+                })
+            }),
+        );
+    }
+    ui.separator();
+    let text = RichText::new(tr("Export list")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        export_download_list(interface);
+    }
+    let text = RichText::new(tr("Import list")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        import_download_list(interface);
+    }
+    ui.separator();
+    let text = RichText::new(tr("Export backup")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        export_state_backup(interface);
+    }
+    let text = RichText::new(tr("Restore backup")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        restore_state_backup(interface);
+    }
+    ui.separator();
+    //pauses and saves everything, unlike the close button which may only hide the window
+    let text = RichText::new(tr("Quit")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        quit(interface);
+    }
+}
+fn export_state_backup(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let path = match FileDialog::new()
+        .set_filename("dl-manager-backup.zip")
+        .add_filter("Zip", &["zip"])
+        .show_save_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            let err = format!("Couldn't open save dialog: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+            return;
+        }
+    };
+    match export_backup(interface, &path) {
+        Ok(_) => {
+            let text = format!("Exported backup to {}", path.display());
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text, *GREEN));
+        }
+        Err(e) => {
+            let err = format!("File Path: {}, Error: {}\n", path.display(), e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+        }
+    }
+}
+fn restore_state_backup(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let path = match FileDialog::new()
+        .add_filter("Zip", &["zip"])
+        .show_open_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            let err = format!("Couldn't open file dialog: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+            return;
+        }
+    };
+    let mut secret = String::new();
+    let restored = read_backup(&path).and_then(|backup| {
+        secret = backup.settings.server_secret.clone();
+        restore_backup(interface, backup)
+    });
+    match restored {
+        Ok((added, skipped)) => {
+            let text = format!(
+                "Restored backup from {}, added {} downloads, skipped {} duplicates",
+                path.display(),
+                added,
+                skipped
+            );
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text, *GREEN));
+            if secret.is_empty() || secret == interface.settings.server_secret {
+                return;
+            }
+            ask_confirm(
+                interface,
+                None,
+                *RED,
+                String::from(tr(
+                    "The backup has another server secret, use it? The current one stops working",
+                )),
+                Box::new(move || {
+                    let secret = secret.clone();
+                    Box::new(move |app: &mut DownloadManager| {
+                        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+                        let (text, color) = match import_server_secret(app, secret) {
+                            Ok(()) => (
+                                String::from("Took the server secret from the backup"),
+                                *GREEN,
+                            ),
+                            Err(e) => (format!("Couldn't write to file: {:?}", e), *RED),
+                        };
+                        app.popups.log.logs.push((formatted_time, text, color));
+                    })
+                }),
+            );
+        }
+        Err(e) => {
+            let err = format!("File Path: {}, Error: {}\n", path.display(), e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+        }
+    }
+}
+fn export_download_list(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let path = match FileDialog::new()
+        .set_filename("downloads.json")
+        .add_filter("JSON", &["json"])
+        .show_save_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            let err = format!("Couldn't open save dialog: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+            return;
+        }
+    };
+    match export_list(&interface.files, &path) {
+        Ok(_) => {
+            let text = format!(
+                "Exported {} downloads to {}",
+                interface.files.len(),
+                path.display()
+            );
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text, *GREEN));
+        }
+        Err(e) => {
+            let err = format!("File Path: {}, Error: {}\n", path.display(), e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+        }
+    }
+}
+fn import_download_list(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let path = match FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .show_open_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            let err = format!("Couldn't open file dialog: {}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+            return;
+        }
+    };
+    match import_list(&path, &interface.settings.dl_dir) {
+        Ok(files) => {
+            let mut added = 0;
+            let mut skipped = 0;
+            for file in files {
+                let duplicate = interface
+                    .files
+                    .iter()
+                    .any(|f| f.file.url.link == file.url.link || f.file.same_file(&file));
+                if duplicate {
+                    skipped += 1;
+                    continue;
+                }
+                let complete = file.complete.load(std::sync::atomic::Ordering::Relaxed);
+                interface.files.push(FDl {
+                    file,
+                    initial_status: complete,
+                    ..Default::default()
+                });
+                added += 1;
+            }
+            let text = format!(
+                "Imported {} downloads from {}, skipped {} duplicates",
+                added,
+                path.display(),
+                skipped
+            );
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, text, *GREEN));
+        }
+        Err(e) => {
+            let err = format!("File Path: {}, Error: {}\n", path.display(), e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+        }
     }
 }
 fn delete_all_files_from_disk(interface: &mut DownloadManager) {
@@ -125,7 +583,6 @@ fn delete_all_files_from_disk(interface: &mut DownloadManager) {
         let name_on_disk = &fdl.file.name_on_disk;
         let dir = &fdl.file.dl_dir;
         let path = format!("{dir}/{}", name_on_disk);
-        let tmp_path = format!("{dir}/.{}.metadl", name_on_disk);
         let now = Local::now();
         let formatted_time = now.format("%H:%M:%S").to_string();
         if Path::new(&path).exists() {
@@ -137,6 +594,7 @@ fn delete_all_files_from_disk(interface: &mut DownloadManager) {
                         .log
                         .logs
                         .push((formatted_time.clone(), text, *GREEN));
+                    record_history(&mut interface.popups.log, fdl, Outcome::Deleted);
                 }
                 Err(e) => {
                     let err = format!("File Path: {}, Error: {}\n", path, e);
@@ -151,28 +609,16 @@ fn delete_all_files_from_disk(interface: &mut DownloadManager) {
                 }
             }
         }
-        if Path::new(&tmp_path).exists() {
-            match remove_file(&tmp_path) {
-                Ok(_) => {
-                    let text = format!("Deleted tmp file: {}\n", tmp_path);
-                    interface
-                        .popups
-                        .log
-                        .logs
-                        .push((formatted_time.clone(), text, *GREEN));
-                }
-                Err(e) => {
-                    let err = format!("File Path: {}, Error: {}\n", tmp_path, e);
-                    interface
-                        .popups
-                        .log
-                        .logs
-                        .push((formatted_time, err.clone(), *RED));
-                    interface.popups.error.value = err;
-                    interface.popups.error.show = true;
-                    is_ok = false;
-                }
-            }
+        if let Err(e) = remove_metadata(&fdl.file) {
+            let err = format!("Couldn't remove the record of {}: {}\n", name_on_disk, e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time, err.clone(), *RED));
+            interface.popups.error.value = err;
+            interface.popups.error.show = true;
+            is_ok = false;
         }
     }
     if is_ok {
@@ -184,8 +630,7 @@ fn remove_selected_from_disk(app: &mut DownloadManager) {
         if core.selected {
             let now = Local::now();
             let formatted_time = now.format("%H:%M:%S").to_string();
-            let path = format!("{}/{}", app.settings.dl_dir, core.file.name_on_disk);
-            let tmp_path = format!("{}/.{}.metadl", app.settings.dl_dir, core.file.name_on_disk);
+            let path = format!("{}/{}", core.file.dl_dir, core.file.name_on_disk);
             match remove_file(&path) {
                 Ok(_) => {
                     let text = format!("File: {} was removed\n", &path);
@@ -193,6 +638,7 @@ fn remove_selected_from_disk(app: &mut DownloadManager) {
                         .log
                         .logs
                         .push((formatted_time.clone(), text, *GREEN));
+                    record_history(&mut app.popups.log, core, Outcome::Deleted);
                 }
                 Err(e) => {
                     let err = format!("File Path: {}, Error: {}\n", &path, e);
@@ -204,20 +650,17 @@ fn remove_selected_from_disk(app: &mut DownloadManager) {
                     app.popups.error.show = true;
                 }
             }
-            match remove_file(&tmp_path) {
-                Ok(_) => {
-                    let text = format!("Tmp File: {} was removed\n", &tmp_path);
-                    app.popups.log.logs.push((formatted_time, text, *GREEN));
-                }
-                Err(e) => {
-                    let err = format!("File Path: {}, Error: {}\n", &tmp_path, e);
-                    app.popups
-                        .log
-                        .logs
-                        .push((formatted_time, err.clone(), *RED));
-                    app.popups.error.value = err;
-                    app.popups.error.show = true;
-                }
+            if let Err(e) = remove_metadata(&core.file) {
+                let err = format!(
+                    "Couldn't remove the record of {}: {}\n",
+                    core.file.name_on_disk, e
+                );
+                app.popups
+                    .log
+                    .logs
+                    .push((formatted_time, err.clone(), *RED));
+                app.popups.error.value = err;
+                app.popups.error.show = true;
             }
             return false;
         }
@@ -245,7 +688,6 @@ fn delete_complete_from_disk(app: &mut DownloadManager) {
         let complete = file.complete.load(std::sync::atomic::Ordering::Relaxed);
         if !complete {
             let location = format!("{}/{}", file.dl_dir, file.name_on_disk);
-            let tmp_location = format!("{}/.{}.metadl", file.dl_dir, file.name_on_disk);
             if Path::new(&location).exists() {
                 if let Err(e) = remove_file(&location) {
                     let formatted_error = format!("{}: {:?}", location, &e);
@@ -255,18 +697,18 @@ fn delete_complete_from_disk(app: &mut DownloadManager) {
                         .log
                         .logs
                         .push((formatted_time.clone(), formatted_error, *RED));
+                } else {
+                    record_history(&mut app.popups.log, fdl, Outcome::Deleted);
                 }
             }
-            if Path::new(&tmp_location).exists() {
-                if let Err(e) = remove_file(&tmp_location) {
-                    let formatted_error = format!("{}: {:?}", tmp_location, &e);
-                    app.popups.error.value = formatted_error.clone();
-                    app.popups.error.show = true;
-                    app.popups
-                        .log
-                        .logs
-                        .push((formatted_time.clone(), formatted_error, *RED));
-                }
+            if let Err(e) = remove_metadata(file) {
+                let formatted_error = format!("{}: {:?}", file.name_on_disk, &e);
+                app.popups.error.value = formatted_error.clone();
+                app.popups.error.show = true;
+                app.popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), formatted_error, *RED));
             }
         }
     }
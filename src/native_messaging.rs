@@ -0,0 +1,316 @@
+use crate::Settings;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    io::{Error, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::{HSTRING, PCWSTR},
+    Win32::{
+        Foundation::ERROR_SUCCESS,
+        System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ},
+    },
+};
+
+const HOST_NAME: &str = "rusty_dl_manager";
+const MANIFEST: &str = "rusty_dl_manager.json";
+//the companion extension's id in firefox, see web_ext/manifest.json
+const FIREFOX_EXTENSION: &str = "downloadinterceptor@hellzeras.com";
+//browsers never take more than 1 MB back, a link with its cookies is nowhere near that either
+const MAX_FRAME: u32 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Browser {
+    Firefox,
+    Chrome,
+}
+
+//what the extension sends for every download it hands off
+#[derive(Debug, Deserialize)]
+struct HostMessage {
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    page_url: String,
+    #[serde(default)]
+    cookies: String,
+    #[serde(default)]
+    referrer: String,
+    #[serde(default)]
+    user_agent: String,
+}
+
+//chrome passes the calling extension's origin, firefox the path of the manifest and the extension id
+fn is_host_launch() -> bool {
+    std::env::args().skip(1).any(|arg| {
+        arg == "--native-messaging"
+            || arg.starts_with("chrome-extension://")
+            || Path::new(&arg)
+                .file_name()
+                .is_some_and(|name| name == MANIFEST)
+    })
+}
+
+//answers the extension until it disconnects, None when the browser didn't start this launch
+pub fn run_native_host() -> Option<i32> {
+    if !is_host_launch() {
+        return None;
+    }
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Some(1);
+        }
+    };
+    let client = Client::new();
+    let mut input = std::io::stdin().lock();
+    let mut output = std::io::stdout().lock();
+    loop {
+        let message = match read_frame(&mut input) {
+            Ok(Some(message)) => message,
+            //the browser closed the port
+            Ok(None) => return Some(0),
+            Err(e) => {
+                eprintln!("{}", e);
+                return Some(1);
+            }
+        };
+        let result = match serde_json::from_slice::<HostMessage>(&message) {
+            Ok(message) => runtime.block_on(forward(&client, &message)),
+            Err(e) => Err(format!("Not a download: {}", e)),
+        };
+        let reply = match result {
+            Ok(()) => json!({ "ok": true }),
+            Err(e) => json!({ "ok": false, "error": e }),
+        };
+        if let Err(e) = write_frame(&mut output, &reply) {
+            eprintln!("{}", e);
+            return Some(1);
+        }
+    }
+}
+
+//frames are a length in native byte order followed by that much json, none is left at the end
+fn read_frame(input: &mut impl Read) -> Result<Option<Vec<u8>>, Error> {
+    let mut length = [0u8; 4];
+    match input.read_exact(&mut length) {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    let length = u32::from_ne_bytes(length);
+    if length > MAX_FRAME {
+        let text = format!("A message of {} bytes is too long", length);
+        return Err(Error::new(ErrorKind::InvalidData, text));
+    }
+    let mut message = vec![0; length as usize];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+fn write_frame(output: &mut impl Write, message: &Value) -> Result<(), Error> {
+    let message = message.to_string();
+    output.write_all(&(message.len() as u32).to_ne_bytes())?;
+    output.write_all(message.as_bytes())?;
+    output.flush()
+}
+
+//goes through the server like the extension's own requests, so the link opens in the download window
+async fn forward(client: &Client, message: &HostMessage) -> Result<(), String> {
+    let body = json!({
+        "value": message.url.trim(),
+        "title": message.title,
+        "page_url": message.page_url,
+        "cookies": message.cookies,
+        "referrer": message.referrer,
+        "user_agent": message.user_agent,
+    })
+    .to_string();
+    let mut launched = false;
+    //about ten seconds for a freshly started app to open its server
+    for _ in 0..40 {
+        //the host runs as the same user, the port and the secret come from the app's settings,
+        //read again each time since a first start only writes them once it's up
+        let (server, secret) = Settings::local_server();
+        let request = client
+            .post(server)
+            .header(CONTENT_TYPE, "application/json")
+            .header("x-api-token", secret)
+            .body(body.clone());
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => return Err(format!("The app answered {}", response.status())),
+            Err(_) => {
+                if !launched {
+                    launch_app().map_err(|e| format!("Couldn't start the app: {}", e))?;
+                    launched = true;
+                }
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+        }
+    }
+    Err(String::from("The app didn't start in time"))
+}
+
+//started apart from the host, which the browser ends as soon as the extension is done with it
+fn launch_app() -> Result<(), Error> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    //chrome puts hosts in a job that's closed with them, DETACHED_PROCESS | CREATE_BREAKAWAY_FROM_JOB
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x0000_0008 | 0x0100_0000);
+    }
+    command.spawn().map(drop)
+}
+
+//folders the browser looks for host manifests in
+#[cfg(target_os = "linux")]
+fn manifest_dirs(browser: Browser) -> Vec<PathBuf> {
+    match browser {
+        Browser::Firefox => dirs_next::home_dir()
+            .map(|home| vec![home.join(".mozilla").join("native-messaging-hosts")])
+            .unwrap_or_default(),
+        Browser::Chrome => dirs_next::config_dir()
+            .map(|config| {
+                vec![
+                    config.join("google-chrome").join("NativeMessagingHosts"),
+                    config.join("chromium").join("NativeMessagingHosts"),
+                ]
+            })
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn manifest_dirs(browser: Browser) -> Vec<PathBuf> {
+    let Some(support) = dirs_next::home_dir().map(|home| home.join("Library/Application Support"))
+    else {
+        return Vec::new();
+    };
+    match browser {
+        Browser::Firefox => vec![support.join("Mozilla").join("NativeMessagingHosts")],
+        Browser::Chrome => vec![
+            support.join("Google/Chrome").join("NativeMessagingHosts"),
+            support.join("Chromium").join("NativeMessagingHosts"),
+        ],
+    }
+}
+
+//any folder works on windows, the registry says where the manifest is
+#[cfg(target_os = "windows")]
+fn manifest_dirs(browser: Browser) -> Vec<PathBuf> {
+    let dir = match browser {
+        Browser::Firefox => "firefox",
+        Browser::Chrome => "chrome",
+    };
+    let native_messaging = crate::dl::storage::data_file("native-messaging");
+    std::env::current_dir()
+        .map(|cwd| vec![cwd.join(native_messaging).join(dir)])
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn manifest_dirs(_browser: Browser) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+fn register(browser: Browser, manifest: &Path) -> Result<(), Error> {
+    let key = match browser {
+        Browser::Firefox => format!("Software\\Mozilla\\NativeMessagingHosts\\{}", HOST_NAME),
+        Browser::Chrome => format!(
+            "Software\\Google\\Chrome\\NativeMessagingHosts\\{}",
+            HOST_NAME
+        ),
+    };
+    let path = manifest
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect::<Vec<u16>>();
+    //the manifest's path goes in the key's default value
+    let status = unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(key),
+            PCWSTR::null(),
+            REG_SZ.0,
+            Some(path.as_ptr() as _),
+            (path.len() * 2) as u32,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return Err(Error::from_raw_os_error(status.0 as i32));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn register(_browser: Browser, _manifest: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
+//points the browsers at this executable, chrome only knows the extension by the id it was given when loaded
+pub fn install_native_host(chrome_extension: Option<&str>) -> Result<Vec<PathBuf>, Error> {
+    let exe = std::env::current_exe()?;
+    let mut manifests = vec![(
+        Browser::Firefox,
+        json!({
+            "name": HOST_NAME,
+            "description": "Rusty Dl Manager",
+            "path": exe,
+            "type": "stdio",
+            "allowed_extensions": [FIREFOX_EXTENSION],
+        }),
+    )];
+    if let Some(id) = chrome_extension {
+        manifests.push((
+            Browser::Chrome,
+            json!({
+                "name": HOST_NAME,
+                "description": "Rusty Dl Manager",
+                "path": exe,
+                "type": "stdio",
+                "allowed_origins": [format!("chrome-extension://{}/", id.trim())],
+            }),
+        ));
+    }
+    let mut written = Vec::new();
+    for (browser, manifest) in manifests {
+        let dirs = manifest_dirs(browser);
+        if dirs.is_empty() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "native messaging isn't supported on this platform",
+            ));
+        }
+        for dir in dirs {
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(MANIFEST);
+            std::fs::write(&path, serde_json::to_string_pretty(&manifest)?)?;
+            register(browser, &path)?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
@@ -0,0 +1,104 @@
+use crate::{
+    colors::RED,
+    dl::{file2dl::FileKey, storage::data_file},
+    i18n::tr,
+    table::StatusFilter,
+    DownloadManager,
+};
+use chrono::Local;
+use notify_rust::Notification;
+use std::{
+    fs::{read, write},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        LazyLock,
+    },
+};
+
+const ICON: &[u8] = include_bytes!("../icon.png");
+//notification servers take a path, so the bundled icon is written out with the rest of the state
+static ICON_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    let path = data_file("icon.png");
+    if read(&path).ok().as_deref() != Some(ICON) {
+        write(&path, ICON).ok()?;
+    }
+    Some(path.to_string_lossy().to_string())
+});
+
+pub enum NotificationEvent {
+    //the download whose notification was clicked, only freedesktop reports clicks
+    #[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+    Clicked(FileKey),
+    Failed(String),
+}
+
+pub struct Notifications {
+    pub channel: (Sender<NotificationEvent>, Receiver<NotificationEvent>),
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self { channel: channel() }
+    }
+}
+
+//waiting on the click blocks until the notification is gone, so every one gets its own thread
+pub fn notify(notifications: &Notifications, summary: &str, body: String, key: &FileKey) {
+    let summary = summary.to_string();
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let key = key.clone();
+    //clicks don't come back elsewhere, so the way back to the window is spelled out
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let body = {
+        let _ = key;
+        format!("{}\n{}", body, tr("Open the window from the tray icon"))
+    };
+    let tx = notifications.channel.0.clone();
+    std::thread::spawn(move || {
+        let mut notification = Notification::new();
+        notification
+            .appname("Rusty Dl Manager")
+            .summary(&summary)
+            .body(&body);
+        if let Some(icon) = ICON_PATH.as_ref() {
+            notification.icon(icon);
+        }
+        //only freedesktop notifications report clicks back
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.action("default", tr("Show"));
+        match notification.show() {
+            #[cfg(all(unix, not(target_os = "macos")))]
+            Ok(handle) => handle.wait_for_action(|action| {
+                if action == "default" {
+                    let _ = tx.send(NotificationEvent::Clicked(key));
+                }
+            }),
+            #[cfg(not(all(unix, not(target_os = "macos"))))]
+            Ok(_) => {}
+            Err(e) => {
+                let _ = tx.send(NotificationEvent::Failed(format!("{:?}", e)));
+            }
+        }
+    });
+}
+
+pub fn check_notifications(interface: &mut DownloadManager) {
+    while let Ok(event) = interface.notifications.channel.1.try_recv() {
+        match event {
+            NotificationEvent::Clicked(key) => {
+                interface.show_window = true;
+                //the row could be filtered out by the tab that was left open
+                interface.status_filter = StatusFilter::default();
+                for fdl in interface.files.iter_mut() {
+                    fdl.selected = fdl.file.is(&key);
+                }
+                interface.select.anchor = Some(key);
+            }
+            NotificationEvent::Failed(e) => {
+                let formatted_time = Local::now().format("%H:%M:%S").to_string();
+                let text = format!("Notification error: {}", e);
+                interface.popups.log.logs.push((formatted_time, text, *RED));
+            }
+        }
+    }
+}
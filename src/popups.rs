@@ -1,13 +1,32 @@
 use crate::{
-    dl::file2dl::File2Dl,
-    download_mechanism::Actions,
+    close::CloseAction,
+    colors::Palette,
+    dl::{
+        browser::BrowserContext,
+        disk::FsyncPolicy,
+        file2dl::{File2Dl, FileKey},
+        limiter::{LimitSplit, SpeedUnit},
+        net::{DnsResolver, HttpVersion, IpPreference},
+        stats::Period,
+        store::HistoryEntry,
+    },
+    download_mechanism::{Actions, SizeGuard, StartMode},
     extern_windows::{
-        show_confirm_window, show_error_window, show_input_window, show_log_window,
-        show_modify_speed_window, show_plot_window, show_settings_window,
+        show_api_tokens_window, show_bandwidth_schedule_window, show_cancel_window,
+        show_close_window, show_columns_window, show_confirm_window, show_duplicate_window,
+        show_error_window, show_history_window, show_input_window, show_intercept_window,
+        show_log_window, show_modify_speed_window, show_note_window, show_plot_window,
+        show_scan_warning_window, show_settings_window, show_speed_rules_window,
+        show_statistics_window, show_tags_window, show_theme_editor_window, show_update_url_window,
     },
+    i18n::Language,
+    server::{auth::TokenScope, interception::Intercepted},
+    sound::SoundSettings,
+    speed_history::HistoryRange,
     DownloadManager,
 };
 use egui_sfml::egui::Color32;
+use serde::{Deserialize, Serialize};
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 type TaskInner = Box<dyn FnOnce(&mut DownloadManager)>;
@@ -18,6 +37,36 @@ pub struct SettingsPopUp {
     pub show: bool,
     pub temp_str: String,
     pub dl_dir: String,
+    pub watch_dir: String,
+    pub drawer_delay: String,
+    pub max_size_gb: String,
+    pub size_guard: SizeGuard,
+    pub ca_cert: String,
+    pub dns: DnsResolver,
+    pub dns_servers: String,
+    pub ip_preference: IpPreference,
+    pub http_version: HttpVersion,
+    pub pool_size: String,
+    pub proxy: String,
+    pub connect_timeout: String,
+    pub read_timeout: String,
+    pub stall_timeout: String,
+    pub write_buffer_kb: String,
+    pub fsync: FsyncPolicy,
+    pub global_limit: String,
+    pub limit_split: LimitSplit,
+    pub auto_resume: bool,
+    pub auto_extract: bool,
+    pub virustotal_key: String,
+    pub language: Language,
+    pub skip_confirm: Vec<ConfirmKind>,
+    pub sound: SoundSettings,
+    pub close_action: CloseAction,
+    pub start_hidden: bool,
+    pub autostart: bool,
+    pub tray_progress: bool,
+    pub server_port: String,
+    pub trusted_sites: Vec<String>,
     pub error: String,
 }
 
@@ -27,11 +76,31 @@ pub struct LogPopUp {
     pub logs: Vec<(String, String, Color32)>,
     pub show: bool,
 }
+//confirmations that can be turned off with "don't ask again"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ConfirmKind {
+    RemoveFromList,
+    RemoveFromDisk,
+}
+
+impl ConfirmKind {
+    pub const ALL: [Self; 2] = [Self::RemoveFromList, Self::RemoveFromDisk];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::RemoveFromList => "Ask before removing from the list",
+            Self::RemoveFromDisk => "Ask before deleting from disk",
+        }
+    }
+}
+
 pub struct ConfirmPopUp {
     pub text: String,
     pub color: Color32,
     pub show: bool,
     pub task: Task,
+    pub kind: Option<ConfirmKind>,
+    pub dont_ask: bool,
 }
 impl Default for ConfirmPopUp {
     fn default() -> Self {
@@ -40,13 +109,47 @@ impl Default for ConfirmPopUp {
             color: Color32::default(),
             show: false,
             task: Box::new(|| Box::new(|_app: &mut DownloadManager| {})),
+            kind: None,
+            dont_ask: false,
         }
     }
 }
 
+//runs the task right away when the user asked not to be asked about this kind again
+pub fn ask_confirm(
+    interface: &mut DownloadManager,
+    kind: Option<ConfirmKind>,
+    color: Color32,
+    text: String,
+    task: Task,
+) {
+    if kind.is_some_and(|kind| interface.settings.skip_confirm.contains(&kind)) {
+        task()(interface);
+        return;
+    }
+    interface.popups.confirm = ConfirmPopUp {
+        text,
+        color,
+        show: true,
+        task,
+        kind,
+        dont_ask: false,
+    };
+}
+
+#[derive(Debug, Default)]
+pub struct DuplicatePopUp {
+    pub show: bool,
+    pub link: String,
+}
+
 #[derive(Debug, Default)]
 pub struct PLotPopUp {
     pub show: bool,
+    pub range: HistoryRange,
+    pub hide_total: bool,
+    //names on disk of the files drawn over the total
+    pub series: Vec<String>,
 }
 
 #[derive(Default)]
@@ -54,13 +157,105 @@ pub struct EditSpeedPopUp {
     pub show: bool,
     pub error: String,
     pub temp_val: String,
+    pub unit: SpeedUnit,
+}
+
+#[derive(Default)]
+pub struct SpeedRulesPopUp {
+    pub show: bool,
+    pub error: String,
+    pub exts: String,
+    pub limit: String,
+    pub start_hour: String,
+    pub end_hour: String,
+}
+
+#[derive(Default)]
+pub struct CancelPopUp {
+    pub show: bool,
+    pub key: FileKey,
+}
+
+#[derive(Default)]
+pub struct ColumnsPopUp {
+    pub show: bool,
+    pub error: String,
+}
+
+#[derive(Default)]
+pub struct ThemeEditorPopUp {
+    pub show: bool,
+    //edited here and only written to the settings on save
+    pub palette: Palette,
+    pub error: String,
+}
+
+#[derive(Default)]
+pub struct NotePopUp {
+    pub show: bool,
+    pub key: FileKey,
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct ClosePopUp {
+    pub show: bool,
+    pub remember: bool,
+}
+
+#[derive(Default)]
+pub struct UpdateUrlPopUp {
+    pub show: bool,
+    pub key: FileKey,
+    pub link: String,
+    pub error: String,
+}
+
+//a probed link from the browser, added only once it's accepted
+#[derive(Default)]
+pub struct InterceptPopUp {
+    pub show: bool,
+    pub intercepted: Intercepted,
+    pub file: Option<File2Dl>,
+    //name the file will be saved as, editable before accepting
+    pub rename: String,
+    pub always_accept: bool,
+    pub start_mode: StartMode,
 }
 
+#[derive(Default)]
+pub struct TagsPopUp {
+    pub show: bool,
+    pub key: FileKey,
+    //comma separated while being edited
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct ScanWarningPopUp {
+    pub show: bool,
+    pub key: FileKey,
+    pub malicious: u64,
+    pub suspicious: u64,
+}
+
+#[derive(Default)]
+pub struct BandwidthSchedulePopUp {
+    pub show: bool,
+    pub error: String,
+    pub limit: String,
+    pub start_hour: String,
+    pub end_hour: String,
+}
+
+//the file and what went wrong
+type TransferError = (FileKey, String);
+
 #[derive(Debug)]
 pub struct ErrorPopUp {
     pub value: String,
     pub show: bool,
-    pub channel: (Sender<String>, Receiver<String>),
+    pub channel: (Sender<TransferError>, Receiver<TransferError>),
 }
 impl Default for ErrorPopUp {
     fn default() -> Self {
@@ -71,39 +266,142 @@ impl Default for ErrorPopUp {
         }
     }
 }
+#[derive(Default)]
+pub struct ApiTokensPopUp {
+    pub show: bool,
+    pub error: String,
+    pub name: String,
+    pub scope: TokenScope,
+}
+
+#[derive(Default)]
+pub struct HistoryPopUp {
+    pub show: bool,
+    pub search: String,
+    //loaded from the store whenever the window is opened
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Default)]
+pub struct StatisticsPopUp {
+    pub show: bool,
+    pub period: Period,
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug)]
+pub enum BatchStatus {
+    Probing,
+    Ready(Box<File2Dl>),
+    Failed(String),
+}
+
+//one line of a multi link paste
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub link: String,
+    pub status: BatchStatus,
+}
+
 #[derive(Debug)]
 pub struct DownloadPopUp {
     pub link: String,
+    pub page_title: String,
+    pub page_url: String,
+    pub browser: BrowserContext,
     pub speed: String,
+    //bytes per second parsed from speed on confirm, the field stays editable while links are probed
+    pub limit: usize,
     pub temp_action: Actions,
+    pub start_mode: StartMode,
     pub temp_file: Option<File2Dl>,
+    //name the probed file will be saved as, editable in the preview
+    pub rename: String,
+    //set once the preview was confirmed
+    pub reviewed: bool,
+    pub size_warned: bool,
+    pub size_confirmed: bool,
+    pub insecure: bool,
+    pub connect_timeout: String,
+    pub read_timeout: String,
+    pub stall_timeout: String,
+    pub checksum: String,
+    pub verify: bool,
     pub file_channel: (Sender<File2Dl>, Receiver<File2Dl>),
     pub show: bool,
     pub error: String,
     pub error_channel: (Sender<String>, Receiver<String>),
+    pub batch: Vec<BatchEntry>,
+    pub batch_channel: BatchChannel,
 }
+
+type BatchChannel = (
+    Sender<(String, Result<File2Dl, String>)>,
+    Receiver<(String, Result<File2Dl, String>)>,
+);
+
+impl DownloadPopUp {
+    pub fn probing(&self) -> bool {
+        self.batch
+            .iter()
+            .any(|entry| matches!(entry.status, BatchStatus::Probing))
+    }
+}
+
 impl Default for DownloadPopUp {
     fn default() -> Self {
         Self {
             link: String::default(),
+            page_title: String::default(),
+            page_url: String::default(),
+            browser: BrowserContext::default(),
             speed: String::default(),
+            limit: 0,
             temp_action: Actions::None,
+            start_mode: StartMode::default(),
             temp_file: None,
+            rename: String::default(),
+            reviewed: false,
+            size_warned: false,
+            size_confirmed: false,
+            insecure: false,
+            connect_timeout: String::default(),
+            read_timeout: String::default(),
+            stall_timeout: String::default(),
+            checksum: String::default(),
+            verify: true,
             file_channel: channel(),
             show: bool::default(),
             error: String::default(),
             error_channel: channel(),
+            batch: Vec::new(),
+            batch_channel: channel(),
         }
     }
 }
 #[derive(Default)]
 pub struct PopUps {
     pub download: DownloadPopUp,
+    pub duplicate: DuplicatePopUp,
+    pub cancel: CancelPopUp,
+    pub tags: TagsPopUp,
+    pub note: NotePopUp,
+    pub update_url: UpdateUrlPopUp,
+    pub close: ClosePopUp,
+    pub columns: ColumnsPopUp,
+    pub theme_editor: ThemeEditorPopUp,
+    pub scan_warning: ScanWarningPopUp,
+    pub intercept: InterceptPopUp,
     pub settings: SettingsPopUp,
     pub error: ErrorPopUp,
     pub confirm: ConfirmPopUp,
     pub plot: PLotPopUp,
     pub speed: EditSpeedPopUp,
+    pub rules: SpeedRulesPopUp,
+    pub schedule: BandwidthSchedulePopUp,
+    pub tokens: ApiTokensPopUp,
+    pub history: HistoryPopUp,
+    pub statistics: StatisticsPopUp,
     pub log: LogPopUp,
 }
 pub fn handle_popups(interface: &mut DownloadManager, ctx: &egui_sfml::egui::Context) {
@@ -113,9 +411,42 @@ pub fn handle_popups(interface: &mut DownloadManager, ctx: &egui_sfml::egui::Con
     if interface.popups.speed.show {
         show_modify_speed_window(ctx, interface);
     }
+    if interface.popups.rules.show {
+        show_speed_rules_window(ctx, interface);
+    }
+    if interface.popups.schedule.show {
+        show_bandwidth_schedule_window(ctx, interface);
+    }
+    if interface.popups.tokens.show {
+        show_api_tokens_window(ctx, interface);
+    }
     if interface.popups.plot.show {
         show_plot_window(ctx, interface);
     }
+    if interface.popups.history.show {
+        show_history_window(ctx, interface);
+    }
+    if interface.popups.statistics.show {
+        show_statistics_window(ctx, interface);
+    }
+    if interface.popups.tags.show {
+        show_tags_window(ctx, interface);
+    }
+    if interface.popups.note.show {
+        show_note_window(ctx, interface);
+    }
+    if interface.popups.update_url.show {
+        show_update_url_window(ctx, interface);
+    }
+    if interface.popups.close.show {
+        show_close_window(ctx, interface);
+    }
+    if interface.popups.columns.show {
+        show_columns_window(ctx, interface);
+    }
+    if interface.popups.theme_editor.show {
+        show_theme_editor_window(ctx, interface);
+    }
     if interface.popups.confirm.show {
         let task = (interface.popups.confirm.task)();
         show_confirm_window(
@@ -132,6 +463,18 @@ pub fn handle_popups(interface: &mut DownloadManager, ctx: &egui_sfml::egui::Con
     if interface.popups.download.show {
         show_input_window(ctx, interface);
     }
+    if interface.popups.intercept.show {
+        show_intercept_window(ctx, interface);
+    }
+    if interface.popups.duplicate.show {
+        show_duplicate_window(ctx, interface);
+    }
+    if interface.popups.cancel.show {
+        show_cancel_window(ctx, interface);
+    }
+    if interface.popups.scan_warning.show {
+        show_scan_warning_window(ctx, interface);
+    }
     if interface.popups.settings.show {
         show_settings_window(ctx, interface);
     }
@@ -0,0 +1,67 @@
+use crate::{colors::RED, dl::file2dl::FileKey, DownloadManager, FDl};
+use chrono::Local;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueueMove {
+    Up,
+    Down,
+    //dropped on another row, it takes that row's place
+    Onto(FileKey),
+}
+
+//the order the table lists unfinished downloads in, finished ones are left to their name
+pub fn queue_key(fdl: &FDl) -> (bool, i64, &str) {
+    let complete = fdl.file.complete.load(Relaxed);
+    let position = if complete {
+        0
+    } else {
+        fdl.file.position.load(Relaxed)
+    };
+    (complete, position, &fdl.file.name_on_disk)
+}
+
+pub fn move_in_queue(interface: &mut DownloadManager, key: &FileKey, to: QueueMove) {
+    let mut order = (0..interface.files.len())
+        .filter(|&idx| !interface.files[idx].file.complete.load(Relaxed))
+        .collect::<Vec<_>>();
+    order.sort_by(|a, b| queue_key(&interface.files[*a]).cmp(&queue_key(&interface.files[*b])));
+    let find = |key: &FileKey| {
+        order
+            .iter()
+            .position(|&idx| interface.files[idx].file.is(key))
+    };
+    let Some(from) = find(key) else {
+        return;
+    };
+    let to = match to {
+        QueueMove::Up => from.saturating_sub(1),
+        QueueMove::Down => (from + 1).min(order.len() - 1),
+        QueueMove::Onto(target) => match find(&target) {
+            Some(to) => to,
+            None => return,
+        },
+    };
+    if from == to {
+        return;
+    }
+    let moved = order.remove(from);
+    order.insert(to, moved);
+    //numbered from one, downloads added later get their added time and so land at the end
+    let mut failed = None;
+    for (position, idx) in order.into_iter().enumerate() {
+        let fdl = &interface.files[idx];
+        let position = position as i64 + 1;
+        if fdl.file.position.swap(position, Relaxed) == position {
+            continue;
+        }
+        if let Err(e) = fdl.save_metadata() {
+            failed.get_or_insert(e);
+        }
+    }
+    if let Some(e) = failed {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!("Couldn't save the queue order: {}", e);
+        interface.popups.log.logs.push((formatted_time, text, *RED));
+    }
+}
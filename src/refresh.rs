@@ -0,0 +1,149 @@
+use crate::{
+    colors::{CYAN, RED},
+    dl::file2dl::FileKey,
+    restart::reprobe,
+    DownloadManager, FDl,
+};
+use chrono::Local;
+use std::{
+    collections::HashMap,
+    fs::{metadata, File},
+    path::Path,
+    sync::atomic::Ordering::Relaxed,
+    time::{Duration, Instant, SystemTime},
+};
+
+pub const REFRESH_CHOICES: [(u64, &str); 6] = [
+    (0, "Never"),
+    (1, "Every hour"),
+    (6, "Every 6 hours"),
+    (12, "Every 12 hours"),
+    (24, "Every day"),
+    (168, "Every week"),
+];
+
+pub struct RefreshSchedule {
+    pub last_check: Instant,
+    //when each file was last probed for its refresh, a probe that failed waits for the next interval
+    tried: HashMap<FileKey, Instant>,
+}
+
+impl Default for RefreshSchedule {
+    fn default() -> Self {
+        Self {
+            last_check: Instant::now(),
+            tried: HashMap::new(),
+        }
+    }
+}
+
+//truncates the file and puts it back in the queue using the stored url info and destination
+pub fn requeue(fdl: &mut FDl) -> Result<(), std::io::Error> {
+    let file = &fdl.file;
+    let path = Path::new(&file.dl_dir).join(&file.name_on_disk);
+    File::create(path)?;
+    file.size_on_disk.store(0, Relaxed);
+    file.bytes_per_sec.store(0, Relaxed);
+    file.smoothed_speed.store(0, Relaxed);
+    file.complete.store(false, Relaxed);
+    file.failed.store(false, Relaxed);
+    file.cancelled.store(false, Relaxed);
+    file.verify_failed.store(false, Relaxed);
+    file.running.store(true, Relaxed);
+    fdl.new = true;
+    fdl.initiated = false;
+    fdl.initial_status = false;
+    fdl.got_notif = false;
+    fdl.has_error = false;
+    fdl.completed_at = None;
+    fdl.started_at = None;
+    fdl.toggled_at = Instant::now();
+    Ok(())
+}
+
+//the link is probed first, the file may have grown or shrunk since it was fetched
+pub fn redownload_selected(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let mut keys = Vec::new();
+    for fdl in interface.files.iter_mut() {
+        if !fdl.selected || !fdl.file.complete.load(Relaxed) {
+            continue;
+        }
+        fdl.selected = false;
+        keys.push(fdl.file.key());
+    }
+    for key in keys {
+        if reprobe(interface, &key) {
+            let text = format!("Re-downloading: {}", &key.name_on_disk);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *CYAN));
+        }
+    }
+}
+
+pub fn set_refresh_selected(interface: &mut DownloadManager, hours: u64) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    for fdl in interface.files.iter_mut().filter(|f| f.selected) {
+        fdl.file.refresh_hours = hours;
+        if let Err(e) = fdl.save_metadata() {
+            let text = format!("Couldn't write to file: {:?}", e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+        }
+    }
+}
+
+//the file's modification time tells when it was last fetched, so schedules survive restarts
+pub fn check_refreshes(interface: &mut DownloadManager) {
+    if interface.refresh.last_check.elapsed() < Duration::from_secs(60) {
+        return;
+    }
+    interface.refresh.last_check = Instant::now();
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let mut due = Vec::new();
+    for fdl in interface.files.iter() {
+        let hours = fdl.file.refresh_hours;
+        if hours == 0 || !fdl.file.complete.load(Relaxed) {
+            continue;
+        }
+        let interval = Duration::from_secs(hours * 3600);
+        let key = fdl.file.key();
+        if interface
+            .refresh
+            .tried
+            .get(&key)
+            .is_some_and(|tried| tried.elapsed() < interval)
+        {
+            continue;
+        }
+        let path = Path::new(&fdl.file.dl_dir).join(&fdl.file.name_on_disk);
+        let is_due = metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age >= interval);
+        if is_due {
+            due.push(key);
+        }
+    }
+    for key in due {
+        interface.refresh.tried.insert(key.clone(), Instant::now());
+        if reprobe(interface, &key) {
+            let text = format!("Scheduled re-download: {}", &key.name_on_disk);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *CYAN));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    colors::{CYAN, RED},
+    content_index::file_path,
+    dl::{file2dl::FileKey, url::Url},
+    i18n::trf,
+    popups::ask_confirm,
+    refresh::requeue,
+    DownloadManager,
+};
+use chrono::Local;
+use std::sync::{
+    atomic::Ordering::Relaxed,
+    mpsc::{channel, Receiver, Sender},
+};
+
+type Probed = (FileKey, Result<Url, String>);
+
+pub struct Relinks {
+    pub channel: (Sender<Probed>, Receiver<Probed>),
+    //probed links waiting for the old transfer to let go of the file, and whether its bytes are kept
+    pub pending: Vec<(FileKey, Url, bool)>,
+}
+
+impl Default for Relinks {
+    fn default() -> Self {
+        Self {
+            channel: channel(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+//stops the row and probes the new link, the old one is only replaced once the probe went through
+pub fn update_url(interface: &mut DownloadManager, key: &FileKey, link: &str) {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(key)) else {
+        return;
+    };
+    let file = &fdl.file;
+    file.running.store(false, Relaxed);
+    file.bytes_per_sec.store(0, Relaxed);
+    file.smoothed_speed.store(0, Relaxed);
+    if let Some(cancel) = &fdl.cancel {
+        let _ = cancel.send(true);
+    }
+    let link = link.to_string();
+    let tls = file.tls.clone();
+    let browser = file.browser.for_new_link(&file.url.link, &link);
+    let key = key.clone();
+    let tx = interface.relinks.channel.0.clone();
+    let text = format!("Updating the link of {} to {}", &key.name_on_disk, &link);
+    interface.runtime.spawn(async move {
+        let probed = Url::with_browser(&link, &tls, &browser)
+            .await
+            .map_err(|e| e.to_string());
+        let _ = tx.send((key, probed));
+    });
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+}
+
+pub fn check_relinks(interface: &mut DownloadManager) {
+    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+    while let Ok((key, probed)) = interface.relinks.channel.1.try_recv() {
+        let url = match probed {
+            Ok(url) => url,
+            Err(e) => {
+                let text = format!("Couldn't update the link of {}: {}", key.name_on_disk, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text.clone(), *RED));
+                interface.popups.error.value = text;
+                interface.popups.error.show = true;
+                continue;
+            }
+        };
+        let Some(fdl) = interface.files.iter().find(|f| f.file.is(&key)) else {
+            continue;
+        };
+        let file = &fdl.file;
+        //a different size means a different file, and without ranges there's nothing to resume from
+        let keep = url.content_length == file.url.content_length
+            && (url.range_support || file.size_on_disk.load(Relaxed) == 0);
+        if keep {
+            interface.relinks.pending.push((key, url, true));
+            continue;
+        }
+        let text = trf(
+            "The new link can't continue {}, its downloaded data will be deleted and it will start over",
+            &[&key.name_on_disk],
+        );
+        ask_confirm(
+            interface,
+            None,
+            *RED,
+            text,
+            Box::new(move || {
+                let key = key.clone();
+                let url = url.clone();
+                Box::new(move |app: &mut DownloadManager| {
+                    app.relinks.pending.push((key, url, false));
+                })
+            }),
+        );
+    }
+    let pending = std::mem::take(&mut interface.relinks.pending);
+    for (key, url, keep) in pending {
+        let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(&key)) else {
+            continue;
+        };
+        let task_alive = fdl.task_alive();
+        if task_alive {
+            interface.relinks.pending.push((key, url, keep));
+            continue;
+        }
+        //the name on disk stays so the row keeps its place and its partial file
+        fdl.file.browser = fdl.file.browser.for_new_link(&fdl.file.url.link, &url.link);
+        fdl.file.url = url;
+        let result = if keep {
+            fdl.error = None;
+            fdl.has_error = false;
+            fdl.initiated = false;
+            fdl.file.failed.store(false, Relaxed);
+            if !fdl.file.complete.load(Relaxed) {
+                fdl.file.running.store(true, Relaxed);
+            }
+            fdl.save_metadata()
+        } else {
+            interface
+                .content_index
+                .entries
+                .remove(&file_path(&fdl.file));
+            requeue(fdl).and_then(|_| fdl.save_metadata())
+        };
+        let (text, color) = match result {
+            Ok(_) if keep => (format!("Updated the link of {}", key.name_on_disk), *CYAN),
+            Ok(_) => (
+                format!("Updated the link of {}, starting over", key.name_on_disk),
+                *CYAN,
+            ),
+            Err(e) => (
+                format!("Couldn't update the link of {}: {}", key.name_on_disk, e),
+                *RED,
+            ),
+        };
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, color));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    colors::{CYAN, RED},
+    content_index::file_path,
+    dl::{file2dl::FileKey, url::Url},
+    refresh::requeue,
+    DownloadManager,
+};
+use chrono::Local;
+use std::{
+    sync::{
+        atomic::Ordering::Relaxed,
+        mpsc::{channel, Receiver, Sender},
+    },
+    time::Instant,
+};
+
+type Probed = (FileKey, Result<Url, String>);
+
+pub struct Restarts {
+    pub channel: (Sender<Probed>, Receiver<Probed>),
+    //probed files waiting for their aborted transfer to let go of the file
+    pub pending: Vec<(FileKey, Url)>,
+}
+
+impl Default for Restarts {
+    fn default() -> Self {
+        Self {
+            channel: channel(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+//stops the row and probes the url again, the file is truncated once the fresh info arrives
+pub fn restart_download(interface: &mut DownloadManager, key: &FileKey) {
+    if !reprobe(interface, key) {
+        return;
+    }
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Restarting from zero: {}", &key.name_on_disk);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+}
+
+//the stored size is stale for a file that changed on the server, check_restarts swaps in the fresh one
+pub fn reprobe(interface: &mut DownloadManager, key: &FileKey) -> bool {
+    let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(key)) else {
+        return false;
+    };
+    let file = &fdl.file;
+    file.running.store(false, Relaxed);
+    file.bytes_per_sec.store(0, Relaxed);
+    file.smoothed_speed.store(0, Relaxed);
+    if let Some(cancel) = &fdl.cancel {
+        let _ = cancel.send(true);
+    }
+    let link = file.url.link.clone();
+    let tls = file.tls.clone();
+    let browser = file.browser.clone();
+    let key = key.clone();
+    let tx = interface.restarts.channel.0.clone();
+    interface.runtime.spawn(async move {
+        let probed = Url::with_browser(&link, &tls, &browser)
+            .await
+            .map_err(|e| e.to_string());
+        let _ = tx.send((key, probed));
+    });
+    true
+}
+
+//a failed transfer that can resume picks up where it stopped, anything else starts over
+pub fn retry_download(interface: &mut DownloadManager, key: &FileKey) {
+    let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(key)) else {
+        return;
+    };
+    fdl.error = None;
+    if !fdl.file.url.range_support && !fdl.new {
+        restart_download(interface, key);
+        return;
+    }
+    fdl.file.failed.store(false, Relaxed);
+    fdl.file.running.store(true, Relaxed);
+    fdl.toggled_at = Instant::now();
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Retrying: {}", key.name_on_disk);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+}
+
+pub fn check_restarts(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    while let Ok((key, probed)) = interface.restarts.channel.1.try_recv() {
+        match probed {
+            Ok(url) => interface.restarts.pending.push((key, url)),
+            Err(e) => {
+                let text = format!("Couldn't restart {}: {}", key.name_on_disk, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text.clone(), *RED));
+                interface.popups.error.value = text;
+                interface.popups.error.show = true;
+            }
+        }
+    }
+    let pending = std::mem::take(&mut interface.restarts.pending);
+    for (key, url) in pending {
+        let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(&key)) else {
+            continue;
+        };
+        let task_alive = fdl.task_alive();
+        if task_alive {
+            interface.restarts.pending.push((key, url));
+            continue;
+        }
+        //the name on disk stays so the row keeps its place, only the remote info is refreshed
+        fdl.file.url = url;
+        interface
+            .content_index
+            .entries
+            .remove(&file_path(&fdl.file));
+        let result = requeue(fdl).and_then(|_| fdl.save_metadata());
+        let (text, color) = match result {
+            Ok(_) => (format!("Restarted: {}", key.name_on_disk), *CYAN),
+            Err(e) => (
+                format!("Couldn't restart {}: {}", key.name_on_disk, e),
+                *RED,
+            ),
+        };
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, color));
+    }
+}
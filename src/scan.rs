@@ -0,0 +1,148 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    dl::{
+        checksum::{hash_file, HashAlgo},
+        file2dl::{File2Dl, FileKey},
+        metadata::remove_metadata,
+        store::Outcome,
+        virustotal::{lookup_sha256, ScanVerdict},
+    },
+    history::record_history,
+    DownloadManager,
+};
+use chrono::Local;
+use std::{
+    fs::remove_file,
+    io::ErrorKind,
+    path::Path,
+    sync::{
+        atomic::AtomicUsize,
+        mpsc::{channel, Receiver, Sender},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanState {
+    Pending,
+    Done(ScanVerdict),
+    Failed(String),
+}
+
+type Scanned = (FileKey, Result<ScanVerdict, String>);
+
+pub struct Scans {
+    pub channel: (Sender<Scanned>, Receiver<Scanned>),
+}
+
+impl Default for Scans {
+    fn default() -> Self {
+        Self { channel: channel() }
+    }
+}
+
+//hashes the file and asks virustotal about it, does nothing without an api key
+pub fn scan_file(interface: &mut DownloadManager, file: &File2Dl) {
+    let api_key = interface.settings.virustotal_key.trim().to_string();
+    if api_key.is_empty() {
+        return;
+    }
+    let Some(fdl) = interface.files.iter_mut().find(|f| f.file.same_file(file)) else {
+        return;
+    };
+    fdl.scan = Some(ScanState::Pending);
+    let tx = interface.scans.channel.0.clone();
+    let key = file.key();
+    let path = Path::new(&file.dl_dir).join(&file.name_on_disk);
+    interface.runtime.spawn(async move {
+        let hashed = tokio::task::spawn_blocking(move || {
+            hash_file(&path, HashAlgo::Sha256, &AtomicUsize::new(0))
+        })
+        .await;
+        let result = match hashed {
+            Ok(Ok(sha256)) => lookup_sha256(&sha256, &api_key)
+                .await
+                .map_err(|e| e.to_string()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let _ = tx.send((key, result));
+    });
+}
+
+pub fn check_scans(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    while let Ok((key, result)) = interface.scans.channel.1.try_recv() {
+        let name = &key.name_on_disk;
+        if let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(&key)) {
+            fdl.scan = Some(match &result {
+                Ok(verdict) => ScanState::Done(*verdict),
+                Err(e) => ScanState::Failed(e.clone()),
+            });
+        }
+        let (text, color) = match result {
+            Ok(ScanVerdict::Clean) => (format!("VirusTotal found nothing in: {}", name), *GREEN),
+            Ok(ScanVerdict::Unknown) => (format!("VirusTotal has no report for: {}", name), *CYAN),
+            Ok(ScanVerdict::Flagged {
+                malicious,
+                suspicious,
+            }) => {
+                interface.popups.scan_warning.key = key.clone();
+                interface.popups.scan_warning.malicious = malicious;
+                interface.popups.scan_warning.suspicious = suspicious;
+                interface.popups.scan_warning.show = true;
+                interface.popups.log.has_error = true;
+                (
+                    format!(
+                        "VirusTotal flagged {}: {} malicious, {} suspicious",
+                        name, malicious, suspicious
+                    ),
+                    *RED,
+                )
+            }
+            Err(e) => (
+                format!("Couldn't check {} on VirusTotal: {}", name, e),
+                *RED,
+            ),
+        };
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, color));
+    }
+}
+
+//removes a flagged file along with its metadata and drops the row
+pub fn delete_flagged(interface: &mut DownloadManager, key: &FileKey) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let Some(idx) = interface.files.iter().position(|f| f.file.is(key)) else {
+        return;
+    };
+    let file = &interface.files[idx].file;
+    let path = Path::new(&file.dl_dir).join(&file.name_on_disk);
+    let removed = match remove_file(&path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => remove_metadata(file),
+    };
+    if let Err(e) = removed {
+        let text = format!("File Path: {}, Error: {}", path.to_string_lossy(), e);
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text.clone(), *RED));
+        interface.popups.error.value = text;
+        interface.popups.error.show = true;
+        return;
+    }
+    let fdl = interface.files.remove(idx);
+    record_history(&mut interface.popups.log, &fdl, Outcome::Deleted);
+    let text = format!("Deleted flagged file: {}", key.name_on_disk);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *GREEN));
+}
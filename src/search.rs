@@ -0,0 +1,119 @@
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    #[default]
+    Plain,
+    Regex,
+    //the typed characters have to show up in order, not next to each other
+    Fuzzy,
+}
+
+impl SearchMode {
+    pub const ALL: [Self; 3] = [Self::Plain, Self::Regex, Self::Fuzzy];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Plain => "Aa",
+            Self::Regex => ".*",
+            Self::Fuzzy => "~",
+        }
+    }
+
+    pub fn hint(&self) -> &str {
+        match self {
+            Self::Plain => "Plain text",
+            Self::Regex => "Regular expression",
+            Self::Fuzzy => "Fuzzy, letters in order",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Search {
+    pub query: String,
+    pub mode: SearchMode,
+    //rows the last drawn table matched, shown in the status bar
+    pub matches: usize,
+}
+
+impl Search {
+    pub fn is_empty(&self) -> bool {
+        self.query.trim().is_empty()
+    }
+}
+
+pub enum Matcher {
+    Everything,
+    //plain text is matched as an escaped pattern so both share the highlighting
+    Pattern(Regex),
+    Fuzzy(Vec<char>),
+}
+
+impl Matcher {
+    pub fn new(search: &Search) -> Result<Self, regex::Error> {
+        let query = search.query.trim();
+        if query.is_empty() {
+            return Ok(Self::Everything);
+        }
+        let pattern = match search.mode {
+            SearchMode::Plain => regex::escape(query),
+            SearchMode::Regex => query.to_string(),
+            SearchMode::Fuzzy => {
+                let chars = query.chars().filter(|c| !c.is_whitespace()).collect();
+                return Ok(Self::Fuzzy(chars));
+            }
+        };
+        //a pathological pattern shouldn't be able to eat the ui thread
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .size_limit(1 << 20)
+            .build()?;
+        Ok(Self::Pattern(regex))
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Everything => true,
+            Self::Pattern(regex) => regex.is_match(text),
+            Self::Fuzzy(_) => !self.ranges(text).is_empty(),
+        }
+    }
+
+    //byte ranges of text to highlight, empty when nothing matched
+    pub fn ranges(&self, text: &str) -> Vec<Range<usize>> {
+        match self {
+            Self::Everything => Vec::new(),
+            Self::Pattern(regex) => regex
+                .find_iter(text)
+                .map(|m| m.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+            Self::Fuzzy(chars) => fuzzy_ranges(chars, text),
+        }
+    }
+}
+
+fn fuzzy_ranges(chars: &[char], text: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut wanted = chars.iter().peekable();
+    for (idx, c) in text.char_indices() {
+        let Some(next) = wanted.peek() else {
+            break;
+        };
+        if !c.to_lowercase().eq(next.to_lowercase()) {
+            continue;
+        }
+        wanted.next();
+        let end = idx + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == idx => last.end = end,
+            _ => ranges.push(idx..end),
+        }
+    }
+    if wanted.peek().is_some() {
+        return Vec::new();
+    }
+    ranges
+}
@@ -0,0 +1,151 @@
+use super::{
+    auth::{authorize_token, Permission},
+    control::{ask_ui, Command, DownloadSummary},
+};
+use poem::{handler, web::Json};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+//the version frontends are told, the calls below behave like it
+const ARIA2_VERSION: &str = "1.37.0";
+
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+fn aria2_status(row: &DownloadSummary) -> &str {
+    match row.status.as_str() {
+        "downloading" => "active",
+        "queued" => "waiting",
+        "complete" => "complete",
+        "failed" => "error",
+        "cancelled" => "removed",
+        _ => "paused",
+    }
+}
+
+//aria2 sends every number as a string
+fn describe(row: &DownloadSummary) -> Value {
+    let path = Path::new(&row.dir).join(&row.name);
+    //ids are already 16 hex digits like aria2's
+    json!({
+        "gid": row.id,
+        "status": aria2_status(row),
+        "totalLength": row.size.to_string(),
+        "completedLength": row.downloaded.to_string(),
+        "downloadSpeed": row.bytes_per_sec.to_string(),
+        "uploadLength": "0",
+        "uploadSpeed": "0",
+        "connections": "1",
+        "dir": row.dir,
+        "files": [{
+            "index": "1",
+            "path": path.to_string_lossy(),
+            "length": row.size.to_string(),
+            "completedLength": row.downloaded.to_string(),
+            "selected": "true",
+            "uris": [{ "uri": row.link, "status": "used" }],
+        }],
+    })
+}
+
+#[handler]
+pub async fn json_rpc(request: Json<RpcRequest>) -> Json<Value> {
+    let RpcRequest {
+        id,
+        method,
+        mut params,
+    } = request.0;
+    //the secret comes first as "token:<secret>", in front of the method's own params
+    let token = params
+        .first()
+        .and_then(Value::as_str)
+        .and_then(|param| param.strip_prefix("token:"))
+        .map(String::from);
+    if token.is_some() {
+        params.remove(0);
+    }
+    let response = match call(&method, &params, token.as_deref()).await {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(message) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": 1, "message": message },
+        }),
+    };
+    Json(response)
+}
+
+async fn call(method: &str, params: &[Value], token: Option<&str>) -> Result<Value, String> {
+    let permission = match method {
+        "aria2.addUri" => Permission::Add,
+        "aria2.pause" | "aria2.forcePause" | "aria2.remove" | "aria2.forceRemove" => {
+            Permission::Control
+        }
+        _ => Permission::Read,
+    };
+    if !authorize_token(token, permission, &format!("aria2 {}", method)) {
+        return Err(String::from("Unauthorized"));
+    }
+    match method {
+        "aria2.getVersion" => Ok(json!({ "version": ARIA2_VERSION, "enabledFeatures": [] })),
+        "aria2.addUri" => {
+            //mirrors aren't supported, the first uri is the one downloaded
+            let link = params
+                .first()
+                .and_then(Value::as_array)
+                .and_then(|uris| uris.first())
+                .and_then(Value::as_str)
+                .ok_or("addUri takes a list of uris")?;
+            let rows = ask_ui(Command::Add(link.to_string()))
+                .await
+                .map_err(|e| e.to_string())?;
+            rows.first()
+                .map(|row| json!(row.id))
+                .ok_or_else(|| format!("Couldn't add {}", link))
+        }
+        "aria2.tellActive" => {
+            let rows = ask_ui(Command::List).await.map_err(|e| e.to_string())?;
+            let active = rows
+                .iter()
+                .filter(|row| row.status == "downloading")
+                .map(describe)
+                .collect();
+            Ok(Value::Array(active))
+        }
+        "aria2.pause" | "aria2.forcePause" => {
+            let gid = find(params).await?;
+            ask_ui(Command::Pause(gid.clone()))
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(json!(gid))
+        }
+        "aria2.remove" | "aria2.forceRemove" => {
+            let gid = find(params).await?;
+            ask_ui(Command::Remove(gid.clone(), false))
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(json!(gid))
+        }
+        _ => Err(format!("No such method: {}", method)),
+    }
+}
+
+//the gid in the first param, once it's known to be a download's id
+async fn find(params: &[Value]) -> Result<String, String> {
+    let wanted = params
+        .first()
+        .and_then(Value::as_str)
+        .ok_or("Missing the gid")?;
+    let rows = ask_ui(Command::List).await.map_err(|e| e.to_string())?;
+    rows.into_iter()
+        .find(|row| row.id == wanted)
+        .map(|row| row.id)
+        .ok_or_else(|| format!("GID {} is not found", wanted))
+}
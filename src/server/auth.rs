@@ -0,0 +1,143 @@
+use poem::http::HeaderMap;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, RwLock};
+
+pub static API_TOKENS: RwLock<Vec<ApiToken>> = RwLock::new(Vec::new());
+pub static SERVER_SECRET: RwLock<String> = RwLock::new(String::new());
+pub static API_LOG: Mutex<Vec<ApiRequest>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TokenScope {
+    ReadOnly,
+    #[default]
+    AddOnly,
+    Full,
+}
+
+impl TokenScope {
+    pub const ALL: [Self; 3] = [Self::ReadOnly, Self::AddOnly, Self::Full];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::ReadOnly => "Read only",
+            Self::AddOnly => "Add only",
+            Self::Full => "Full control",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    Read,
+    Add,
+    Control,
+}
+
+impl TokenScope {
+    pub fn allows(&self, permission: Permission) -> bool {
+        matches!(
+            (self, permission),
+            (Self::Full, _) | (Self::ReadOnly, Permission::Read) | (Self::AddOnly, Permission::Add)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String,
+    pub token: String,
+    pub scope: TokenScope,
+}
+
+impl ApiToken {
+    pub fn generate(name: &str, scope: TokenScope) -> Self {
+        Self {
+            name: name.to_string(),
+            token: generate_secret(),
+            scope,
+        }
+    }
+}
+
+pub fn generate_secret() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub token_name: Option<String>,
+    pub action: String,
+    pub allowed: bool,
+}
+
+pub fn set_api_tokens(tokens: &[ApiToken]) {
+    if let Ok(mut locked) = API_TOKENS.write() {
+        *locked = tokens.to_vec();
+    }
+}
+
+pub fn set_server_secret(secret: &str) {
+    if let Ok(mut locked) = SERVER_SECRET.write() {
+        *locked = secret.to_string();
+    }
+}
+
+pub fn server_secret() -> String {
+    SERVER_SECRET
+        .read()
+        .map(|secret| secret.clone())
+        .unwrap_or_default()
+}
+
+pub fn token_from_headers(headers: &HeaderMap) -> Option<&str> {
+    if let Some(token) = headers.get("x-api-token").and_then(|v| v.to_str().ok()) {
+        return Some(token.trim());
+    }
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+//looks at every byte whatever matched before, so the time taken doesn't tell how much of a guess was right
+pub fn same_secret(given: &str, secret: &str) -> bool {
+    given.len() == secret.len()
+        && given
+            .bytes()
+            .zip(secret.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+//every request needs the server secret or a token with the right scope
+pub fn authorize(headers: &HeaderMap, permission: Permission, action: &str) -> bool {
+    authorize_token(token_from_headers(headers), permission, action)
+}
+
+//for websockets, which browsers can only hand a token in the query
+pub fn authorize_token(given: Option<&str>, permission: Permission, action: &str) -> bool {
+    //the extension, the dashboard and the cli send the secret all the time, only tokens are logged
+    let secret = server_secret();
+    if !secret.is_empty() && given.is_some_and(|given| same_secret(given, &secret)) {
+        return true;
+    }
+    let Ok(tokens) = API_TOKENS.read() else {
+        return false;
+    };
+    let matched = given.and_then(|given| tokens.iter().find(|t| same_secret(given, &t.token)));
+    let allowed = matched.is_some_and(|t| t.scope.allows(permission));
+    if let Ok(mut log) = API_LOG.lock() {
+        log.push(ApiRequest {
+            token_name: matched.map(|t| t.name.clone()),
+            action: action.to_string(),
+            allowed,
+        });
+    }
+    allowed
+}
@@ -0,0 +1,187 @@
+use super::auth::{authorize, Permission};
+use poem::{
+    handler,
+    http::{HeaderMap, StatusCode},
+    web::{Json, Path, Query},
+};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::{sync::Mutex, time::Duration};
+use tokio::sync::oneshot;
+
+pub static CONTROL_STATE: Mutex<Vec<ControlRequest>> = Mutex::new(Vec::new());
+
+//names are only unique within a folder, so the id covers both, 16 hex digits like an aria2 gid that survive restarts
+pub fn download_id(dl_dir: &str, name_on_disk: &str) -> String {
+    let path = std::path::Path::new(dl_dir).join(name_on_disk);
+    hex::encode(&Sha1::digest(path.to_string_lossy().as_bytes())[..8])
+}
+
+//downloads are named by their id, see download_id
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    List,
+    Add(String),
+    Pause(String),
+    Resume(String),
+    Limit(String, usize),
+    //true also deletes the file from disk
+    Remove(String, bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlError {
+    NotFound(String),
+    Rejected(String),
+    Failed(String),
+    //the ui didn't pick the request up in time
+    Unavailable,
+}
+
+impl std::fmt::Display for ControlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(e) | Self::Rejected(e) | Self::Failed(e) => write!(f, "{}", e),
+            Self::Unavailable => write!(f, "The app didn't answer in time"),
+        }
+    }
+}
+
+impl ControlError {
+    fn into_poem(self) -> poem::Error {
+        match self {
+            Self::NotFound(e) => poem::Error::from_string(e, StatusCode::NOT_FOUND),
+            Self::Rejected(e) => poem::Error::from_string(e, StatusCode::BAD_REQUEST),
+            Self::Failed(e) => poem::Error::from_string(e, StatusCode::INTERNAL_SERVER_ERROR),
+            Self::Unavailable => poem::Error::from_status(StatusCode::SERVICE_UNAVAILABLE),
+        }
+    }
+}
+
+//the ui answers with the rows the command touched, or why it couldn't
+pub type ControlReply = Result<Vec<DownloadSummary>, ControlError>;
+
+pub struct ControlRequest {
+    pub command: Command,
+    pub reply: oneshot::Sender<ControlReply>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadSummary {
+    pub id: String,
+    pub name: String,
+    pub link: String,
+    pub dir: String,
+    pub status: String,
+    pub size: usize,
+    pub downloaded: usize,
+    pub bytes_per_sec: usize,
+    //0 when unlimited
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddParams {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LimitParams {
+    bytes_per_sec: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoveParams {
+    #[serde(default)]
+    delete_file: bool,
+}
+
+//hands a command to the ui and waits for its answer
+pub async fn ask_ui(command: Command) -> ControlReply {
+    let (reply, answer) = oneshot::channel();
+    CONTROL_STATE
+        .lock()
+        .map_err(|_| ControlError::Failed(String::from("The request queue is poisoned")))?
+        .push(ControlRequest { command, reply });
+    //the ui picks requests up every frame even with the window hidden, adding waits for the probe
+    match tokio::time::timeout(Duration::from_secs(60), answer).await {
+        Ok(Ok(reply)) => reply,
+        _ => Err(ControlError::Unavailable),
+    }
+}
+
+async fn send(command: Command) -> poem::Result<Vec<DownloadSummary>> {
+    ask_ui(command).await.map_err(ControlError::into_poem)
+}
+
+#[handler]
+pub async fn list(headers: &HeaderMap) -> poem::Result<Json<Vec<DownloadSummary>>> {
+    if !authorize(headers, Permission::Read, "list downloads") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    send(Command::List).await.map(Json)
+}
+
+//starts right away without the download window, unlike links sent to /
+#[handler]
+pub async fn add(
+    params: Json<AddParams>,
+    headers: &HeaderMap,
+) -> poem::Result<(StatusCode, Json<Vec<DownloadSummary>>)> {
+    if !authorize(headers, Permission::Add, "add download") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    let rows = send(Command::Add(params.url.trim().to_string())).await?;
+    Ok((StatusCode::CREATED, Json(rows)))
+}
+
+//takes the row off the list, and its file too with ?delete_file=true
+#[handler]
+pub async fn remove(
+    id: Path<String>,
+    params: Query<RemoveParams>,
+    headers: &HeaderMap,
+) -> poem::Result<Json<Vec<DownloadSummary>>> {
+    if !authorize(headers, Permission::Control, "remove download") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    send(Command::Remove(id.0, params.delete_file))
+        .await
+        .map(Json)
+}
+
+#[handler]
+pub async fn pause(
+    id: Path<String>,
+    headers: &HeaderMap,
+) -> poem::Result<Json<Vec<DownloadSummary>>> {
+    if !authorize(headers, Permission::Control, "pause download") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    send(Command::Pause(id.0)).await.map(Json)
+}
+
+#[handler]
+pub async fn resume(
+    id: Path<String>,
+    headers: &HeaderMap,
+) -> poem::Result<Json<Vec<DownloadSummary>>> {
+    if !authorize(headers, Permission::Control, "resume download") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    send(Command::Resume(id.0)).await.map(Json)
+}
+
+#[handler]
+pub async fn limit(
+    id: Path<String>,
+    params: Query<LimitParams>,
+    headers: &HeaderMap,
+) -> poem::Result<Json<Vec<DownloadSummary>>> {
+    if !authorize(headers, Permission::Control, "limit download") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    send(Command::Limit(id.0, params.bytes_per_sec))
+        .await
+        .map(Json)
+}
@@ -0,0 +1,7 @@
+use poem::{handler, web::Html};
+
+//the page itself is open, the calls it makes send the token typed into it
+#[handler]
+pub fn dashboard() -> Html<&'static str> {
+    Html(include_str!("dashboard.html"))
+}
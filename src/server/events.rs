@@ -0,0 +1,77 @@
+use super::{
+    auth::{authorize_token, token_from_headers, Permission},
+    control::DownloadSummary,
+};
+use futures::{SinkExt, StreamExt};
+use poem::{
+    handler,
+    http::{HeaderMap, StatusCode},
+    web::{
+        websocket::{Message, WebSocket},
+        Query,
+    },
+    IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum DownloadEvent {
+    //added, or its status changed, also sent for every row when someone connects
+    State(DownloadSummary),
+    //bytes or speed moved, at most once a second per row
+    Progress(DownloadSummary),
+    Removed { id: String },
+}
+
+//the ui publishes, every connected socket gets its own copy
+pub fn events() -> &'static broadcast::Sender<DownloadEvent> {
+    static EVENTS: OnceLock<broadcast::Sender<DownloadEvent>> = OnceLock::new();
+    EVENTS.get_or_init(|| broadcast::channel(1024).0)
+}
+
+#[derive(Debug, Deserialize)]
+struct EventParams {
+    token: Option<String>,
+}
+
+#[handler]
+pub fn stream_events(
+    ws: WebSocket,
+    params: Query<EventParams>,
+    headers: &HeaderMap,
+) -> poem::Result<impl IntoResponse> {
+    let token = params.token.as_deref().or(token_from_headers(headers));
+    if !authorize_token(token, Permission::Read, "download events") {
+        return Err(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+    }
+    let mut events = events().subscribe();
+    Ok(ws.on_upgrade(|socket| async move {
+        let (mut sink, mut incoming) = socket.split();
+        loop {
+            tokio::select! {
+                event = events.recv() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        //a slow client misses a few updates, the next ones catch it up
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    };
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if sink.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                message = incoming.next() => {
+                    if !matches!(message, Some(Ok(message)) if !message.is_close()) {
+                        break;
+                    }
+                }
+            }
+        }
+    }))
+}
@@ -1,18 +1,64 @@
-use poem::{handler, http::StatusCode, listener::TcpListener, post, web::Json, Route, Server};
+use super::{
+    aria2,
+    auth::{authorize, Permission},
+    control, dashboard, events,
+    maintenance::{pause, resume, status},
+};
+use crate::dl::browser::BrowserContext;
+use poem::{
+    delete, get, handler,
+    http::{HeaderMap, StatusCode},
+    listener::TcpListener,
+    post,
+    web::Json,
+    Route, Server,
+};
 use serde::Deserialize;
 use std::sync::Mutex;
 
-pub static SERVER_STATE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+pub static SERVER_STATE: Mutex<Vec<Intercepted>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Default)]
+pub struct Intercepted {
+    pub link: String,
+    pub page_title: String,
+    pub page_url: String,
+    pub browser: BrowserContext,
+}
 
 #[derive(Debug, Deserialize)]
 struct Url {
     value: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    page_url: String,
+    //sent by the native messaging host, older extensions leave them out
+    #[serde(default)]
+    cookies: String,
+    #[serde(default)]
+    referrer: String,
+    #[serde(default)]
+    user_agent: String,
 }
 
 #[handler]
-fn get_url(res: Json<Url>) {
+fn get_url(res: Json<Url>, headers: &HeaderMap) -> StatusCode {
+    if !authorize(headers, Permission::Add, "add link") {
+        return StatusCode::UNAUTHORIZED;
+    }
     let mut state = SERVER_STATE.try_lock().unwrap();
-    state.push(res.value.clone());
+    state.push(Intercepted {
+        link: res.value.clone(),
+        page_title: res.title.clone(),
+        page_url: res.page_url.clone(),
+        browser: BrowserContext {
+            cookies: res.cookies.clone(),
+            referrer: res.referrer.clone(),
+            user_agent: res.user_agent.clone(),
+        },
+    });
+    StatusCode::OK
 }
 
 #[handler]
@@ -21,15 +67,27 @@ fn handle_head() -> StatusCode {
 }
 
 #[tokio::main]
-pub async fn init_server() -> Result<(), std::io::Error> {
+pub async fn init_server(port: u16) -> Result<(), std::io::Error> {
     // if std::env::var_os("RUST_LOG").is_none() {
     //     std::env::set_var("RUST_LOG", "poem=debug");
     // }
     // tracing_subscriber::fmt::init();
 
-    let app = Route::new().at("/", post(get_url).head(handle_head));
+    let app = Route::new()
+        .at("/", post(get_url).head(handle_head))
+        .at("/maintenance/pause", post(pause).get(pause))
+        .at("/maintenance/resume", post(resume).get(resume))
+        .at("/maintenance", get(status))
+        .at("/ui", get(dashboard::dashboard))
+        .at("/jsonrpc", post(aria2::json_rpc))
+        .at("/downloads", get(control::list).post(control::add))
+        .at("/downloads/events", get(events::stream_events))
+        .at("/downloads/:id", delete(control::remove))
+        .at("/downloads/:id/pause", post(control::pause))
+        .at("/downloads/:id/resume", post(control::resume))
+        .at("/downloads/:id/limit", post(control::limit));
 
-    Server::new(TcpListener::bind("0.0.0.0:3000"))
+    Server::new(TcpListener::bind(format!("0.0.0.0:{}", port)))
         .run(app)
         .await
 }
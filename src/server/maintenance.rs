@@ -0,0 +1,80 @@
+use super::auth::{authorize, Permission};
+use poem::{
+    handler,
+    http::{HeaderMap, StatusCode},
+    web::{Json, Query},
+};
+use serde::{Deserialize, Serialize};
+use std::{sync::Mutex, time::SystemTime};
+
+pub static MAINTENANCE_STATE: Mutex<Vec<MaintenanceRequest>> = Mutex::new(Vec::new());
+//mirrors the ui's current window so scripts can poll it
+pub static PAUSED_UNTIL: Mutex<Option<SystemTime>> = Mutex::new(None);
+//a week, longer windows are a typo and would overflow the clocks
+pub const MAX_PAUSE_MINUTES: u64 = 7 * 24 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaintenanceRequest {
+    Pause(u64),
+    Resume,
+}
+
+#[derive(Debug, Deserialize)]
+struct PauseParams {
+    minutes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct MaintenanceStatus {
+    paused: bool,
+    seconds_left: u64,
+}
+
+#[handler]
+pub fn pause(params: Query<PauseParams>, headers: &HeaderMap) -> StatusCode {
+    if !authorize(headers, Permission::Control, "maintenance pause") {
+        return StatusCode::UNAUTHORIZED;
+    }
+    if !(1..=MAX_PAUSE_MINUTES).contains(&params.minutes) {
+        return StatusCode::BAD_REQUEST;
+    }
+    match MAINTENANCE_STATE.lock() {
+        Ok(mut state) => {
+            state.push(MaintenanceRequest::Pause(params.minutes));
+            StatusCode::OK
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[handler]
+pub fn resume(headers: &HeaderMap) -> StatusCode {
+    if !authorize(headers, Permission::Control, "maintenance resume") {
+        return StatusCode::UNAUTHORIZED;
+    }
+    match MAINTENANCE_STATE.lock() {
+        Ok(mut state) => {
+            state.push(MaintenanceRequest::Resume);
+            StatusCode::OK
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[handler]
+pub fn status(headers: &HeaderMap) -> Result<Json<MaintenanceStatus>, StatusCode> {
+    if !authorize(headers, Permission::Read, "maintenance status") {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let until = PAUSED_UNTIL
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let seconds_left = until
+        .and_then(|until| until.duration_since(SystemTime::now()).ok())
+        .map(|left| left.as_secs())
+        .unwrap_or_default();
+    Ok(Json(MaintenanceStatus {
+        paused: until.is_some(),
+        seconds_left,
+    }))
+}
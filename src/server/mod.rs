@@ -1 +1,7 @@
+pub mod aria2;
+pub mod auth;
+pub mod control;
+pub mod dashboard;
+pub mod events;
 pub mod interception;
+pub mod maintenance;
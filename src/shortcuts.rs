@@ -0,0 +1,61 @@
+use crate::{menu_bar::confirm_remove_selected, DownloadManager};
+use egui_sfml::egui::{Context, Id, Key, KeyboardShortcut, Modifiers};
+use std::sync::atomic::Ordering::Relaxed;
+
+pub const SEARCH_ID: &str = "search";
+
+const ADD_DOWNLOAD: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::N);
+const TOGGLE_PAUSE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Space);
+const REMOVE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Delete);
+const SELECT_ALL: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::A);
+const FOCUS_SEARCH: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::F);
+
+//shown in the help menu
+pub const SHORTCUTS: [(KeyboardShortcut, &str); 5] = [
+    (ADD_DOWNLOAD, "Add a download"),
+    (TOGGLE_PAUSE, "Pause or resume selected"),
+    (REMOVE, "Remove selected from list"),
+    (SELECT_ALL, "Select all"),
+    (FOCUS_SEARCH, "Search"),
+];
+
+pub fn handle_shortcuts(interface: &mut DownloadManager, ctx: &Context) {
+    if !interface.show_window {
+        return;
+    }
+    if ctx.input_mut(|i| i.consume_shortcut(&ADD_DOWNLOAD)) {
+        interface.popups.download.show = true;
+    }
+    if ctx.input_mut(|i| i.consume_shortcut(&FOCUS_SEARCH)) {
+        ctx.memory_mut(|m| m.request_focus(Id::new(SEARCH_ID)));
+    }
+    //the rest mean something else while typing
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    if ctx.input_mut(|i| i.consume_shortcut(&TOGGLE_PAUSE)) {
+        toggle_selected(interface);
+    }
+    if ctx.input_mut(|i| i.consume_shortcut(&REMOVE)) && interface.files.iter().any(|f| f.selected)
+    {
+        confirm_remove_selected(interface);
+    }
+    if ctx.input_mut(|i| i.consume_shortcut(&SELECT_ALL)) {
+        for fdl in interface.files.iter_mut() {
+            fdl.selected = true;
+        }
+    }
+}
+
+//same rules as the row's play button, cancelled rows only come back through their restart button
+fn toggle_selected(interface: &mut DownloadManager) {
+    for fdl in interface.files.iter().filter(|f| f.selected) {
+        let file = &fdl.file;
+        if file.complete.load(Relaxed) || file.cancelled.load(Relaxed) {
+            continue;
+        }
+        if file.url.range_support || fdl.new {
+            file.toggle_status();
+        }
+    }
+}
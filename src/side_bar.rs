@@ -1,4 +1,7 @@
 use crate::colors::*;
+use crate::dl::{file2dl::File2Dl, limiter::global_limit};
+use crate::search::{Matcher, SearchMode};
+use crate::shortcuts::SEARCH_ID;
 use crate::DownloadManager;
 use egui_phosphor::fill::*;
 use egui_sfml::egui::Ui;
@@ -7,6 +10,8 @@ use egui_sfml::egui::*;
 #[derive(Default)]
 pub struct Explorer {
     pub current: Vec<String>,
+    //set when a tag was picked instead of a file type
+    pub tag: Option<String>,
     types: Types,
 }
 
@@ -23,6 +28,7 @@ impl Explorer {
         self.types.slides.clicked = false;
         self.types.texts.clicked = false;
         self.types.videos.clicked = false;
+        self.tag = None;
     }
 }
 
@@ -39,6 +45,39 @@ pub struct Types {
     texts: Inner,
     videos: Inner,
 }
+impl Types {
+    //every type but all, in the order they're listed
+    fn categories(&self) -> [(&'static str, &'static str, &Inner); 10] {
+        [
+            (BINARY, "Binaries", &self.binaries),
+            (ARCHIVE, "Archives", &self.archives),
+            (MUSIC_NOTE, "Audio", &self.audio),
+            (BOOK_BOOKMARK, "Books", &self.books),
+            (TEXT_AA, "Fonts", &self.fonts),
+            (IMAGE, "Images", &self.images),
+            (NOTE, "Sheets", &self.sheets),
+            (SLIDESHOW, "Slides", &self.slides),
+            (TEXT_T, "Texts", &self.texts),
+            (VIDEO, "Videos", &self.videos),
+        ]
+    }
+
+    fn categories_mut(&mut self) -> [&mut Inner; 10] {
+        [
+            &mut self.binaries,
+            &mut self.archives,
+            &mut self.audio,
+            &mut self.books,
+            &mut self.fonts,
+            &mut self.images,
+            &mut self.sheets,
+            &mut self.slides,
+            &mut self.texts,
+            &mut self.videos,
+        ]
+    }
+}
+
 pub struct Inner {
     clicked: bool,
     exts: Vec<String>,
@@ -213,17 +252,31 @@ pub fn lay_side_bar_content(interface: &mut DownloadManager, ui: &mut Ui) {
         let text = RichText::new(formatted).size(20.0);
         let res = ui.colored_label(*GREEN, text);
         if res.hovered() {
-            let text = RichText::new("Total download speed").color(*CYAN);
+            let (limit, _) = global_limit();
+            let text = if limit > 0 {
+                let limit_in_mbs = limit as f64 / (1024.0 * 1024.0);
+                format!("Total download speed, capped at {:.1}Mbs/s", limit_in_mbs)
+            } else {
+                String::from("Total download speed")
+            };
+            let text = RichText::new(text).color(*CYAN);
             res.show_tooltip_text(text);
         }
         ui.horizontal(|ui| {
             ui.add_space(10.0);
+            let invalid = Matcher::new(&interface.search).err();
             ui.scope(|ui| {
                 ui.visuals_mut().extreme_bg_color = *CYAN;
-                ui.visuals_mut().override_text_color = Some(*PURPLE);
-                let hint_text = RichText::new("Filename or Url").color(*GRAY);
-                let single_line = TextEdit::singleline(&mut interface.search).hint_text(hint_text);
-                ui.add_sized((180.0, 17.0), single_line);
+                let color = if invalid.is_some() { *RED } else { *PURPLE };
+                ui.visuals_mut().override_text_color = Some(color);
+                let hint_text = RichText::new("Filename, Url, tag or contains:").color(*GRAY);
+                let single_line = TextEdit::singleline(&mut interface.search.query)
+                    .id(Id::new(SEARCH_ID))
+                    .hint_text(hint_text);
+                let res = ui.add_sized((180.0, 17.0), single_line);
+                if let Some(e) = invalid {
+                    res.on_hover_text(RichText::new(e.to_string()).color(*RED));
+                }
             });
             let text = egui_sfml::egui::RichText::new(egui_phosphor::regular::MAGNIFYING_GLASS)
                 .size(19.0)
@@ -233,24 +286,39 @@ pub fn lay_side_bar_content(interface: &mut DownloadManager, ui: &mut Ui) {
             }
             ui.add_space(1.0);
         });
+        ui.horizontal(|ui| {
+            ui.add_space(10.0);
+            for mode in SearchMode::ALL {
+                let text = RichText::new(mode.label()).monospace().color(*CYAN);
+                ui.selectable_value(&mut interface.search.mode, mode, text)
+                    .on_hover_text(RichText::new(mode.hint()).color(*CYAN));
+            }
+        });
     });
     ui.add(Separator::default().grow(ui.available_width()));
     ui.add_space(5.0);
-    let text = if interface.explorer.types.all.clicked {
-        let text = format!("{}{}All", CARET_RIGHT, FOLDER);
-        RichText::new(text).color(*GREEN).size(15.0)
-    } else {
-        let text = format!("{}All", FOLDER);
-        RichText::new(text).color(*CYAN).size(15.0)
-    };
+    //how many downloads fall under each type, shown next to its button
+    let total = interface.files.len();
+    let counts = interface.explorer.types.categories().map(|(_, _, inner)| {
+        interface
+            .files
+            .iter()
+            .filter(|f| has_ext(&f.file.name_on_disk, &inner.exts))
+            .count()
+    });
+    let tags = tag_counts(interface);
+    //the last download carrying the picked tag is gone, fall back to showing everything
+    if let Some(tag) = interface.explorer.tag.as_ref() {
+        if !interface.files.iter().any(|f| has_tag(&f.file, tag)) {
+            interface.explorer.toggle_off();
+            interface.explorer.types.all.clicked = true;
+        }
+    }
+
     ui.horizontal(|ui| {
         ui.add_space(5.0);
-        let butt = Button::new(text).frame(false);
-        let res = ui.add(butt);
-        if res.hovered() {
-            ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-        }
-        if res.clicked() {
+        let clicked = interface.explorer.types.all.clicked;
+        if category_button(ui, FOLDER, "All", total, clicked) {
             interface.explorer.toggle_off();
             interface.explorer.types.all.clicked = true;
             interface.explorer.current = vec![];
@@ -258,257 +326,100 @@ pub fn lay_side_bar_content(interface: &mut DownloadManager, ui: &mut Ui) {
     });
     ui.vertical(|ui| {
         ui.add_space(5.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.binaries.clicked {
-                    let text = format!("{}{} Binaries", CARET_RIGHT, BINARY);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Binaries", BINARY);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
-
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.binaries.clicked = true;
-                    interface.explorer.current = interface.explorer.types.binaries.exts.clone()
-                }
-                ui.add_space(5.0);
-            });
-        }
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.archives.clicked {
-                    let text = format!("{}{} Archives", CARET_RIGHT, ARCHIVE);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Archives", ARCHIVE);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
-
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.archives.clicked = true;
-                    interface.explorer.current = interface.explorer.types.archives.exts.clone()
-                }
-                ui.add_space(5.0);
-            });
-        }
-        ui.add_space(10.0);
-        {
+        let mut picked = None;
+        let categories = interface.explorer.types.categories();
+        for (idx, ((icon, label, inner), count)) in categories.iter().zip(counts).enumerate() {
             ui.horizontal(|ui| {
                 ui.add_space(20.0);
-                let text = if interface.explorer.types.audio.clicked {
-                    let text = format!("{}{} Audio", CARET_RIGHT, MUSIC_NOTE);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Audio", MUSIC_NOTE);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
-
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.audio.clicked = true;
-                    interface.explorer.current = interface.explorer.types.audio.exts.clone()
-                }
-                ui.add_space(5.0);
-            });
-        }
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.books.clicked {
-                    let text = format!("{}{} Books", CARET_RIGHT, BOOK_BOOKMARK);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Books", BOOK_BOOKMARK);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
-
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.books.clicked = true;
-                    interface.explorer.current = interface.explorer.types.books.exts.clone()
+                if category_button(ui, icon, label, count, inner.clicked) {
+                    picked = Some(idx);
                 }
                 ui.add_space(5.0);
             });
+            ui.add_space(10.0);
         }
-
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.fonts.clicked {
-                    let text = format!("{}{} Fonts", CARET_RIGHT, TEXT_AA);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Fonts", TEXT_AA);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
-
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.fonts.clicked = true;
-                    interface.explorer.current = interface.explorer.types.fonts.exts.clone();
-                }
-                ui.add_space(5.0);
-            });
+        if let Some(idx) = picked {
+            interface.explorer.toggle_off();
+            let inner = &mut interface.explorer.types.categories_mut()[idx];
+            inner.clicked = true;
+            let exts = inner.exts.clone();
+            interface.explorer.current = exts;
         }
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.images.clicked {
-                    let text = format!("{}{} Images", CARET_RIGHT, IMAGE);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Images", IMAGE);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
+    });
+    if tags.is_empty() {
+        return;
+    }
+    ui.add(Separator::default().grow(ui.available_width()));
+    ui.add_space(5.0);
+    ui.horizontal(|ui| {
+        ui.add_space(5.0);
+        ui.colored_label(*CYAN, RichText::new("Tags").size(15.0).strong());
+    });
+    ui.add_space(5.0);
+    ScrollArea::vertical()
+        .id_salt("tag_categories")
+        .show(ui, |ui| {
+            for (tag, count) in tags {
+                ui.horizontal(|ui| {
+                    ui.add_space(20.0);
+                    let clicked = interface
+                        .explorer
+                        .tag
+                        .as_ref()
+                        .is_some_and(|current| current.to_lowercase() == tag.to_lowercase());
+                    if category_button(ui, TAG, &tag, count, clicked) {
+                        interface.explorer.toggle_off();
+                        interface.explorer.current = vec![];
+                        interface.explorer.tag = Some(tag);
+                    }
+                    ui.add_space(5.0);
+                });
+                ui.add_space(10.0);
+            }
+        });
+}
 
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.images.clicked = true;
-                    interface.explorer.current = interface.explorer.types.images.exts.clone();
-                }
-                ui.add_space(5.0);
-            });
-        }
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.sheets.clicked {
-                    let text = format!("{}{} Sheets", CARET_RIGHT, NOTE);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Sheets", NOTE);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
+fn category_button(ui: &mut Ui, icon: &str, label: &str, count: usize, clicked: bool) -> bool {
+    let text = if clicked {
+        let text = format!("{}{} {}", CARET_RIGHT, icon, label);
+        RichText::new(text).color(*GREEN).size(15.0)
+    } else {
+        let text = format!("{} {}", icon, label);
+        RichText::new(text).color(*CYAN).size(15.0)
+    };
+    let butt = Button::new(text).frame(false);
+    let res = ui.add(butt);
+    if res.hovered() {
+        ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+    }
+    ui.colored_label(*GRAY, count.to_string());
+    res.clicked()
+}
 
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.sheets.clicked = true;
-                    interface.explorer.current = interface.explorer.types.sheets.exts.clone();
-                }
-                ui.add_space(5.0);
-            });
-        }
-        ui.add_space(10.0);
+//every tag in use with how many downloads carry it, tags differing only by case are counted together
+fn tag_counts(interface: &DownloadManager) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for tag in interface.files.iter().flat_map(|f| f.file.tags()) {
+        match counts
+            .iter_mut()
+            .find(|(seen, _)| seen.to_lowercase() == tag.to_lowercase())
         {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.slides.clicked {
-                    let text = format!("{}{} Slides", CARET_RIGHT, SLIDESHOW);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Slides", SLIDESHOW);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
-
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.slides.clicked = true;
-                    interface.explorer.current = interface.explorer.types.slides.exts.clone()
-                }
-                ui.add_space(5.0);
-            });
+            Some((_, count)) => *count += 1,
+            None => counts.push((tag, 1)),
         }
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.texts.clicked {
-                    let text = format!("{}{} Texts", CARET_RIGHT, TEXT_T);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Texts", TEXT_T);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
+    }
+    counts.sort_by_key(|(tag, _)| tag.to_lowercase());
+    counts
+}
 
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.texts.clicked = true;
-                    interface.explorer.current = interface.explorer.types.texts.exts.clone();
-                }
-                ui.add_space(5.0);
-            });
-        }
-        ui.add_space(10.0);
-        {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0);
-                let text = if interface.explorer.types.videos.clicked {
-                    let text = format!("{}{} Videos", CARET_RIGHT, VIDEO);
-                    RichText::new(text).color(*GREEN).size(15.0)
-                } else {
-                    let text = format!("{} Videos", VIDEO);
-                    RichText::new(text).color(*CYAN).size(15.0)
-                };
+pub fn has_ext(name: &str, exts: &[String]) -> bool {
+    let name = name.trim().to_lowercase();
+    exts.iter()
+        .any(|ext| name.ends_with(&ext.trim().to_lowercase()))
+}
 
-                let butt = Button::new(text).frame(false);
-                let res = ui.add(butt);
-                if res.hovered() {
-                    ui.output_mut(|o: &mut egui_sfml::egui::PlatformOutput| {
-                        o.cursor_icon = CursorIcon::PointingHand
-                    });
-                }
-                if res.clicked() {
-                    interface.explorer.toggle_off();
-                    interface.explorer.types.videos.clicked = true;
-                    interface.explorer.current = interface.explorer.types.videos.exts.clone();
-                }
-                ui.add_space(5.0);
-            });
-        }
-    });
+pub fn has_tag(file: &File2Dl, tag: &str) -> bool {
+    file.tags()
+        .iter()
+        .any(|t| t.to_lowercase() == tag.to_lowercase())
 }
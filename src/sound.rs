@@ -0,0 +1,107 @@
+use crate::{colors::RED, DownloadManager};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sfml::{
+    audio::{Sound, SoundBuffer},
+    cpp::FBox,
+};
+use std::{f32::consts::TAU, sync::atomic::Ordering::Relaxed};
+
+const SAMPLE_RATE: u32 = 44100;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SoundTrigger {
+    #[default]
+    Never,
+    EachDownload,
+    //once nothing is left running
+    AllDownloads,
+}
+
+impl SoundTrigger {
+    pub const ALL: [Self; 3] = [Self::Never, Self::EachDownload, Self::AllDownloads];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Never => "Never",
+            Self::EachDownload => "When a download completes",
+            Self::AllDownloads => "When all downloads complete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub trigger: SoundTrigger,
+    //0 to 100
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            trigger: SoundTrigger::default(),
+            volume: 60.0,
+            muted: false,
+        }
+    }
+}
+
+//built the first time it's played, the buffer is kept for the whole run since the sound borrows it
+#[derive(Default)]
+pub struct Chime {
+    sound: Option<FBox<Sound<'static>>>,
+}
+
+//two short decaying tones, so no audio file has to ship with the app
+fn chime_samples() -> Vec<i16> {
+    let tone = |freq: f32, secs: f32| {
+        let len = (SAMPLE_RATE as f32 * secs) as usize;
+        (0..len).map(move |idx| {
+            let t = idx as f32 / SAMPLE_RATE as f32;
+            let decay = (-t * 12.0).exp();
+            ((TAU * freq * t).sin() * decay * i16::MAX as f32 * 0.5) as i16
+        })
+    };
+    tone(880.0, 0.15).chain(tone(1320.0, 0.3)).collect()
+}
+
+fn build_chime() -> Option<FBox<Sound<'static>>> {
+    let buffer = SoundBuffer::from_samples(&chime_samples(), 1, SAMPLE_RATE).ok()?;
+    let buffer: &'static FBox<SoundBuffer> = Box::leak(Box::new(buffer));
+    Sound::with_buffer(buffer).ok()
+}
+
+//called with how many downloads finished since the last frame
+pub fn play_completion_sound(interface: &mut DownloadManager, finished: usize) {
+    let settings = interface.settings.sound;
+    if finished == 0 || settings.muted {
+        return;
+    }
+    match settings.trigger {
+        SoundTrigger::Never => return,
+        SoundTrigger::EachDownload => {}
+        SoundTrigger::AllDownloads => {
+            let running = interface
+                .files
+                .iter()
+                .any(|fdl| !fdl.file.complete.load(Relaxed) && fdl.file.running.load(Relaxed));
+            if running {
+                return;
+            }
+        }
+    }
+    if interface.chime.sound.is_none() {
+        interface.chime.sound = build_chime();
+    }
+    let Some(sound) = interface.chime.sound.as_mut() else {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = String::from("Couldn't set up the completion sound");
+        interface.popups.log.logs.push((formatted_time, text, *RED));
+        return;
+    };
+    sound.set_volume(settings.volume.clamp(0.0, 100.0));
+    sound.play();
+}
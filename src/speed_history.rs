@@ -0,0 +1,209 @@
+use crate::{colors::RED, dl::storage::data_file, DownloadManager};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Write},
+    sync::atomic::Ordering::Relaxed,
+    time::{Duration, Instant},
+};
+
+pub const HISTORY_FILE: &str = "speed_history.json";
+//an hour of one second samples and a day of one minute averages
+const MAX_SECONDS: usize = 3600;
+const MAX_MINUTES: usize = 1440;
+const SAVE_EVERY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Sample {
+    //unix time in seconds
+    pub at: i64,
+    pub total: usize,
+    //bytes per sec of every file that was transferring, keyed by name on disk
+    pub files: Vec<(String, usize)>,
+}
+
+impl Sample {
+    pub fn speed_of(&self, name: &str) -> usize {
+        self.files
+            .iter()
+            .find(|(file, _)| file == name)
+            .map(|(_, speed)| *speed)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum HistoryRange {
+    FiveMinutes,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl HistoryRange {
+    pub const ALL: [Self; 3] = [Self::FiveMinutes, Self::Hour, Self::Day];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::FiveMinutes => "Last 5 minutes",
+            Self::Hour => "Last hour",
+            Self::Day => "Last day",
+        }
+    }
+
+    fn secs(&self) -> i64 {
+        match self {
+            Self::FiveMinutes => 5 * 60,
+            Self::Hour => 3600,
+            Self::Day => 24 * 3600,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stored {
+    seconds: VecDeque<Sample>,
+    minutes: VecDeque<Sample>,
+}
+
+pub struct SpeedHistory {
+    seconds: VecDeque<Sample>,
+    minutes: VecDeque<Sample>,
+    last_sample: Instant,
+    last_save: Instant,
+}
+
+impl Default for SpeedHistory {
+    fn default() -> Self {
+        let stored = Self::parse().unwrap_or_default();
+        Self {
+            seconds: stored.seconds,
+            minutes: stored.minutes,
+            last_sample: Instant::now(),
+            last_save: Instant::now(),
+        }
+    }
+}
+
+impl SpeedHistory {
+    fn parse() -> Result<Stored, std::io::Error> {
+        let mut buffer = String::new();
+        File::open(data_file(HISTORY_FILE))?.read_to_string(&mut buffer)?;
+        Ok(serde_json::from_str(&buffer)?)
+    }
+
+    fn save(&self) -> Result<(), std::io::Error> {
+        let stored = Stored {
+            seconds: self.seconds.clone(),
+            minutes: self.minutes.clone(),
+        };
+        let mut file = File::create(data_file(HISTORY_FILE))?;
+        file.write_all(serde_json::to_string(&stored)?.as_bytes())
+    }
+
+    fn push(&mut self, sample: Sample) {
+        let minute = sample.at.div_euclid(60);
+        let closed_minute = self
+            .seconds
+            .back()
+            .map(|last| last.at.div_euclid(60))
+            .filter(|last| *last != minute);
+        if let Some(closed) = closed_minute {
+            self.minutes.push_back(self.average_of(closed));
+            while self.minutes.len() > MAX_MINUTES {
+                self.minutes.pop_front();
+            }
+        }
+        self.seconds.push_back(sample);
+        while self.seconds.len() > MAX_SECONDS {
+            self.seconds.pop_front();
+        }
+    }
+
+    fn average_of(&self, minute: i64) -> Sample {
+        let samples = self
+            .seconds
+            .iter()
+            .filter(|s| s.at.div_euclid(60) == minute)
+            .collect::<Vec<_>>();
+        let count = samples.len().max(1);
+        let mut files: Vec<(String, usize)> = Vec::new();
+        for (name, speed) in samples.iter().flat_map(|s| s.files.iter()) {
+            match files.iter_mut().find(|(file, _)| file == name) {
+                Some((_, sum)) => *sum += speed,
+                None => files.push((name.clone(), *speed)),
+            }
+        }
+        Sample {
+            at: minute * 60,
+            total: samples.iter().map(|s| s.total).sum::<usize>() / count,
+            files: files
+                .into_iter()
+                .map(|(name, sum)| (name, sum / count))
+                .collect(),
+        }
+    }
+
+    //the last hour is drawn from the per second samples, anything longer from the minute averages
+    pub fn samples(&self, range: HistoryRange, now: i64) -> Vec<&Sample> {
+        let source = if range.secs() <= 3600 {
+            &self.seconds
+        } else {
+            &self.minutes
+        };
+        source
+            .iter()
+            .filter(|s| s.at >= now - range.secs())
+            .collect()
+    }
+
+    //names of the files that show up anywhere in the range
+    pub fn files(&self, range: HistoryRange, now: i64) -> Vec<String> {
+        let mut names = Vec::new();
+        for sample in self.samples(range, now) {
+            for (name, _) in sample.files.iter() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names.sort();
+        names
+    }
+}
+
+//samples once per second and writes the history out every minute
+pub fn record_speed(interface: &mut DownloadManager) {
+    let history = &mut interface.speed_history;
+    if history.last_sample.elapsed() < Duration::from_secs(1) {
+        return;
+    }
+    history.last_sample = Instant::now();
+    let files = interface
+        .files
+        .iter()
+        .map(|f| {
+            (
+                f.file.name_on_disk.clone(),
+                f.file.bytes_per_sec.load(Relaxed),
+            )
+        })
+        .filter(|(_, speed)| *speed > 0)
+        .collect();
+    history.push(Sample {
+        at: Local::now().timestamp(),
+        total: interface.bandwidth.total_bandwidth,
+        files,
+    });
+    if history.last_save.elapsed() < SAVE_EVERY {
+        return;
+    }
+    history.last_save = Instant::now();
+    if let Err(e) = history.save() {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!("Couldn't write to file: {:?}", e);
+        interface.popups.log.logs.push((formatted_time, text, *RED));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{colors::CYAN, dl::eta::format_size, i18n::trf, DownloadManager};
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering::Relaxed;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpeedRule {
+    pub exts: Vec<String>,
+    pub limit: usize,
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+//same start and end hour means the window is active all day, windows can wrap past midnight
+pub fn hour_in_window(start_hour: u32, end_hour: u32, hour: u32) -> bool {
+    if start_hour == end_hour {
+        true
+    } else if start_hour < end_hour {
+        hour >= start_hour && hour < end_hour
+    } else {
+        hour >= start_hour || hour < end_hour
+    }
+}
+
+impl SpeedRule {
+    pub fn is_active(&self, hour: u32) -> bool {
+        hour_in_window(self.start_hour, self.end_hour, hour)
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        self.exts
+            .iter()
+            .any(|ext| name.ends_with(&format!(".{}", ext.trim().to_lowercase())))
+    }
+}
+
+//0 is no limit at all
+fn stricter(a: usize, b: usize) -> usize {
+    match (a, b) {
+        (0, limit) | (limit, 0) => limit,
+        (a, b) => a.min(b),
+    }
+}
+
+//a rule only ever slows a download down, the user's own limit wins when it's lower
+pub fn apply_speed_rules(interface: &mut DownloadManager) {
+    let hour = Local::now().hour();
+    let rules = &interface.settings.speed_rules;
+    for fdl in interface.files.iter_mut() {
+        let file = &fdl.file;
+        if file.complete.load(Relaxed) {
+            continue;
+        }
+        //the lowest cap wins when several rules match the same file
+        let rule_limit = rules
+            .iter()
+            .filter(|rule| rule.is_active(hour) && rule.matches(&file.name_on_disk))
+            .map(|rule| rule.limit)
+            .min();
+        match (rule_limit, fdl.speed_before_rule) {
+            (Some(limit), None) => {
+                let speed = file.speed.load(Relaxed);
+                fdl.speed_before_rule = Some(speed);
+                let limit = stricter(limit, speed);
+                if limit != speed {
+                    file.speed.store(limit, Relaxed);
+                    let text = trf(
+                        "A speed rule limited {} to {}/s",
+                        &[&file.name_on_disk, &format_size(limit)],
+                    );
+                    interface.toasts.push(text, *CYAN);
+                }
+            }
+            (Some(limit), Some(speed)) => file.speed.store(stricter(limit, speed), Relaxed),
+            (None, Some(speed)) => {
+                file.speed.store(speed, Relaxed);
+                fdl.speed_before_rule = None;
+            }
+            (None, None) => {}
+        }
+    }
+}
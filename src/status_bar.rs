@@ -1,8 +1,18 @@
-use crate::{colors::*, DownloadManager};
+use crate::{
+    colors::*,
+    dl::file2dl::FileKey,
+    i18n::{tr, trf},
+    sound::SoundTrigger,
+    DownloadManager,
+};
+use chrono::Local;
 use egui_sfml::egui::{self, Button, CursorIcon, Label, Layout, RichText, Separator, Ui};
 use std::{
     net::TcpStream,
-    sync::mpsc::{channel, Receiver, Sender},
+    sync::{
+        atomic::Ordering::Relaxed,
+        mpsc::{channel, Receiver, Sender},
+    },
     thread::sleep,
     time::Duration,
 };
@@ -11,19 +21,114 @@ use std::{
 pub struct Connection {
     connected: bool,
     channel: (Sender<bool>, Receiver<bool>),
+    //downloads paused because the network went away, only these get resumed
+    auto_paused: Vec<FileKey>,
 }
 impl Default for Connection {
     fn default() -> Self {
         Self {
             channel: channel(),
             connected: false,
+            auto_paused: Vec::new(),
         }
     }
 }
 
 pub fn update_connected(interface: &mut DownloadManager) {
     if let Ok(val) = interface.connection.channel.1.try_recv() {
+        let was_connected = interface.connection.connected;
         interface.connection.connected = val;
+        if !interface.settings.auto_resume || was_connected == val {
+            return;
+        }
+        if val {
+            resume_after_reconnect(interface);
+        } else {
+            pause_on_disconnect(interface);
+        }
+    }
+}
+
+fn pause_on_disconnect(interface: &mut DownloadManager) {
+    for fdl in interface.files.iter() {
+        let file = &fdl.file;
+        if file.running.load(Relaxed) && !file.complete.load(Relaxed) {
+            file.running.store(false, Relaxed);
+            interface.connection.auto_paused.push(file.key());
+        }
+    }
+    if interface.connection.auto_paused.is_empty() {
+        return;
+    }
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!(
+        "Connection lost: paused {} downloads",
+        interface.connection.auto_paused.len()
+    );
+    interface.popups.log.logs.push((formatted_time, text, *RED));
+}
+
+fn resume_after_reconnect(interface: &mut DownloadManager) {
+    let auto_paused = std::mem::take(&mut interface.connection.auto_paused);
+    let mut resumed = 0;
+    for fdl in interface.files.iter_mut() {
+        let file = &fdl.file;
+        if file.complete.load(Relaxed) || file.cancelled.load(Relaxed) {
+            continue;
+        }
+        if auto_paused.iter().any(|key| file.is(key)) {
+            file.running.store(true, Relaxed);
+            resumed += 1;
+            continue;
+        }
+        //a transfer that gave up on an error while the row was still running gets a new attempt
+        let task_alive = fdl.task_alive();
+        if file.running.load(Relaxed) && fdl.initiated && !task_alive {
+            fdl.initiated = false;
+            resumed += 1;
+        }
+    }
+    if resumed == 0 {
+        return;
+    }
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Connection is back: resumed {} downloads", resumed);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *GREEN));
+}
+
+//the settings window's sound section has to agree with the switch, it's saved right away
+fn toggle_mute(interface: &mut DownloadManager, ui: &mut Ui) {
+    let sound = &mut interface.settings.sound;
+    let (icon, tooltip) = if sound.muted {
+        (egui_phosphor::fill::SPEAKER_SLASH, tr("Unmute"))
+    } else {
+        (egui_phosphor::fill::SPEAKER_HIGH, tr("Mute"))
+    };
+    let text = RichText::new(icon).size(25.0).color(*CYAN);
+    let res = ui.add(
+        Label::new(text)
+            .selectable(false)
+            .sense(egui::Sense::click()),
+    );
+    if res.hovered() {
+        ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+        res.show_tooltip_text(RichText::new(tooltip).color(*CYAN));
+    }
+    if !res.clicked() {
+        return;
+    }
+    sound.muted = !sound.muted;
+    interface.popups.settings.sound.muted = sound.muted;
+    if let Err(e) = interface.settings.save() {
+        let formatted_time = Local::now().format("%H:%M:%S").to_string();
+        let text = format!("Couldn't save the settings: {}", e);
+        interface.popups.log.logs.push((formatted_time, text, *RED));
     }
 }
 
@@ -39,7 +144,7 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 let res = ui.add(label);
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::Default);
-                    let text = RichText::new("Connected").color(*GREEN);
+                    let text = RichText::new(tr("Connected")).color(*GREEN);
                     res.show_tooltip_text(text);
                 }
             } else {
@@ -50,12 +155,24 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 let res = ui.add(label);
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::Default);
-                    let text = RichText::new("Disconnected").color(*RED);
+                    let text = RichText::new(tr("Disconnected")).color(*RED);
                     res.show_tooltip_text(text);
                 }
             }
         });
         ui.add(Separator::grow(Separator::default(), 35.0));
+        if interface.settings.sound.trigger != SoundTrigger::Never {
+            toggle_mute(interface, ui);
+            ui.add(Separator::grow(Separator::default(), 35.0));
+        }
+        if !interface.search.is_empty() {
+            let text = match interface.search.matches {
+                1 => tr("1 match").to_string(),
+                n => trf("{} matches", &[&n]),
+            };
+            ui.colored_label(*CYAN, text);
+            ui.add(Separator::grow(Separator::default(), 35.0));
+        }
         ui.add_space(ui.available_width() - 200.0);
         ui.horizontal_centered(|ui| {
             {
@@ -69,7 +186,7 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 }
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                    let text = RichText::new("Modify Settings").color(*CYAN);
+                    let text = RichText::new(tr("Modify Settings")).color(*CYAN);
                     res.show_tooltip_text(text);
                 }
             }
@@ -89,7 +206,7 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 }
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                    let text = RichText::new("Logs").color(*CYAN);
+                    let text = RichText::new(tr("Logs")).color(*CYAN);
                     res.show_tooltip_text(text);
                 }
             }
@@ -102,7 +219,7 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
                     let text =
-                        RichText::new("Modify download speed for selected files").color(*CYAN);
+                        RichText::new(tr("Modify download speed for selected files")).color(*CYAN);
                     res.show_tooltip_text(text);
                 }
                 if res.clicked() {
@@ -118,7 +235,7 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 let res = ui.add(butt);
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                    let text = RichText::new("Live plotting of download speed").color(*CYAN);
+                    let text = RichText::new(tr("Live plotting of download speed")).color(*CYAN);
                     res.show_tooltip_text(text);
                 }
                 if res.clicked() {
@@ -136,7 +253,7 @@ pub fn init_status_bar(interface: &mut DownloadManager, ui: &mut Ui) {
                 }
                 if res.hovered() {
                     ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
-                    let text = RichText::new("Add Download").color(*CYAN);
+                    let text = RichText::new(tr("Add Download")).color(*CYAN);
                     res.show_tooltip_text(text);
                 }
             }
@@ -1,28 +1,120 @@
 use crate::{
-    colors::{CYAN, DARK_INNER, GRAY, RED},
-    dl::file2dl::File2Dl,
-    Actions, DownloadManager,
+    batch::{apply_to_selected, SelectionAction},
+    cancel::restart_cancelled,
+    colors::{CYAN, DARK_INNER, GRAY, GREEN, RED},
+    columns::{store_widths, visible_columns, ColumnKind},
+    content_index::file_path,
+    details::show_details,
+    dl::{
+        eta::{format_eta, format_size},
+        file2dl::{File2Dl, FileKey},
+        virustotal::ScanVerdict,
+    },
+    extract::{extract_archive, is_archive},
+    i18n::{tr, trf},
+    menu_bar::{copy_selected, selection_menu, CopyField},
+    popups::ask_confirm,
+    queue::{move_in_queue, queue_key, QueueMove},
+    restart::{restart_download, retry_download},
+    scan::ScanState,
+    search::{Matcher, SearchMode},
+    side_bar::{has_ext, has_tag},
+    update_check::check_for_update,
+    verification::verify_file,
+    Actions, DownloadManager, FDl,
 };
+use chrono::{DateTime, Local};
 use egui_extras::{Column, TableBuilder};
-#[cfg(unix)]
+#[cfg(target_os = "linux")]
 use std::os::unix::process::CommandExt;
 use std::process::Command;
 
+use egui_sfml::egui::text::LayoutJob;
 use egui_sfml::egui::*;
 use irox_egui_extras::progressbar::ProgressBar;
+use std::ops::Range;
 use std::sync::atomic::Ordering::Relaxed;
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+}
+
+impl StatusFilter {
+    const ALL: [Self; 5] = [
+        Self::All,
+        Self::Downloading,
+        Self::Paused,
+        Self::Completed,
+        Self::Failed,
+    ];
+
+    fn label(&self) -> &str {
+        match self {
+            Self::All => "All",
+            Self::Downloading => "Downloading",
+            Self::Paused => "Paused",
+            Self::Completed => "Completed",
+            Self::Failed => "Failed",
+        }
+    }
+
+    fn matches(&self, fdl: &FDl) -> bool {
+        let file = &fdl.file;
+        let complete = file.complete.load(Relaxed);
+        let failed = if complete {
+            file.verify_failed.load(Relaxed) || fdl.extract_failed
+        } else {
+            file.failed.load(Relaxed) || fdl.error.is_some()
+        };
+        match self {
+            Self::All => true,
+            Self::Downloading => file.running.load(Relaxed) && !complete && !failed,
+            Self::Paused => !file.running.load(Relaxed) && !complete && !failed,
+            Self::Completed => complete && !failed,
+            Self::Failed => failed,
+        }
+    }
+}
+
+pub fn lay_status_tabs(interface: &mut DownloadManager, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        for filter in StatusFilter::ALL {
+            let count = interface.files.iter().filter(|f| filter.matches(f)).count();
+            let color = match filter {
+                StatusFilter::Failed if count > 0 => *RED,
+                _ => *CYAN,
+            };
+            let text = RichText::new(format!("{} ({})", tr(filter.label()), count))
+                .color(color)
+                .strong();
+            ui.selectable_value(&mut interface.status_filter, filter, text);
+        }
+    });
+    ui.add_space(3.0);
+}
+
 pub fn lay_table(interface: &mut DownloadManager, ui: &mut Ui, ctx: &Context) {
     let available_width = ui.available_width();
-    TableBuilder::new(ui)
+    let columns = visible_columns(&interface.settings.columns);
+    let kinds = columns.iter().map(|c| c.kind).collect::<Vec<_>>();
+    let mut table = TableBuilder::new(ui)
+        //a different set or order of columns starts from the saved widths instead of the old drag state
+        .id_salt(&kinds)
         .auto_shrink(false)
         .striped(false)
-        .column(Column::exact(available_width * 0.04))
-        .column(Column::initial(available_width * 0.1855))
-        .column(Column::initial(available_width * 0.255))
-        .column(Column::initial(available_width * 0.15))
-        .column(Column::initial(available_width * 0.17))
-        .column(Column::initial(available_width * 0.1995))
+        .resizable(true)
+        .sense(Sense::click())
+        .column(Column::exact(available_width * 0.04));
+    for column in columns.iter() {
+        table = table.column(Column::initial(available_width * column.width).at_least(30.0));
+    }
+    table
         .header(20.0, |mut header| {
             header.col(|ui| {
                 select_logic(interface);
@@ -31,78 +123,94 @@ pub fn lay_table(interface: &mut DownloadManager, ui: &mut Ui, ctx: &Context) {
                     ui.add(Checkbox::without_text(&mut interface.select.select_all));
                 });
             });
-            header.col(|ui| {
-                let text = RichText::new("Filename").color(*CYAN).strong();
-                ui.horizontal_centered(|ui| {
-                    ui.heading(text);
-                });
-
-                ui.add(
-                    Separator::default()
-                        .horizontal()
-                        .grow(ctx.screen_rect().width()),
-                );
-            });
-            header.col(|ui| {
-                let text = RichText::new("Progress").color(*CYAN).strong();
-                ui.vertical_centered(|ui| {
-                    ui.heading(text);
-                });
-            });
-            header.col(|ui| {
-                let text = RichText::new("Speed").color(*CYAN).strong();
-                ui.vertical_centered(|ui| {
-                    ui.heading(text);
-                });
-            });
-            header.col(|ui| {
-                let text = RichText::new("On save").color(*CYAN).strong();
-                ui.vertical_centered(|ui| {
-                    ui.heading(text);
-                });
-            });
-            header.col(|ui| {
-                let text = RichText::new("Status").color(*CYAN).strong();
-                ui.vertical_centered(|ui| {
-                    ui.heading(text);
+            for kind in kinds.iter() {
+                header.col(|ui| {
+                    let text = RichText::new(tr(kind.label())).color(*CYAN).strong();
+                    if *kind == ColumnKind::Filename {
+                        ui.horizontal_centered(|ui| {
+                            ui.heading(text);
+                        });
+                        ui.add(
+                            Separator::default()
+                                .horizontal()
+                                .grow(ctx.screen_rect().width()),
+                        );
+                    } else {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(text);
+                        });
+                    }
                 });
-            });
+            }
         })
         .body(|mut body| {
+            //widths are written back once a column edge was dragged and let go, not on every window resize
+            let dragging = ctx.input(|i| i.pointer.any_down());
+            let widths = body.widths()[1..].to_vec();
+            let id = Id::new("column_widths");
+            let (last, mut resized) = ctx
+                .data(|d| d.get_temp::<(Vec<f32>, bool)>(id))
+                .unwrap_or_else(|| (widths.clone(), false));
+            resized |= dragging && last != widths;
+            if resized
+                && !dragging
+                && store_widths(&mut interface.settings.columns, &widths, available_width)
+            {
+                if let Err(e) = interface.settings.save() {
+                    let formatted_time = Local::now().format("%H:%M:%S").to_string();
+                    let text = format!("Couldn't save the column layout: {}", e);
+                    interface.popups.log.logs.push((formatted_time, text, *RED));
+                }
+            }
+            ctx.data_mut(|d| d.insert_temp(id, (widths, resized && dragging)));
+            //an unfinished regex matches nothing until it parses, the search box shows why
+            let matcher = Matcher::new(&interface.search).ok();
             let mut to_display = interface
                 .files
                 .iter()
                 .filter(|f| {
-                    f.file
-                        .name_on_disk
-                        .to_lowercase()
-                        .contains(&interface.search)
-                        || f.file.url.link.to_lowercase().contains(&interface.search)
+                    matcher
+                        .as_ref()
+                        .is_some_and(|matcher| search_matches(interface, matcher, f))
                 })
                 .map(|f| f.to_owned())
                 .collect::<Vec<_>>();
+            let drawer_delay = interface.settings.drawer_delay;
+            let status_filter = interface.status_filter;
+            to_display.retain(|f| status_filter.matches(f));
+            //searches and the completed tab also reach into the drawer so indexed archives can be found
+            if interface.search.is_empty() && status_filter != StatusFilter::Completed {
+                to_display.retain(|f| !f.in_drawer(drawer_delay));
+            }
             if !interface.explorer.current.is_empty() {
-                to_display.retain(|f| {
-                    interface.explorer.current.iter().any(|ext| {
-                        f.file
-                            .name_on_disk
-                            .trim()
-                            .to_lowercase()
-                            .ends_with(&ext.trim().to_lowercase())
-                    })
-                });
+                to_display.retain(|f| has_ext(&f.file.name_on_disk, &interface.explorer.current));
             }
+            if let Some(tag) = interface.explorer.tag.as_ref() {
+                to_display.retain(|f| has_tag(&f.file, tag));
+            }
+            interface.search.matches = to_display.len();
 
-            to_display.sort_by(|a, b| {
-                (a.file.complete.load(Relaxed), &a.file.name_on_disk)
-                    .cmp(&(b.file.complete.load(Relaxed), &b.file.name_on_disk))
-            });
+            to_display.sort_by(|a, b| queue_key(a).cmp(&queue_key(b)));
+            let selected_count = interface.files.iter().filter(|f| f.selected).count();
+            let shown = to_display.iter().map(|f| f.file.key()).collect::<Vec<_>>();
+            let mut clicked = None;
+            let mut dropped = None;
             for fdl in to_display.iter_mut() {
                 let file = &fdl.file;
                 let complete = file.complete.load(std::sync::atomic::Ordering::Relaxed);
                 let new = fdl.new;
                 let file_has_error = fdl.has_error;
+                let progress = RowProgress::of(fdl);
+                let scan = fdl.scan.clone();
+                let in_selection = if fdl.selected { selected_count } else { 0 };
+                let ranges = matcher
+                    .as_ref()
+                    .map(|matcher| matcher.ranges(&file.name_on_disk))
+                    .unwrap_or_default();
                 body.row(30.0, |mut row| {
+                    row.set_selected(fdl.selected);
+                    let mut name_clicked = false;
+                    let mut name_double_clicked = false;
                     row.col(|ui| {
                         ui.vertical(|ui| {
                             ui.add_space(3.0);
@@ -116,144 +224,725 @@ pub fn lay_table(interface: &mut DownloadManager, ui: &mut Ui, ctx: &Context) {
                         let file = interface
                             .files
                             .iter_mut()
-                            .find(|f| f.file.name_on_disk == fdl.file.name_on_disk);
+                            .find(|f| f.file.same_file(&fdl.file));
                         if let Some(f) = file {
                             f.selected = fdl.selected;
                         }
                     });
-                    row.col(|ui| {
-                        file_name(file_has_error, &file.name_on_disk, ui);
-                        ui.add(
-                            Separator::default()
-                                .horizontal()
-                                .grow(ctx.screen_rect().width()),
-                        );
-                    });
-                    row.col(|ui| progress_bar(file, ui, ctx));
-                    row.col(|ui| {
-                        ui.vertical(|ui| {
-                            ui.add_space(5.0);
-                            let text = RichText::new(format!(
-                                "{:.2} Mbs",
-                                file.bytes_per_sec
-                                    .load(std::sync::atomic::Ordering::Relaxed)
-                                    as f64
-                                    / (1024.0 * 1024.0)
-                            ))
-                            .size(15.0)
-                            .strong();
-                            let label = Label::new(text).wrap_mode(TextWrapMode::Truncate);
-                            let res = ui.add_sized(
-                                (ui.available_width(), ui.available_height() - 10.0),
-                                label,
-                            );
-                            if res.hovered() {
-                                let text = RichText::new(format!(
-                                    "Limited to: {:.2}MBs",
-                                    (file.speed.load(std::sync::atomic::Ordering::Relaxed) as f64
-                                        / (1024 * 1024) as f64)
-                                ))
-                                .color(*CYAN);
-                                res.show_tooltip_text(text);
-                            }
-                            ui.add_space(5.0);
-                        });
-                    });
-                    row.col(|ui| {
-                        let file_to_change = interface
-                            .files
-                            .iter_mut()
-                            .find(|f| f.file.name_on_disk == fdl.file.name_on_disk)
-                            .unwrap();
-
-                        match file_to_change.action_on_save {
-                            Actions::Open if complete => {
-                                let path = format!("{}/{}", fdl.file.dl_dir, fdl.file.name_on_disk);
-                                match opener::open(path) {
-                                    Ok(_) => {
-                                        let _ = file_to_change.action_on_save == Actions::None;
+                    for kind in kinds.iter() {
+                        row.col(|ui| match kind {
+                            ColumnKind::Filename => {
+                                let (action, res) =
+                                    file_name(file_has_error, file, scan.as_ref(), &ranges, in_selection, ui);
+                                name_clicked |= res.clicked();
+                                name_double_clicked |= res.double_clicked();
+                                match action {
+                                    Some(RowAction::Verify) => {
+                                        verify_file(interface, &file.key())
+                                    }
+                                    Some(RowAction::Extract) => extract_archive(interface, file),
+                                    Some(RowAction::OpenSource) => {
+                                        open_source_page(interface, file)
+                                    }
+                                    Some(RowAction::OpenFolder) => open_folder(interface, file),
+                                    Some(RowAction::ShowDetails) => {
+                                        show_details(interface, &file.key())
+                                    }
+                                    Some(RowAction::Copy(field)) => {
+                                        copy_rows(interface, file, field, ui)
                                     }
-                                    Err(e) => {
-                                        interface.popups.error.value = e.to_string();
-                                        interface.popups.error.show = true;
+                                    Some(RowAction::EditTags) => edit_tags(interface, file),
+                                    Some(RowAction::EditNote) => edit_note(interface, file),
+                                    Some(RowAction::UpdateUrl) => edit_url(interface, file),
+                                    Some(RowAction::Selection(selection)) => {
+                                        apply_to_selected(interface, selection)
                                     }
+                                    Some(RowAction::Queue(to)) => {
+                                        move_in_queue(interface, &file.key(), to)
+                                    }
+                                    Some(RowAction::PlotSpeed) => {
+                                        plot_speed(interface, &file.name_on_disk)
+                                    }
+                                    Some(RowAction::CheckUpdate) => {
+                                        check_for_update(interface, &file.key())
+                                    }
+                                    Some(RowAction::RestartFromZero) => {
+                                        let key = file.key();
+                                        let text = trf(
+                                            "This will delete the downloaded data of {} and start over",
+                                            &[&file.name_on_disk],
+                                        );
+                                        ask_confirm(
+                                            interface,
+                                            None,
+                                            *RED,
+                                            text,
+                                            Box::new(move || {
+                                                let key = key.clone();
+                                                Box::new(move |app: &mut DownloadManager| {
+                                                    restart_download(app, &key);
+                                                })
+                                            }),
+                                        );
+                                    }
+                                    _ => {}
                                 }
+                                ui.add(
+                                    Separator::default()
+                                        .horizontal()
+                                        .grow(ctx.screen_rect().width()),
+                                );
                             }
-                            Actions::Reboot if complete => {
-                                reboot_system();
-                            }
-                            Actions::Shutdown if complete => {
-                                shutdown_system();
+                            ColumnKind::Url => url_cell(file, ui),
+                            ColumnKind::Progress => progress_bar(file, &progress, ui, ctx),
+                            ColumnKind::Speed => {
+                                ui.vertical(|ui| {
+                                    ui.add_space(5.0);
+                                    let eta = file.eta().map(format_eta);
+                                    let speed = format!(
+                                        "{:.2} Mbs",
+                                        file.bytes_per_sec
+                                            .load(std::sync::atomic::Ordering::Relaxed)
+                                            as f64
+                                            / (1024.0 * 1024.0)
+                                    );
+                                    //the time left gets its own cell once that column is shown
+                                    let text = match &eta {
+                                        Some(eta) if !kinds.contains(&ColumnKind::Eta) => {
+                                            format!("{} · {}", speed, eta)
+                                        }
+                                        _ => speed,
+                                    };
+                                    let text = RichText::new(text).size(15.0).strong();
+                                    let label = Label::new(text).wrap_mode(TextWrapMode::Truncate);
+                                    let res = ui.add_sized(
+                                        (ui.available_width(), ui.available_height() - 10.0),
+                                        label,
+                                    );
+                                    if res.hovered() {
+                                        let limit = format!(
+                                            "{:.2}",
+                                            (file.speed.load(std::sync::atomic::Ordering::Relaxed)
+                                                as f64
+                                                / (1024 * 1024) as f64)
+                                        );
+                                        let mut text = trf("Limited to: {}MBs", &[&limit]);
+                                        if let Some(eta) = eta {
+                                            text.push('\n');
+                                            text.push_str(&trf("Time left: {}", &[&eta]));
+                                        }
+                                        let text = RichText::new(text).color(*CYAN);
+                                        res.show_tooltip_text(text);
+                                    }
+                                    ui.add_space(5.0);
+                                });
                             }
-                            _ => {}
-                        }
-                        ui.vertical_centered(|ui| {
-                            ui.visuals_mut().widgets.inactive.weak_bg_fill = *CYAN;
-                            ui.visuals_mut().widgets.open.weak_bg_fill = *CYAN;
-                            ui.visuals_mut().widgets.hovered.weak_bg_fill = *CYAN;
-                            ui.visuals_mut().widgets.active.weak_bg_fill = *CYAN;
-                            ui.visuals_mut().widgets.inactive.fg_stroke.color = *DARK_INNER;
-                            ui.visuals_mut().widgets.open.fg_stroke.color = *DARK_INNER;
-                            ui.visuals_mut().widgets.hovered.fg_stroke.color = *DARK_INNER;
-                            ui.visuals_mut().widgets.active.fg_stroke.color = *DARK_INNER;
-                            ui.visuals_mut().override_text_color = Some(*DARK_INNER);
-                            if !complete {
-                                ui.centered_and_justified(|ui| {
-                                    egui_sfml::egui::ComboBox::from_label("")
-                                        .selected_text(format!(
-                                            "{:?}",
-                                            file_to_change.action_on_save
-                                        ))
-                                        .width(available_width * 0.2)
-                                        .show_ui(ui, |ui| {
-                                            ui.selectable_value(
-                                                &mut file_to_change.action_on_save,
-                                                Actions::None,
-                                                "None",
-                                            );
-                                            ui.selectable_value(
-                                                &mut file_to_change.action_on_save,
-                                                Actions::Open,
-                                                "Open",
-                                            );
-                                            ui.selectable_value(
-                                                &mut file_to_change.action_on_save,
-                                                Actions::Shutdown,
-                                                "Shutdown",
-                                            );
-                                            ui.selectable_value(
-                                                &mut file_to_change.action_on_save,
-                                                Actions::Reboot,
-                                                "Reboot",
-                                            );
+                            ColumnKind::Eta => eta_cell(file, ui),
+                            ColumnKind::Remaining => remaining_cell(file, complete, ui),
+                            ColumnKind::Added => added_cell(file, ui),
+                            ColumnKind::OnSave => {
+                                let file_to_change = interface
+                                    .files
+                                    .iter_mut()
+                                    .find(|f| f.file.same_file(&fdl.file))
+                                    .unwrap();
+
+                                match file_to_change.action_on_save {
+                                    Actions::Open if complete => {
+                                        let path = format!(
+                                            "{}/{}",
+                                            fdl.file.dl_dir, fdl.file.name_on_disk
+                                        );
+                                        match opener::open(path) {
+                                            Ok(_) => {
+                                                let _ =
+                                                    file_to_change.action_on_save == Actions::None;
+                                            }
+                                            Err(e) => {
+                                                interface.popups.error.value = e.to_string();
+                                                interface.popups.error.show = true;
+                                            }
+                                        }
+                                    }
+                                    Actions::Reboot if complete => {
+                                        reboot_system();
+                                    }
+                                    Actions::Shutdown if complete => {
+                                        shutdown_system();
+                                    }
+                                    _ => {}
+                                }
+                                ui.vertical_centered(|ui| {
+                                    ui.visuals_mut().widgets.inactive.weak_bg_fill = *CYAN;
+                                    ui.visuals_mut().widgets.open.weak_bg_fill = *CYAN;
+                                    ui.visuals_mut().widgets.hovered.weak_bg_fill = *CYAN;
+                                    ui.visuals_mut().widgets.active.weak_bg_fill = *CYAN;
+                                    ui.visuals_mut().widgets.inactive.fg_stroke.color = *DARK_INNER;
+                                    ui.visuals_mut().widgets.open.fg_stroke.color = *DARK_INNER;
+                                    ui.visuals_mut().widgets.hovered.fg_stroke.color = *DARK_INNER;
+                                    ui.visuals_mut().widgets.active.fg_stroke.color = *DARK_INNER;
+                                    ui.visuals_mut().override_text_color = Some(*DARK_INNER);
+                                    if !complete {
+                                        ui.centered_and_justified(|ui| {
+                                            egui_sfml::egui::ComboBox::from_label("")
+                                                .selected_text(format!(
+                                                    "{:?}",
+                                                    file_to_change.action_on_save
+                                                ))
+                                                .width(available_width * 0.2)
+                                                .show_ui(ui, |ui| {
+                                                    ui.selectable_value(
+                                                        &mut file_to_change.action_on_save,
+                                                        Actions::None,
+                                                        tr("None"),
+                                                    );
+                                                    ui.selectable_value(
+                                                        &mut file_to_change.action_on_save,
+                                                        Actions::Open,
+                                                        tr("Open"),
+                                                    );
+                                                    ui.selectable_value(
+                                                        &mut file_to_change.action_on_save,
+                                                        Actions::Shutdown,
+                                                        tr("Shutdown"),
+                                                    );
+                                                    ui.selectable_value(
+                                                        &mut file_to_change.action_on_save,
+                                                        Actions::Reboot,
+                                                        tr("Reboot"),
+                                                    );
+                                                });
                                         });
-                                });
-                            } else {
-                                ui.centered_and_justified(|ui| {
-                                    ComboBox::from_label("")
-                                        .width(available_width * 0.2)
-                                        .height(ui.available_height() - 10.0)
-                                        .selected_text(format!("{:?}", fdl.action_on_save))
-                                        .show_ui(ui, |ui| {
-                                            ui.selectable_value(
-                                                &mut file_to_change.action_on_save,
-                                                Actions::None,
-                                                "None",
-                                            );
+                                    } else {
+                                        ui.centered_and_justified(|ui| {
+                                            ComboBox::from_label("")
+                                                .width(available_width * 0.2)
+                                                .height(ui.available_height() - 10.0)
+                                                .selected_text(format!("{:?}", fdl.action_on_save))
+                                                .show_ui(ui, |ui| {
+                                                    ui.selectable_value(
+                                                        &mut file_to_change.action_on_save,
+                                                        Actions::None,
+                                                        tr("None"),
+                                                    );
+                                                });
                                         });
+                                    }
                                 });
                             }
+                            ColumnKind::Status => {
+                                match action_button(file, &progress, ui, complete, new) {
+                                    Some(RowAction::Cancel) => {
+                                        interface.popups.cancel.key = file.key();
+                                        interface.popups.cancel.show = true;
+                                    }
+                                    Some(RowAction::Restart) => {
+                                        if let Err(e) = restart_cancelled(interface, &file.key()) {
+                                            interface.popups.error.value = e.to_string();
+                                            interface.popups.error.show = true;
+                                        }
+                                    }
+                                    Some(RowAction::RestartFromZero) => {
+                                        restart_download(interface, &file.key());
+                                    }
+                                    Some(RowAction::Retry) => {
+                                        retry_download(interface, &file.key())
+                                    }
+                                    _ => {}
+                                }
+                            }
                         });
-                    });
-                    row.col(|ui| {
-                        action_button(file, ui, complete, new);
-                    });
+                    }
+                    if name_clicked || row.response().clicked() {
+                        clicked = Some(file.key());
+                    }
+                    if complete && (name_double_clicked || row.response().double_clicked()) {
+                        open_file(interface, file);
+                    }
+                    //a row dragged by its handle takes the place of the unfinished row it's dropped on
+                    if !complete && row.response().dnd_hover_payload::<FileKey>().is_some() {
+                        let rect = row.response().rect;
+                        ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("queue_drop")))
+                            .hline(rect.x_range(), rect.top(), Stroke::new(2.0, *CYAN));
+                    }
+                    if let Some(dragged) = row.response().dnd_release_payload::<FileKey>() {
+                        if !complete {
+                            dropped = Some(((*dragged).clone(), file.key()));
+                        }
+                    }
                 });
             }
+            if let Some((dragged, target)) = dropped {
+                move_in_queue(interface, &dragged, QueueMove::Onto(target));
+            }
+            //applied once every row is drawn, rows after the clicked one write back their checkbox state
+            if let Some(key) = clicked {
+                let modifiers = ctx.input(|i| i.modifiers);
+                click_select(interface, &shown, &key, modifiers);
+            }
         });
 }
 
-fn action_button(file: &File2Dl, ui: &mut Ui, complete: bool, new: bool) {
+//a plain click selects only that row, ctrl toggles it and shift selects every shown row from the anchor
+fn click_select(
+    interface: &mut DownloadManager,
+    shown: &[FileKey],
+    key: &FileKey,
+    modifiers: Modifiers,
+) {
+    let anchor = interface
+        .select
+        .anchor
+        .as_ref()
+        .and_then(|anchor| shown.iter().position(|k| k == anchor));
+    let target = shown.iter().position(|k| k == key);
+    match (anchor, target) {
+        (Some(anchor), Some(target)) if modifiers.shift => {
+            let range = &shown[anchor.min(target)..=anchor.max(target)];
+            for fdl in interface.files.iter_mut() {
+                //ctrl+shift adds the range to what was already selected
+                fdl.selected =
+                    range.iter().any(|key| fdl.file.is(key)) || (modifiers.command && fdl.selected);
+            }
+            return;
+        }
+        _ if modifiers.command => {
+            if let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(key)) {
+                fdl.selected = !fdl.selected;
+            }
+        }
+        _ => {
+            for fdl in interface.files.iter_mut() {
+                fdl.selected = fdl.file.is(key);
+            }
+        }
+    }
+    interface.select.anchor = Some(key.clone());
+}
+
+pub fn lay_completed_drawer(interface: &mut DownloadManager, ui: &mut Ui) {
+    let drawer_delay = interface.settings.drawer_delay;
+    let finished = interface
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.in_drawer(drawer_delay))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if finished.is_empty() {
+        return;
+    }
+    TopBottomPanel::bottom(Id::new("Completed"))
+        .frame(Frame::none())
+        .resizable(false)
+        .show_separator_line(true)
+        .show_inside(ui, |ui| {
+            let text = RichText::new(format!(
+                "{} {}",
+                egui_phosphor::fill::CHECK_CIRCLE,
+                trf("Completed ({})", &[&finished.len()])
+            ))
+            .color(*GREEN)
+            .strong();
+            CollapsingHeader::new(text)
+                .id_salt("completed_drawer")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                        for idx in finished.iter() {
+                            let file = interface.files[*idx].file.clone();
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut interface.files[*idx].selected, "");
+                                let text = RichText::new(egui_phosphor::regular::FOLDER_OPEN)
+                                    .size(15.0)
+                                    .color(*CYAN);
+                                let res = ui.add(Button::new(text).frame(false));
+                                if res.hovered() {
+                                    ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                                    let text = RichText::new(tr("Open file")).color(*CYAN);
+                                    res.show_tooltip_text(text);
+                                }
+                                if res.clicked() {
+                                    open_file(interface, &file);
+                                }
+                                let label = Label::new(RichText::new(&file.name_on_disk).strong())
+                                    .truncate()
+                                    .sense(Sense::click());
+                                let mut action = None;
+                                tag_chips(&file, ui);
+                                let res = ui.add(label);
+                                if res.double_clicked() {
+                                    open_file(interface, &file);
+                                }
+                                res.context_menu(|ui| {
+                                    //every menu is drawn, at most one of them was clicked
+                                    let menus = [
+                                        completed_menu(&file, ui),
+                                        tags_menu(ui),
+                                        copy_menu(ui),
+                                        source_menu(&file, ui),
+                                    ];
+                                    action = menus.into_iter().flatten().next();
+                                });
+                                match action {
+                                    Some(RowAction::Verify) => verify_file(interface, &file.key()),
+                                    Some(RowAction::Extract) => extract_archive(interface, &file),
+                                    Some(RowAction::OpenSource) => {
+                                        open_source_page(interface, &file)
+                                    }
+                                    Some(RowAction::OpenFolder) => open_folder(interface, &file),
+                                    Some(RowAction::ShowDetails) => {
+                                        show_details(interface, &file.key())
+                                    }
+                                    Some(RowAction::Copy(field)) => {
+                                        copy_rows(interface, &file, field, ui)
+                                    }
+                                    Some(RowAction::EditTags) => edit_tags(interface, &file),
+                                    Some(RowAction::EditNote) => edit_note(interface, &file),
+                                    Some(RowAction::CheckUpdate) => {
+                                        check_for_update(interface, &file.key())
+                                    }
+                                    _ => {}
+                                }
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    let size = format_size(file.size_on_disk.load(Relaxed));
+                                    ui.colored_label(*GRAY, size);
+                                });
+                            });
+                        }
+                    });
+                });
+        });
+}
+
+fn url_cell(file: &File2Dl, ui: &mut Ui) {
+    ui.vertical(|ui| {
+        ui.add_space(5.0);
+        let text = RichText::new(&file.url.link).color(*GRAY);
+        let label = Label::new(text).truncate();
+        let res = ui.add_sized((ui.available_width(), ui.available_height() - 10.0), label);
+        if res.hovered() {
+            let text = RichText::new(&file.url.link).color(*CYAN);
+            res.show_tooltip_text(text);
+        }
+    });
+}
+
+fn eta_cell(file: &File2Dl, ui: &mut Ui) {
+    let eta = file.eta().map(format_eta);
+    ui.vertical_centered(|ui| {
+        ui.add_space(5.0);
+        let text = RichText::new(eta.as_deref().unwrap_or("-")).strong();
+        ui.add(Label::new(text).truncate());
+    });
+}
+
+fn remaining_cell(file: &File2Dl, complete: bool, ui: &mut Ui) {
+    let text = match file.remaining() {
+        _ if complete => String::from("-"),
+        Some(remaining) => trf("{} left", &[&format_size(remaining)]),
+        None => String::from("?"),
+    };
+    ui.vertical_centered(|ui| {
+        ui.add_space(5.0);
+        ui.add(Label::new(RichText::new(text).color(*GRAY)).truncate());
+    });
+}
+
+fn added_cell(file: &File2Dl, ui: &mut Ui) {
+    let added = DateTime::from_timestamp(file.added_at, 0)
+        .filter(|_| file.added_at > 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| String::from("-"));
+    ui.vertical_centered(|ui| {
+        ui.add_space(5.0);
+        ui.colored_label(*GRAY, added);
+    });
+}
+
+enum RowAction {
+    Cancel,
+    Restart,
+    RestartFromZero,
+    Verify,
+    Extract,
+    CheckUpdate,
+    Retry,
+    OpenSource,
+    OpenFolder,
+    Copy(CopyField),
+    ShowDetails,
+    EditTags,
+    EditNote,
+    UpdateUrl,
+    Selection(SelectionAction),
+    Queue(QueueMove),
+    PlotSpeed,
+}
+
+//adds the file to the plot's series, next to whatever was already there
+fn plot_speed(interface: &mut DownloadManager, name_on_disk: &str) {
+    let plot = &mut interface.popups.plot;
+    if !plot.series.iter().any(|name| name == name_on_disk) {
+        plot.series.push(name_on_disk.to_string());
+    }
+    plot.show = true;
+}
+
+fn queue_menu(ui: &mut Ui) -> Option<RowAction> {
+    let mut action = None;
+    ui.separator();
+    let text = RichText::new(tr("Move up in queue")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::Queue(QueueMove::Up));
+        ui.close_menu();
+    }
+    let text = RichText::new(tr("Move down in queue"))
+        .color(*CYAN)
+        .strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::Queue(QueueMove::Down));
+        ui.close_menu();
+    }
+    action
+}
+
+fn tags_menu(ui: &mut Ui) -> Option<RowAction> {
+    let mut action = None;
+    let text = RichText::new(tr("Details")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::ShowDetails);
+        ui.close_menu();
+    }
+    let text = RichText::new(tr("Edit tags")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::EditTags);
+        ui.close_menu();
+    }
+    let text = RichText::new(tr("Edit note")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::EditNote);
+        ui.close_menu();
+    }
+    action
+}
+
+fn copy_menu(ui: &mut Ui) -> Option<RowAction> {
+    let mut action = None;
+    ui.separator();
+    for (field, label) in [
+        (CopyField::Url, "Copy url"),
+        (CopyField::Path, "Copy file path"),
+    ] {
+        let text = RichText::new(tr(label)).color(*CYAN).strong();
+        if ui.button(text).clicked() {
+            action = Some(RowAction::Copy(field));
+            ui.close_menu();
+        }
+    }
+    action
+}
+
+//the whole selection when the row is part of it, otherwise just the row
+fn copy_rows(interface: &DownloadManager, file: &File2Dl, field: CopyField, ui: &Ui) {
+    let in_selection = interface
+        .files
+        .iter()
+        .any(|f| f.selected && f.file.same_file(file));
+    if in_selection {
+        copy_selected(interface, field, ui);
+    } else {
+        ui.output_mut(|o| o.copied_text = field.of(file));
+    }
+}
+
+fn edit_note(interface: &mut DownloadManager, file: &File2Dl) {
+    interface.popups.note.key = file.key();
+    interface.popups.note.text = file.note();
+    interface.popups.note.show = true;
+}
+
+fn edit_url(interface: &mut DownloadManager, file: &File2Dl) {
+    interface.popups.update_url.key = file.key();
+    interface.popups.update_url.link = file.url.link.clone();
+    interface.popups.update_url.error = String::default();
+    interface.popups.update_url.show = true;
+}
+
+pub fn edit_tags(interface: &mut DownloadManager, file: &File2Dl) {
+    interface.popups.tags.key = file.key();
+    interface.popups.tags.text = file.tags().join(", ");
+    interface.popups.tags.show = true;
+}
+
+pub fn tag_chips(file: &File2Dl, ui: &mut Ui) {
+    for tag in file.tags() {
+        let text = RichText::new(format!(" {} ", tag))
+            .size(11.0)
+            .color(*DARK_INNER)
+            .background_color(*CYAN);
+        ui.label(text);
+    }
+}
+
+//the page the link was grabbed from, for finding it again once the link itself has expired
+fn source_menu(file: &File2Dl, ui: &mut Ui) -> Option<RowAction> {
+    if file.page_url.is_empty() {
+        return None;
+    }
+    let mut action = None;
+    ui.separator();
+    let text = RichText::new(tr("Open source page")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::OpenSource);
+        ui.close_menu();
+    }
+    let text = RichText::new(tr("Copy source page url"))
+        .color(*CYAN)
+        .strong();
+    if ui.button(text).clicked() {
+        ui.output_mut(|o| o.copied_text = file.page_url.clone());
+        ui.close_menu();
+    }
+    action
+}
+
+fn open_source_page(interface: &mut DownloadManager, file: &File2Dl) {
+    if let Err(e) = opener::open_browser(&file.page_url) {
+        report_open_error(interface, &file.page_url, e);
+    }
+}
+
+fn open_file(interface: &mut DownloadManager, file: &File2Dl) {
+    let path = file_path(file);
+    if let Err(e) = opener::open(&path) {
+        report_open_error(interface, &path, e);
+    }
+}
+
+//selects the file in the os file manager, or just opens the folder where that isn't supported
+fn open_folder(interface: &mut DownloadManager, file: &File2Dl) {
+    let path = file_path(file);
+    if let Err(e) = opener::reveal(&path) {
+        report_open_error(interface, &path, e);
+    }
+}
+
+fn report_open_error(interface: &mut DownloadManager, target: &str, e: opener::OpenError) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Couldn't open {}: {}", target, e);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text.clone(), *RED));
+    interface.popups.error.value = text;
+    interface.popups.error.show = true;
+}
+
+//context menu entries that only make sense once the file is on disk
+fn completed_menu(file: &File2Dl, ui: &mut Ui) -> Option<RowAction> {
+    let mut action = None;
+    let text = RichText::new(tr("Open containing folder"))
+        .color(*CYAN)
+        .strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::OpenFolder);
+        ui.close_menu();
+    }
+    let text = RichText::new(tr("Verify file")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::Verify);
+        ui.close_menu();
+    }
+    let text = RichText::new(tr("Check for update")).color(*CYAN).strong();
+    if ui.button(text).clicked() {
+        action = Some(RowAction::CheckUpdate);
+        ui.close_menu();
+    }
+    if is_archive(&file.name_on_disk) {
+        let text = RichText::new(tr("Extract")).color(*CYAN).strong();
+        if ui.button(text).clicked() {
+            action = Some(RowAction::Extract);
+            ui.close_menu();
+        }
+    }
+    action
+}
+
+fn action_button(
+    file: &File2Dl,
+    progress: &RowProgress,
+    ui: &mut Ui,
+    complete: bool,
+    new: bool,
+) -> Option<RowAction> {
+    let gave_up = file.failed.load(Relaxed) && !file.running.load(Relaxed);
+    if let Some(error) = progress.error.as_ref().filter(|_| gave_up && !complete) {
+        let mut action = None;
+        ui.horizontal(|ui: &mut Ui| {
+            ui.add_space(ui.available_width() / 3.8);
+            let text = RichText::new(egui_phosphor::regular::ARROW_CLOCKWISE)
+                .size(20.0)
+                .color(*RED);
+            let res = ui.add(Button::new(text).frame(false));
+            if res.hovered() {
+                ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                let text =
+                    RichText::new(format!("{}\n{}", error, tr("Click to retry"))).color(*RED);
+                res.show_tooltip_text(text);
+            }
+            if res.clicked() {
+                action = Some(RowAction::Retry);
+            }
+        });
+        return action;
+    }
+    if file.verify_failed.load(Relaxed) {
+        let mut action = None;
+        ui.horizontal(|ui: &mut Ui| {
+            ui.add_space(ui.available_width() / 3.8);
+            let text = RichText::new(egui_phosphor::regular::ARROW_CLOCKWISE)
+                .size(20.0)
+                .color(*RED);
+            let res = ui.add(Button::new(text).frame(false));
+            if res.hovered() {
+                ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                let text =
+                    RichText::new(tr("Failed verification, click to download again")).color(*RED);
+                res.show_tooltip_text(text);
+            }
+            if res.clicked() {
+                action = Some(RowAction::RestartFromZero);
+            }
+        });
+        return action;
+    }
+    if file.cancelled.load(Relaxed) {
+        let mut action = None;
+        ui.horizontal(|ui: &mut Ui| {
+            ui.add_space(ui.available_width() / 3.8);
+            let text = RichText::new(egui_phosphor::regular::ARROW_CLOCKWISE)
+                .size(20.0)
+                .color(*CYAN);
+            let res = ui.add(Button::new(text).frame(false));
+            if res.hovered() {
+                ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                let text = RichText::new(tr("Cancelled, click to restart")).color(*CYAN);
+                res.show_tooltip_text(text);
+            }
+            if res.clicked() {
+                action = Some(RowAction::Restart);
+            }
+        });
+        return action;
+    }
+    let mut action = None;
     let text = {
         let running = file.running.load(std::sync::atomic::Ordering::Relaxed);
         if !running {
@@ -284,7 +973,7 @@ fn action_button(file: &File2Dl, ui: &mut Ui, complete: bool, new: bool) {
             } else if new {
                 ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand)
             } else {
-                let text = RichText::new("File does not support resumption").color(*CYAN);
+                let text = RichText::new(tr("File does not support resumption")).color(*CYAN);
                 res.show_tooltip_text(text);
             }
         }
@@ -296,11 +985,49 @@ fn action_button(file: &File2Dl, ui: &mut Ui, complete: bool, new: bool) {
                 file.toggle_status();
             }
         }
+        if !complete {
+            let text = RichText::new(egui_phosphor::regular::X)
+                .size(20.0)
+                .color(*RED);
+            let res = ui.add(Button::new(text).frame(false));
+            if res.hovered() {
+                ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                let text = RichText::new(tr("Cancel download")).color(*CYAN);
+                res.show_tooltip_text(text);
+            }
+            if res.clicked() {
+                action = Some(RowAction::Cancel);
+            }
+        }
     });
+    action
+}
+//post processing state of a row, read once per frame
+struct RowProgress {
+    //bytes hashed so far
+    verifying: Option<usize>,
+    //thousandths unpacked so far
+    extracting: Option<usize>,
+    extract_failed: bool,
+    error: Option<String>,
+}
+
+impl RowProgress {
+    fn of(fdl: &FDl) -> Self {
+        Self {
+            verifying: fdl.verifying.as_ref().map(|hashed| hashed.load(Relaxed)),
+            extracting: fdl.extracting.as_ref().map(|done| done.load(Relaxed)),
+            extract_failed: fdl.extract_failed,
+            error: fdl.error.clone(),
+        }
+    }
 }
-fn progress_bar(file: &File2Dl, ui: &mut Ui, ctx: &Context) {
+
+fn progress_bar(file: &File2Dl, progress: &RowProgress, ui: &mut Ui, ctx: &Context) {
+    let verifying = progress.verifying;
+    let extracting = progress.extracting;
     let is_running = file.running.load(Relaxed);
-    if is_running {
+    if is_running || verifying.is_some() || extracting.is_some() {
         ctx.request_repaint();
     }
     let size = file.size_on_disk.load(std::sync::atomic::Ordering::Relaxed) as f32;
@@ -313,31 +1040,85 @@ fn progress_bar(file: &File2Dl, ui: &mut Ui, ctx: &Context) {
             ui.visuals_mut().extreme_bg_color = *GRAY;
             ui.visuals_mut().selection.bg_fill = *CYAN;
             ui.visuals_mut().override_text_color = Some(*DARK_INNER);
+            if file.url.content_length == 0 && !complete {
+                let text = match progress.error.as_ref() {
+                    Some(_) if !is_running => tr("Failed").to_string(),
+                    _ => format!("{} / ?", format_size(size as usize)),
+                };
+                let res = striped_bar(ui, text, is_running);
+                if let Some(error) = progress.error.as_ref().filter(|_| res.hovered()) {
+                    res.show_tooltip_text(RichText::new(error).color(*RED));
+                }
+                return;
+            }
             let pb = {
-                if file.url.content_length == 0 && !complete {
-                    ProgressBar::new(0.0)
+                if file.cancelled.load(Relaxed) {
+                    ProgressBar::new(if total_size > 0.0 { percentage } else { 0.0 })
+                        .desired_width(ui.available_width())
+                        .desired_height(ui.available_height() - 2.0)
+                        .text_center(tr("Cancelled").to_string())
+                } else if progress.error.is_some() && !complete {
+                    ui.visuals_mut().selection.bg_fill = *RED;
+                    let text = tr(if is_running { "Retrying" } else { "Failed" });
+                    ProgressBar::new(if total_size > 0.0 { percentage } else { 0.0 })
+                        .desired_width(ui.available_width())
+                        .desired_height(ui.available_height() - 2.0)
+                        .text_center(text.to_string())
+                } else if let Some(hashed) = verifying.filter(|_| complete) {
+                    let percentage = hashed as f32 / size.max(1.0);
+                    ProgressBar::new(percentage)
                         .desired_width(ui.available_width())
                         .desired_height(ui.available_height() - 2.0)
-                        .text_center("?".to_string())
+                        .text_center(trf("Verifying {}%", &[&((percentage * 100.0) as i32)]))
+                } else if let Some(done) = extracting.filter(|_| complete) {
+                    let percentage = done as f32 / 1000.0;
+                    ProgressBar::new(percentage)
+                        .desired_width(ui.available_width())
+                        .desired_height(ui.available_height() - 2.0)
+                        .text_center(trf("Extracting {}%", &[&((percentage * 100.0) as i32)]))
+                } else if complete && progress.extract_failed {
+                    ui.visuals_mut().selection.bg_fill = *RED;
+                    ProgressBar::new(1.0)
+                        .desired_width(ui.available_width())
+                        .desired_height(ui.available_height() - 2.0)
+                        .text_center(tr("Extraction failed").to_string())
+                } else if complete && file.verify_failed.load(Relaxed) {
+                    ui.visuals_mut().selection.bg_fill = *RED;
+                    ProgressBar::new(1.0)
+                        .desired_width(ui.available_width())
+                        .desired_height(ui.available_height() - 2.0)
+                        .text_center(tr("Failed verification").to_string())
                 } else if complete {
                     ProgressBar::new(1.0)
                         .desired_width(ui.available_width())
                         .desired_height(ui.available_height() - 2.0)
-                        .text_center("100%".to_string())
+                        .text_center(format!("100% · {}", format_size(size as usize)))
                 } else {
                     ProgressBar::new(percentage)
                         .desired_width(ui.available_width())
                         .desired_height(ui.available_height() - 2.0)
-                        .text_center(format!("{}%", (percentage * 100.0) as i32))
+                        .text_center(format!(
+                            "{}% · {} / {}",
+                            (percentage * 100.0) as i32,
+                            format_size(size as usize),
+                            format_size(file.url.content_length)
+                        ))
                 }
             };
             let res = ui.add(pb);
-            if res.hovered() {
+            if let Some(error) = progress
+                .error
+                .as_ref()
+                .filter(|_| res.hovered() && !complete)
+            {
+                res.show_tooltip_text(RichText::new(error).color(*RED));
+            } else if res.hovered() {
                 ui.set_width(ui.available_width());
-                let size_mbs = size / (1024.0 * 1024.0);
-                let total_size_mbs = file.url.content_length as f32 / (1024.0 * 1024.0);
-                let text = RichText::new(format!("{:.3}/{:.3} Mbs", size_mbs, total_size_mbs))
-                    .color(*CYAN);
+                let text = RichText::new(format!(
+                    "{} / {} bytes",
+                    size as usize, file.url.content_length
+                ))
+                .color(*CYAN);
                 res.show_tooltip_text(text);
             };
         });
@@ -345,19 +1126,230 @@ fn progress_bar(file: &File2Dl, ui: &mut Ui, ctx: &Context) {
     });
 }
 
-fn file_name(has_error: bool, name: &str, ui: &mut Ui) {
-    let text = if has_error {
-        RichText::new(name).strong().size(15.0).color(*RED)
+//downloads of unknown length have no fraction to show, the stripes scroll while bytes come in
+fn striped_bar(ui: &mut Ui, text: String, moving: bool) -> Response {
+    const STRIPE: f32 = 8.0;
+    let size = vec2(ui.available_width(), ui.available_height() - 2.0);
+    let (rect, res) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, *GRAY);
+    let offset = match moving {
+        true => (ui.input(|i| i.time) * 20.0) as f32 % (STRIPE * 2.0),
+        false => 0.0,
+    };
+    let mut x = rect.left() - rect.height() - STRIPE * 2.0 + offset;
+    while x < rect.right() {
+        let stripe = vec![
+            pos2(x, rect.bottom()),
+            pos2(x + rect.height(), rect.top()),
+            pos2(x + rect.height() + STRIPE, rect.top()),
+            pos2(x + STRIPE, rect.bottom()),
+        ];
+        painter.add(Shape::convex_polygon(stripe, *CYAN, Stroke::NONE));
+        x += STRIPE * 2.0;
+    }
+    painter.text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        text,
+        FontId::proportional(14.0),
+        *DARK_INNER,
+    );
+    res
+}
+
+fn scan_badge(scan: &ScanState, ui: &mut Ui) {
+    let (icon, color, tooltip) = match scan {
+        ScanState::Pending => (
+            egui_phosphor::regular::SHIELD,
+            *GRAY,
+            tr("Checking with VirusTotal").to_string(),
+        ),
+        ScanState::Done(ScanVerdict::Clean) => (
+            egui_phosphor::fill::SHIELD_CHECK,
+            *GREEN,
+            tr("No engine on VirusTotal flagged this file").to_string(),
+        ),
+        ScanState::Done(ScanVerdict::Flagged {
+            malicious,
+            suspicious,
+        }) => (
+            egui_phosphor::fill::SHIELD_WARNING,
+            *RED,
+            trf(
+                "Flagged on VirusTotal: {} malicious, {} suspicious",
+                &[malicious, suspicious],
+            ),
+        ),
+        ScanState::Done(ScanVerdict::Unknown) => (
+            egui_phosphor::regular::SHIELD,
+            *CYAN,
+            tr("VirusTotal has never seen this file").to_string(),
+        ),
+        ScanState::Failed(e) => (
+            egui_phosphor::regular::SHIELD_SLASH,
+            *GRAY,
+            trf("Couldn't check with VirusTotal: {}", &[e]),
+        ),
+    };
+    let res = ui.label(RichText::new(icon).size(15.0).color(color));
+    if res.hovered() {
+        res.show_tooltip_text(RichText::new(tooltip).color(color));
+    }
+}
+
+fn search_matches(interface: &DownloadManager, matcher: &Matcher, f: &FDl) -> bool {
+    let file = &f.file;
+    let query = interface.search.query.trim().to_lowercase();
+    matcher.matches(&file.name_on_disk)
+        || matcher.matches(&file.url.link)
+        || matcher.matches(&file.page_title)
+        || matcher.matches(&file.page_url)
+        || file.tags().iter().any(|tag| matcher.matches(tag))
+        || matcher.matches(&file.note())
+        //the archive index only knows substrings, so it's left out of the other modes
+        || (interface.search.mode == SearchMode::Plain
+            && interface.content_index.matches(&file_path(file), &query))
+}
+
+//the name with the parts the search matched painted over
+fn highlighted_name(name: &str, ranges: &[Range<usize>], color: Color32) -> LayoutJob {
+    let font_id = FontId::proportional(15.0);
+    let plain = TextFormat::simple(font_id.clone(), color);
+    let marked = TextFormat {
+        font_id,
+        color: *DARK_INNER,
+        background: *CYAN,
+        ..Default::default()
+    };
+    let mut job = LayoutJob::default();
+    let mut at = 0;
+    for range in ranges {
+        job.append(&name[at..range.start], 0.0, plain.clone());
+        job.append(&name[range.clone()], 0.0, marked.clone());
+        at = range.end;
+    }
+    job.append(&name[at..], 0.0, plain);
+    job
+}
+
+//in_selection is how many rows are selected when this one is among them
+fn file_name(
+    has_error: bool,
+    file: &File2Dl,
+    scan: Option<&ScanState>,
+    ranges: &[Range<usize>],
+    in_selection: usize,
+    ui: &mut Ui,
+) -> (Option<RowAction>, Response) {
+    let name = &file.name_on_disk;
+    let text: WidgetText = if !ranges.is_empty() {
+        let color = if has_error {
+            *RED
+        } else {
+            ui.visuals().strong_text_color()
+        };
+        highlighted_name(name, ranges, color).into()
+    } else if has_error {
+        RichText::new(name).strong().size(15.0).color(*RED).into()
     } else {
-        RichText::new(name).strong().size(15.0)
+        RichText::new(name).strong().size(15.0).into()
     };
 
-    let label = Label::new(text).truncate();
-    ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+    let label = Label::new(text).truncate().sense(Sense::click());
+    let mut action = None;
+    let res = ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
         ui.horizontal_centered(|ui| {
-            ui.add(label);
+            if !file.complete.load(Relaxed) {
+                let handle = RichText::new(egui_phosphor::regular::DOTS_SIX_VERTICAL).color(*GRAY);
+                let res = ui.add(Label::new(handle).selectable(false).sense(Sense::drag()));
+                if res.hovered() {
+                    ui.output_mut(|o| o.cursor_icon = CursorIcon::Grab);
+                }
+                res.dnd_set_drag_payload(file.key());
+            }
+            if let Some(scan) = scan {
+                scan_badge(scan, ui);
+            }
+            tag_chips(file, ui);
+            let res = ui.add(label);
+            res.context_menu(|ui| {
+                if in_selection > 1 {
+                    let text = RichText::new(trf("Selected ({})", &[&in_selection]))
+                        .color(*CYAN)
+                        .strong();
+                    ui.menu_button(text, |ui| {
+                        if let Some(selection) = selection_menu(ui) {
+                            action = Some(RowAction::Selection(selection));
+                        }
+                    });
+                    ui.separator();
+                }
+                if file.complete.load(Relaxed) {
+                    if let Some(completed) = completed_menu(file, ui) {
+                        action = Some(completed);
+                    }
+                }
+                let text = RichText::new(tr("Restart from zero")).color(*CYAN).strong();
+                if ui.button(text).clicked() {
+                    action = Some(RowAction::RestartFromZero);
+                    ui.close_menu();
+                }
+                if let Some(tags) = tags_menu(ui) {
+                    action = Some(tags);
+                }
+                if !file.complete.load(Relaxed) {
+                    if let Some(queue) = queue_menu(ui) {
+                        action = Some(queue);
+                    }
+                }
+                let text = RichText::new(tr("Plot speed")).color(*CYAN).strong();
+                if ui.button(text).clicked() {
+                    action = Some(RowAction::PlotSpeed);
+                    ui.close_menu();
+                }
+                let text = RichText::new(tr("Update URL")).color(*CYAN).strong();
+                if ui.button(text).clicked() {
+                    action = Some(RowAction::UpdateUrl);
+                    ui.close_menu();
+                }
+                if let Some(copy) = copy_menu(ui) {
+                    action = Some(copy);
+                }
+                if let Some(source) = source_menu(file, ui) {
+                    action = Some(source);
+                }
+            });
+            if res.hovered() && file.failed.load(Relaxed) {
+                let text =
+                    RichText::new(tr("Download failed, check the log for details")).color(*RED);
+                res.show_tooltip_text(text);
+            } else if res.hovered() {
+                let protocol = file.protocol.lock().map(|p| p.clone()).unwrap_or_default();
+                let mut lines = Vec::new();
+                if !file.page_url.is_empty() {
+                    lines.push(format!(
+                        "{}\n{}",
+                        trf("From: {}", &[&file.page_title]),
+                        file.page_url
+                    ));
+                }
+                if !protocol.is_empty() {
+                    lines.push(trf("Protocol: {}", &[&protocol]));
+                }
+                let note = file.note();
+                if !note.is_empty() {
+                    lines.push(trf("Note: {}", &[&note]));
+                }
+                if !lines.is_empty() {
+                    res.show_tooltip_text(RichText::new(lines.join("\n")).color(*CYAN));
+                }
+            }
+            res
         })
+        .inner
     });
+    (action, res.inner)
 }
 
 fn reboot_system() {
@@ -373,6 +1365,13 @@ fn reboot_system() {
             .spawn()
             .expect("Failed to reboot");
     }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("osascript")
+            .args(["-e", "tell app \"System Events\" to restart"])
+            .spawn();
+    }
 }
 
 fn shutdown_system() {
@@ -388,6 +1387,13 @@ fn shutdown_system() {
             .spawn()
             .expect("Failed to shutdown");
     }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("osascript")
+            .args(["-e", "tell app \"System Events\" to shut down"])
+            .spawn();
+    }
 }
 
 fn select_logic(interface: &mut DownloadManager) {
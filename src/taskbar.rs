@@ -0,0 +1,123 @@
+use crate::{DownloadManager, FDl};
+use egui_sfml::sfml::graphics::RenderWindow;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::{
+    Foundation::HWND,
+    System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+    },
+    UI::{
+        Shell::{ITaskbarList3, TaskbarList, TBPF_NOPROGRESS, TBPF_NORMAL},
+        WindowsAndMessaging::{FlashWindowEx, FLASHWINFO, FLASHW_TIMERNOFG, FLASHW_TRAY},
+    },
+};
+
+//progress of everything downloading on the taskbar button, only windows has one to draw on
+#[derive(Default)]
+pub struct Taskbar {
+    #[cfg(target_os = "windows")]
+    shell: Option<(HWND, ITaskbarList3)>,
+    //thousandths last handed to the shell, it's only told about changes
+    shown: Option<u64>,
+}
+
+impl Taskbar {
+    #[cfg(target_os = "windows")]
+    pub fn new(rw: &RenderWindow) -> Self {
+        let hwnd = HWND(rw.system_handle() as _);
+        //without explorer running there's no taskbar, the app just goes without
+        let list = unsafe {
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER)
+                .and_then(|list| list.HrInit().map(|_| list))
+                .ok()
+        };
+        Self {
+            shell: list.map(|list| (hwnd, list)),
+            shown: None,
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn new(_rw: &RenderWindow) -> Self {
+        Self::default()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn set_progress(&self, permille: Option<u64>) {
+        let Some((hwnd, list)) = &self.shell else {
+            return;
+        };
+        unsafe {
+            let _ = match permille {
+                Some(permille) => list
+                    .SetProgressState(*hwnd, TBPF_NORMAL)
+                    .and_then(|_| list.SetProgressValue(*hwnd, permille, 1000)),
+                None => list.SetProgressState(*hwnd, TBPF_NOPROGRESS),
+            };
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn set_progress(&self, _permille: Option<u64>) {}
+
+    //keeps flashing until the window is brought to the front
+    #[cfg(target_os = "windows")]
+    fn flash(&self) {
+        let Some((hwnd, _)) = &self.shell else {
+            return;
+        };
+        let info = FLASHWINFO {
+            cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+            hwnd: *hwnd,
+            dwFlags: FLASHW_TRAY | FLASHW_TIMERNOFG,
+            uCount: 0,
+            dwTimeout: 0,
+        };
+        unsafe {
+            let _ = FlashWindowEx(&info);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn flash(&self) {}
+}
+
+//thousandths of everything downloading, paused and finished downloads don't count
+pub fn overall_permille(files: &[FDl]) -> Option<u64> {
+    let (done, total) = files
+        .iter()
+        .filter(|fdl| {
+            let file = &fdl.file;
+            file.running.load(Relaxed)
+                && !file.complete.load(Relaxed)
+                && file.url.content_length > 0
+        })
+        .fold((0u64, 0u64), |(done, total), fdl| {
+            let file = &fdl.file;
+            (
+                done + file.size_on_disk.load(Relaxed) as u64,
+                total + file.url.content_length as u64,
+            )
+        });
+    (total > 0).then(|| (done * 1000 / total).min(1000))
+}
+
+//the bar goes away once nothing is downloading
+pub fn update_taskbar(interface: &mut DownloadManager) {
+    let permille = overall_permille(&interface.files);
+    if permille == interface.taskbar.shown {
+        return;
+    }
+    interface.taskbar.shown = permille;
+    interface.taskbar.set_progress(permille);
+}
+
+//called with how many downloads finished since the last frame
+pub fn flash_taskbar(interface: &DownloadManager, finished: usize) {
+    if finished > 0 {
+        interface.taskbar.flash();
+    }
+}
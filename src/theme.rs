@@ -0,0 +1,213 @@
+use crate::{
+    colors::{set_palette, Palette, PURPLE_PALETTE},
+    DownloadManager,
+};
+use egui_aesthetix::{
+    themes::{
+        CarlDark, NordDark, NordLight, StandardDark, StandardLight, TokyoNight, TokyoNightStorm,
+    },
+    Aesthetix,
+};
+use egui_sfml::egui::{Color32, Context, Visuals};
+use serde::{Deserialize, Serialize};
+use std::sync::{LazyLock, Mutex};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Purple,
+    StandardDark,
+    CarlDark,
+    NordDark,
+    TokyoNight,
+    TokyoNightStorm,
+    StandardLight,
+    NordLight,
+    //colors picked in the theme editor
+    Custom,
+}
+
+//built once, the colors module keeps pointing into these
+static PALETTES: LazyLock<Vec<Palette>> =
+    LazyLock::new(|| Theme::ALL.iter().map(Theme::build_palette).collect());
+
+impl Theme {
+    pub const ALL: [Self; 9] = [
+        Self::Purple,
+        Self::StandardDark,
+        Self::CarlDark,
+        Self::NordDark,
+        Self::TokyoNight,
+        Self::TokyoNightStorm,
+        Self::StandardLight,
+        Self::NordLight,
+        Self::Custom,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Purple => "Purple",
+            Self::StandardDark => "Standard dark",
+            Self::CarlDark => "Carl dark",
+            Self::NordDark => "Nord dark",
+            Self::TokyoNight => "Tokyo Night",
+            Self::TokyoNightStorm => "Tokyo Night Storm",
+            Self::StandardLight => "Standard light",
+            Self::NordLight => "Nord light",
+            Self::Custom => "Custom",
+        }
+    }
+
+    fn aesthetix(&self) -> Option<&'static dyn Aesthetix> {
+        match self {
+            Self::Purple | Self::Custom => None,
+            Self::StandardDark => Some(&StandardDark),
+            Self::CarlDark => Some(&CarlDark),
+            Self::NordDark => Some(&NordDark),
+            Self::TokyoNight => Some(&TokyoNight),
+            Self::TokyoNightStorm => Some(&TokyoNightStorm),
+            Self::StandardLight => Some(&StandardLight),
+            Self::NordLight => Some(&NordLight),
+        }
+    }
+
+    //egui_aesthetix builds against an older egui, so its colors are carried over by value
+    fn build_palette(&self) -> Palette {
+        let Some(theme) = self.aesthetix() else {
+            return PURPLE_PALETTE;
+        };
+        let color = |color: [u8; 4]| {
+            Color32::from_rgba_premultiplied(color[0], color[1], color[2], color[3])
+        };
+        Palette {
+            cyan: color(theme.primary_accent_color_visuals().to_array()),
+            purple: color(theme.bg_primary_color_visuals().to_array()),
+            darker_purple: color(theme.bg_secondary_color_visuals().to_array()),
+            dark_inner: color(theme.bg_triage_color_visuals().to_array()),
+            gray: PURPLE_PALETTE.gray,
+            green: color(theme.fg_success_text_color_visuals().to_array()),
+            red: color(theme.fg_error_text_color_visuals().to_array()),
+        }
+    }
+
+    //the custom theme's colors live in the settings, see CustomColors
+    pub fn palette(&self) -> &'static Palette {
+        let idx = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        &PALETTES[idx]
+    }
+
+    //the purple scheme keeps egui's own dark look, the others tint it with their palette
+    fn visuals(&self, palette: &Palette) -> Visuals {
+        if *self == Self::Purple {
+            return Visuals::dark();
+        }
+        let mut visuals = if is_light(palette) {
+            Visuals::light()
+        } else {
+            Visuals::dark()
+        };
+        visuals.panel_fill = palette.purple;
+        visuals.window_fill = palette.darker_purple;
+        visuals.faint_bg_color = palette.dark_inner;
+        visuals.extreme_bg_color = palette.dark_inner;
+        visuals.hyperlink_color = palette.cyan;
+        visuals.selection.bg_fill = palette.cyan.linear_multiply(0.4);
+        visuals.selection.stroke.color = palette.cyan;
+        visuals.error_fg_color = palette.red;
+        visuals
+    }
+}
+
+//perceived brightness of the background decides between egui's light and dark widgets
+fn is_light(palette: &Palette) -> bool {
+    let [r, g, b, _] = palette.purple.to_array();
+    (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000 > 128
+}
+
+//hex so the settings file stays editable by hand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomColors {
+    pub accent: String,
+    pub background: String,
+    pub panels: String,
+    pub inner: String,
+    pub muted: String,
+    pub success: String,
+    pub error: String,
+}
+
+impl Default for CustomColors {
+    fn default() -> Self {
+        Self::from(&PURPLE_PALETTE)
+    }
+}
+
+impl From<&Palette> for CustomColors {
+    fn from(palette: &Palette) -> Self {
+        Self {
+            accent: palette.cyan.to_hex(),
+            background: palette.purple.to_hex(),
+            panels: palette.darker_purple.to_hex(),
+            inner: palette.dark_inner.to_hex(),
+            muted: palette.gray.to_hex(),
+            success: palette.green.to_hex(),
+            error: palette.red.to_hex(),
+        }
+    }
+}
+
+impl CustomColors {
+    //a color that doesn't parse falls back to the purple scheme's
+    pub fn palette(&self) -> Palette {
+        let parse =
+            |hex: &str, fallback: Color32| Color32::from_hex(hex.trim()).unwrap_or(fallback);
+        Palette {
+            cyan: parse(&self.accent, PURPLE_PALETTE.cyan),
+            purple: parse(&self.background, PURPLE_PALETTE.purple),
+            darker_purple: parse(&self.panels, PURPLE_PALETTE.darker_purple),
+            dark_inner: parse(&self.inner, PURPLE_PALETTE.dark_inner),
+            gray: parse(&self.muted, PURPLE_PALETTE.gray),
+            green: parse(&self.success, PURPLE_PALETTE.green),
+            red: parse(&self.error, PURPLE_PALETTE.red),
+        }
+    }
+}
+
+//the colors module only holds on to palettes that live for the whole run, so every distinct
+//custom one that gets applied is kept, that's a handful at most
+static KEPT: Mutex<Vec<&'static Palette>> = Mutex::new(Vec::new());
+
+fn keep(palette: Palette) -> &'static Palette {
+    let mut kept = KEPT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(found) = kept.iter().find(|kept| ***kept == palette) {
+        return found;
+    }
+    let leaked = Box::leak(Box::new(palette));
+    kept.push(leaked);
+    leaked
+}
+
+#[derive(Default)]
+pub struct ThemeState {
+    //what was last handed to egui, so it's only redone after a switch
+    applied: Option<(Theme, Palette)>,
+}
+
+pub fn apply_theme(interface: &mut DownloadManager, ctx: &Context) {
+    let theme = interface.settings.theme;
+    let palette = match theme {
+        Theme::Custom => interface.settings.custom_colors.palette(),
+        _ => *theme.palette(),
+    };
+    if interface.theme.applied == Some((theme, palette)) {
+        return;
+    }
+    let palette = match theme {
+        Theme::Custom => keep(palette),
+        _ => theme.palette(),
+    };
+    set_palette(palette);
+    ctx.set_visuals(theme.visuals(palette));
+    interface.theme.applied = Some((theme, *palette));
+}
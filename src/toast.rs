@@ -0,0 +1,73 @@
+use crate::{colors::DARKER_PURPLE, DownloadManager};
+use egui_sfml::egui::{Align2, Area, Color32, Context, Frame, Id, Order, RichText, Sense, Stroke};
+use std::time::{Duration, Instant};
+
+const LIFETIME: Duration = Duration::from_secs(4);
+const FADE: Duration = Duration::from_millis(500);
+//older ones make room when more pile up
+const MAX_SHOWN: usize = 5;
+
+struct Toast {
+    text: String,
+    color: Color32,
+    shown_at: Instant,
+}
+
+//short notices in the bottom right corner, they go away on their own or when clicked
+#[derive(Default)]
+pub struct Toasts {
+    items: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, text: impl Into<String>, color: Color32) {
+        self.items.push(Toast {
+            text: text.into(),
+            color,
+            shown_at: Instant::now(),
+        });
+        if self.items.len() > MAX_SHOWN {
+            self.items.remove(0);
+        }
+    }
+}
+
+pub fn show_toasts(interface: &mut DownloadManager, ctx: &Context) {
+    let toasts = &mut interface.toasts;
+    toasts
+        .items
+        .retain(|toast| toast.shown_at.elapsed() < LIFETIME);
+    if toasts.items.is_empty() {
+        return;
+    }
+    let mut dismissed = None;
+    //above the status bar, newest at the bottom
+    Area::new(Id::new("toasts"))
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_BOTTOM, [-10.0, -50.0])
+        .show(ctx, |ui| {
+            for (idx, toast) in toasts.items.iter().enumerate() {
+                let left = LIFETIME.saturating_sub(toast.shown_at.elapsed());
+                let opacity = (left.as_secs_f32() / FADE.as_secs_f32()).min(1.0);
+                let color = toast.color.gamma_multiply(opacity);
+                let res = Frame::none()
+                    .fill(DARKER_PURPLE.gamma_multiply(opacity))
+                    .stroke(Stroke::new(1.0, color))
+                    .rounding(5.0)
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        ui.set_max_width(300.0);
+                        ui.label(RichText::new(&toast.text).color(color));
+                    })
+                    .response
+                    .interact(Sense::click());
+                if res.clicked() {
+                    dismissed = Some(idx);
+                }
+                ui.add_space(5.0);
+            }
+        });
+    if let Some(idx) = dismissed {
+        toasts.items.remove(idx);
+    }
+}
@@ -1,10 +1,28 @@
-use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::{
+    sync::{
+        atomic::Ordering::Relaxed,
+        mpsc::{self, Receiver, SyncSender},
+    },
+    time::{Duration, Instant},
+};
 
-use tray_item::{IconSource, TrayItem};
+#[cfg(target_os = "linux")]
+use ksni::{menu::StandardItem, Icon, MenuItem, ToolTip, TrayService};
+#[cfg(target_os = "linux")]
+use std::sync::LazyLock;
+#[cfg(not(target_os = "linux"))]
+use tray_item::{IconSource, TIError, TrayItem};
 
-use crate::DownloadManager;
+use crate::{
+    batch::{pause_all, resume_all},
+    close::{hide_window, quit},
+    dl::eta::format_size,
+    i18n::{tr, trf},
+    taskbar::overall_permille,
+    DownloadManager,
+};
 
-#[derive(PartialEq, Eq, Default, Debug)]
+#[derive(PartialEq, Eq, Default, Debug, Clone, Copy)]
 pub enum Message {
     #[default]
     None,
@@ -12,91 +30,268 @@ pub enum Message {
     Quit,
     Hide,
     AddDl,
+    PauseAll,
+    ResumeAll,
 }
+
+const MENU: [(&str, Message); 6] = [
+    ("Add Download", Message::AddDl),
+    ("Pause all", Message::PauseAll),
+    ("Resume all", Message::ResumeAll),
+    ("Show", Message::Show),
+    ("Hide", Message::Hide),
+    ("Quit", Message::Quit),
+];
+
 pub struct Tray {
     pub message: Message,
-    pub tray: TrayItem,
+    #[cfg(target_os = "linux")]
+    notifier: ksni::Handle<StatusNotifier>,
+    #[cfg(not(target_os = "linux"))]
+    tray: TrayItem,
     pub channel: (SyncSender<Message>, Receiver<Message>),
+    //total speed and how many downloads are active, shown in the icon's tooltip
+    status: String,
+    //percent drawn on the icon, none while it's the plain one
+    drawn: Option<u64>,
+    last_update: Instant,
 }
 
-impl Default for Tray {
-    fn default() -> Self {
+impl Tray {
+    //fails without a tray to show it in, the daemon never asks for one
+    pub fn new() -> Result<Self, String> {
         let channel = mpsc::sync_channel::<Message>(2);
-        let mut tray = construct_tray();
-        let add_dl_tx = channel.0.clone();
-        tray.add_menu_item("Add Download", move || {
-            add_dl_tx.send(Message::AddDl).unwrap();
-        })
-        .unwrap();
-        let show_tx: SyncSender<Message> = channel.0.clone();
-        tray.add_menu_item("Show", move || {
-            show_tx.send(Message::Show).unwrap();
-        })
-        .unwrap();
-
-        let hide_tx: SyncSender<Message> = channel.0.clone();
-        tray.add_menu_item("Hide", move || {
-            hide_tx.send(Message::Hide).unwrap();
-        })
-        .unwrap();
-
-        let quit_tx = channel.0.clone();
-        tray.add_menu_item("Exit", move || {
-            quit_tx.send(Message::Quit).unwrap();
-        })
-        .unwrap();
+        #[cfg(target_os = "linux")]
+        let notifier = {
+            if !has_session_bus() {
+                return Err(String::from("there's no D-Bus session to show it in"));
+            }
+            let service = TrayService::new(StatusNotifier {
+                tx: channel.0.clone(),
+                status: String::from(tr("No active downloads")),
+                icon: icon_data(None),
+            });
+            let notifier = service.handle();
+            //ksni's own spawn unwraps the error on the thread it starts
+            std::thread::spawn(move || {
+                let _ = service.run();
+            });
+            notifier
+        };
+        #[cfg(not(target_os = "linux"))]
+        let tray = {
+            let mut tray = construct_tray().map_err(|e| format!("{:?}", e))?;
+            for (label, message) in MENU {
+                let tx = channel.0.clone();
+                tray.add_menu_item(tr(label), move || {
+                    let _ = tx.send(message);
+                })
+                .map_err(|e| format!("{:?}", e))?;
+            }
+            tray
+        };
 
-        Self {
+        Ok(Self {
+            #[cfg(target_os = "linux")]
+            notifier,
+            #[cfg(not(target_os = "linux"))]
             tray,
             message: Message::default(),
             channel,
+            status: String::new(),
+            drawn: None,
+            last_update: Instant::now(),
+        })
+    }
+
+    fn set_status(&mut self, status: String) {
+        #[cfg(target_os = "linux")]
+        self.notifier
+            .update(|notifier| notifier.status.clone_from(&status));
+        #[cfg(target_os = "windows")]
+        {
+            let _ = self.tray.inner_mut().set_tooltip(&status);
+        }
+        //the macos menu bar item has no tooltip, the status is left out there
+        self.status = status;
+    }
+}
+
+//the status notifier the desktop draws, tray-item has no way to change its tooltip
+#[cfg(target_os = "linux")]
+struct StatusNotifier {
+    tx: SyncSender<Message>,
+    status: String,
+    icon: Option<Icon>,
+}
+
+#[cfg(target_os = "linux")]
+impl ksni::Tray for StatusNotifier {
+    fn id(&self) -> String {
+        String::from("rusty-dl-manager")
+    }
+
+    fn title(&self) -> String {
+        String::from("Rusty Dl Manager")
+    }
+
+    fn icon_pixmap(&self) -> Vec<Icon> {
+        self.icon.iter().cloned().collect()
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        ToolTip {
+            title: self.title(),
+            description: self.status.clone(),
+            ..Default::default()
         }
     }
+
+    //a left click brings the window back
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let _ = self.tx.send(Message::Show);
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        MENU.iter()
+            .map(|&(label, message)| {
+                StandardItem {
+                    label: tr(label).to_string(),
+                    activate: Box::new(move |notifier: &mut Self| {
+                        let _ = notifier.tx.send(message);
+                    }),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect()
+    }
+}
+
+//ksni panics on its own thread when there's no session bus to register with
+#[cfg(target_os = "linux")]
+fn has_session_bus() -> bool {
+    std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+        || std::env::var_os("XDG_RUNTIME_DIR")
+            .is_some_and(|dir| std::path::Path::new(&dir).join("bus").exists())
 }
 
 pub fn handle_tray_events(interface: &mut DownloadManager) {
-    if let Ok(msg) = interface.tray_menu.channel.1.try_recv() {
+    let Some(tray) = interface.tray_menu.as_ref() else {
+        return;
+    };
+    if let Ok(msg) = tray.channel.1.try_recv() {
         match msg {
             Message::AddDl => {
                 interface.show_window = true;
                 interface.popups.download.show = true;
             }
+            Message::PauseAll => pause_all(interface),
+            Message::ResumeAll => resume_all(interface),
             Message::Show => interface.show_window = true,
-            Message::Hide => {
-                interface.show_window = false;
-                interface.popups.download.show = false;
-                interface.popups.confirm.show = false;
-                interface.popups.error.show = false;
-                interface.popups.plot.show = false;
-                interface.popups.speed.show = false;
-            }
-            Message::Quit => std::process::exit(0),
+            Message::Hide => hide_window(interface),
+            Message::Quit => quit(interface),
             _ => {}
         }
     }
 }
 
-fn construct_tray() -> TrayItem {
-    #[cfg(target_os = "linux")]
-    {
-        let (icon_rgba, icon_width, icon_height) = {
-            let img_bytes = include_bytes!("../icon.png").to_vec();
-            let image = image::load_from_memory(&img_bytes)
-                .expect("Failed loading img from mem")
-                .into_rgba8();
-            let (width, height) = image.dimensions();
-            let rgba = image.into_raw();
-            (rgba, width, height)
-        };
-        let icon = IconSource::Data {
-            data: icon_rgba,
-            height: icon_height as i32,
-            width: icon_width as i32,
-        };
-        TrayItem::new("File Download Manager", icon).unwrap()
+//refreshed once a second, the tray doesn't need every frame
+pub fn update_tray(interface: &mut DownloadManager) {
+    let Some(tray) = interface.tray_menu.as_mut() else {
+        return;
+    };
+    if tray.last_update.elapsed() < Duration::from_secs(1) {
+        return;
     }
+    tray.last_update = Instant::now();
+    let active = interface
+        .files
+        .iter()
+        .filter(|fdl| fdl.file.running.load(Relaxed) && !fdl.file.complete.load(Relaxed))
+        .count();
+    let speed: usize = interface
+        .files
+        .iter()
+        .map(|fdl| fdl.file.bytes_per_sec.load(Relaxed))
+        .sum();
+    let status = if active == 0 {
+        String::from(tr("No active downloads"))
+    } else {
+        trf("{} active, {}/s", &[&active, &format_size(speed)])
+    };
+    if status != tray.status {
+        tray.set_status(status);
+    }
+    let percent = if interface.settings.tray_progress {
+        overall_permille(&interface.files).map(|permille| permille / 10)
+    } else {
+        None
+    };
+    if percent != tray.drawn {
+        tray.drawn = percent;
+        #[cfg(target_os = "linux")]
+        tray.notifier
+            .update(|notifier| notifier.icon = icon_data(percent));
+    }
+}
+
+//decoded once, the pie is drawn over a copy of it
+#[cfg(target_os = "linux")]
+static ICON: LazyLock<Option<image::RgbaImage>> = LazyLock::new(|| {
+    image::load_from_memory(include_bytes!("../icon.png"))
+        .ok()
+        .map(|image| image.into_rgba8())
+});
+
+//the bundled icon, with a pie in the bottom right quarter that fills up clockwise
+#[cfg(target_os = "linux")]
+fn icon_data(percent: Option<u64>) -> Option<Icon> {
+    let image = ICON.as_ref()?;
+    let (width, height) = image.dimensions();
+    let mut rgba = image.as_raw().clone();
+    if let Some(percent) = percent {
+        let radius = width.min(height) as f32 / 4.0;
+        let (cx, cy) = (width as f32 - radius, height as f32 - radius);
+        let filled = percent as f32 / 100.0 * std::f32::consts::TAU;
+        for y in 0..height {
+            for x in 0..width {
+                let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                if dx.hypot(dy) > radius {
+                    continue;
+                }
+                //angle from twelve o'clock
+                let angle = dx.atan2(-dy).rem_euclid(std::f32::consts::TAU);
+                let color = if angle <= filled {
+                    crate::colors::GREEN.to_array()
+                } else {
+                    crate::colors::DARKER_PURPLE.to_array()
+                };
+                let idx = ((y * width + x) * 4) as usize;
+                rgba[idx..idx + 4].copy_from_slice(&color);
+            }
+        }
+    }
+    //the status notifier spec wants argb
+    for pixel in rgba.chunks_exact_mut(4) {
+        pixel.rotate_right(1);
+    }
+    Some(Icon {
+        width: width as i32,
+        height: height as i32,
+        data: rgba,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn construct_tray() -> Result<TrayItem, TIError> {
     #[cfg(target_os = "windows")]
     {
-        TrayItem::new("File Download Manager", IconSource::Resource("icon")).unwrap()
+        TrayItem::new("File Download Manager", IconSource::Resource("icon"))
+    }
+    //the menu bar item shows its title when no bundled icon is named
+    #[cfg(target_os = "macos")]
+    {
+        TrayItem::new("Rusty Dl", IconSource::Resource(""))
     }
 }
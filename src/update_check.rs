@@ -0,0 +1,108 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    dl::{
+        file2dl::FileKey,
+        url::{remote_changes, Url},
+    },
+    popups::ask_confirm,
+    DownloadManager,
+};
+use chrono::Local;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+type Probed = (FileKey, Result<Url, String>);
+
+pub struct UpdateChecks {
+    pub channel: (Sender<Probed>, Receiver<Probed>),
+}
+
+impl Default for UpdateChecks {
+    fn default() -> Self {
+        Self { channel: channel() }
+    }
+}
+
+//probes the url again without touching the file, the answer is handled in check_updates
+pub fn check_for_update(interface: &mut DownloadManager, key: &FileKey) {
+    let Some(fdl) = interface.files.iter().find(|f| f.file.is(key)) else {
+        return;
+    };
+    let link = fdl.file.url.link.clone();
+    let tls = fdl.file.tls.clone();
+    let browser = fdl.file.browser.clone();
+    let key = key.clone();
+    let tx = interface.update_checks.channel.0.clone();
+    interface.runtime.spawn(async move {
+        let probed = Url::with_browser(&link, &tls, &browser)
+            .await
+            .map_err(|e| e.to_string());
+        let _ = tx.send((key, probed));
+    });
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Checking for update: {}", &fdl.file.name_on_disk);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+}
+
+pub fn check_updates(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    while let Ok((key, probed)) = interface.update_checks.channel.1.try_recv() {
+        let name_on_disk = &key.name_on_disk;
+        let probed = match probed {
+            Ok(probed) => probed,
+            Err(e) => {
+                let text = format!("Couldn't check {} for updates: {}", name_on_disk, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text.clone(), *RED));
+                interface.popups.error.value = text;
+                interface.popups.error.show = true;
+                continue;
+            }
+        };
+        let Some(fdl) = interface.files.iter().find(|f| f.file.is(&key)) else {
+            continue;
+        };
+        let changes = remote_changes(&fdl.file.url, &probed);
+        if changes.is_empty() {
+            let text = format!("Up to date: {}", name_on_disk);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *GREEN));
+            continue;
+        }
+        let text = format!(
+            "The remote file changed ({}), re-download {}?",
+            changes.join(", "),
+            name_on_disk
+        );
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text.clone(), *CYAN));
+        //the restart queue swaps in the fresh url info and starts over once nothing holds the file
+        ask_confirm(
+            interface,
+            None,
+            *CYAN,
+            text,
+            Box::new(move || {
+                let key = key.clone();
+                let probed = probed.clone();
+                Box::new(move |app: &mut DownloadManager| {
+                    app.restarts.pending.push((key, probed));
+                })
+            }),
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    dl::{
+        checksum::{fetch_published_sha256, hash_file, HashAlgo},
+        file2dl::{File2Dl, FileKey},
+    },
+    DownloadManager,
+};
+use chrono::Local;
+use std::{
+    fs::metadata,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+};
+use tokio::task::JoinError;
+
+#[derive(Debug)]
+pub enum VerifyResult {
+    Match,
+    Mismatch { expected: String, actual: String },
+    SizeMatch,
+    SizeMismatch { expected: usize, actual: usize },
+    NoChecksum,
+    Failed(String),
+}
+
+type Outcome = (FileKey, VerifyResult);
+
+pub struct Verification {
+    pub channel: (Sender<Outcome>, Receiver<Outcome>),
+}
+
+impl Default for Verification {
+    fn default() -> Self {
+        Self { channel: channel() }
+    }
+}
+
+pub fn is_disk_image(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    name.ends_with(".iso") || name.ends_with(".img")
+}
+
+fn compare(expected: String, hashed: Result<std::io::Result<String>, JoinError>) -> VerifyResult {
+    match hashed {
+        Ok(Ok(actual)) if actual == expected => VerifyResult::Match,
+        Ok(Ok(actual)) => VerifyResult::Mismatch { expected, actual },
+        Ok(Err(e)) => VerifyResult::Failed(e.to_string()),
+        Err(e) => VerifyResult::Failed(e.to_string()),
+    }
+}
+
+//logs the start and hands out the counter the table reads the progress from
+fn start_verifying(interface: &mut DownloadManager, file: &File2Dl) -> Arc<AtomicUsize> {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    let text = format!("Verifying: {}", file.name_on_disk);
+    interface
+        .popups
+        .log
+        .logs
+        .push((formatted_time, text, *CYAN));
+    let hashed = Arc::new(AtomicUsize::new(0));
+    if let Some(fdl) = interface.files.iter_mut().find(|f| f.file.same_file(file)) {
+        fdl.verifying = Some(hashed.clone());
+    }
+    hashed
+}
+
+//manual check from the context menu, the size is all there is to compare when no checksum is known
+pub fn verify_file(interface: &mut DownloadManager, key: &FileKey) {
+    let Some(fdl) = interface.files.iter().find(|f| f.file.is(key)) else {
+        return;
+    };
+    if fdl.verifying.is_some() {
+        return;
+    }
+    let file = fdl.file.clone();
+    if HashAlgo::detect(&file.checksum).is_some() {
+        verify_checksum(interface, &file);
+    } else if is_disk_image(&file.name_on_disk) {
+        verify_disk_image(interface, &file);
+    } else {
+        verify_size(interface, &file);
+    }
+}
+
+fn verify_size(interface: &mut DownloadManager, file: &File2Dl) {
+    let expected = file.url.content_length;
+    let path = Path::new(&file.dl_dir).join(&file.name_on_disk);
+    let result = match metadata(path) {
+        Ok(_) if expected == 0 => {
+            VerifyResult::Failed(String::from("the server never reported a size"))
+        }
+        Ok(m) if m.len() as usize == expected => VerifyResult::SizeMatch,
+        Ok(m) => VerifyResult::SizeMismatch {
+            expected,
+            actual: m.len() as usize,
+        },
+        Err(e) => VerifyResult::Failed(e.to_string()),
+    };
+    interface
+        .verification
+        .channel
+        .0
+        .send((file.key(), result))
+        .unwrap();
+}
+
+//hashes the file with whichever algorithm matches the checksum entered when it was added
+pub fn verify_checksum(interface: &mut DownloadManager, file: &File2Dl) {
+    let hashed = start_verifying(interface, file);
+    let tx = interface.verification.channel.0.clone();
+    let expected = file.checksum.trim().to_lowercase();
+    let key = file.key();
+    let path = Path::new(&file.dl_dir).join(&file.name_on_disk);
+    interface.runtime.spawn(async move {
+        let result = match HashAlgo::detect(&expected) {
+            Some(algo) => compare(
+                expected,
+                tokio::task::spawn_blocking(move || hash_file(&path, algo, &hashed)).await,
+            ),
+            None => VerifyResult::Failed(String::from("unrecognized checksum")),
+        };
+        tx.send((key, result)).unwrap();
+    });
+}
+
+//fetches the published SHA256SUMS next to the image and compares it against the file on disk
+pub fn verify_disk_image(interface: &mut DownloadManager, file: &File2Dl) {
+    let hashed = start_verifying(interface, file);
+    let tx = interface.verification.channel.0.clone();
+    let link = file.url.link.clone();
+    let filename = file.url.filename.clone();
+    let tls = file.tls.clone();
+    let key = file.key();
+    let path = Path::new(&file.dl_dir).join(&file.name_on_disk);
+    interface.runtime.spawn(async move {
+        let result = match fetch_published_sha256(&link, &filename, &tls).await {
+            Ok(Some(expected)) => compare(
+                expected,
+                tokio::task::spawn_blocking(move || hash_file(&path, HashAlgo::Sha256, &hashed))
+                    .await,
+            ),
+            Ok(None) => VerifyResult::NoChecksum,
+            Err(e) => VerifyResult::Failed(e.to_string()),
+        };
+        tx.send((key, result)).unwrap();
+    });
+}
+
+//kept in the metadata so a corrupt file is still flagged after a restart
+fn mark_verified(interface: &mut DownloadManager, key: &FileKey, failed: bool) {
+    let Some(fdl) = interface.files.iter().find(|f| f.file.is(key)) else {
+        return;
+    };
+    fdl.file.verify_failed.store(failed, Relaxed);
+    if let Err(e) = fdl.save_metadata() {
+        let now = Local::now();
+        let formatted_time = now.format("%H:%M:%S").to_string();
+        let text = format!(
+            "Couldn't save the verification of {}: {}",
+            key.name_on_disk, e
+        );
+        interface.popups.log.logs.push((formatted_time, text, *RED));
+    }
+}
+
+pub fn check_verifications(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    while let Ok((key, result)) = interface.verification.channel.1.try_recv() {
+        let name = &key.name_on_disk;
+        if let Some(fdl) = interface.files.iter_mut().find(|f| f.file.is(&key)) {
+            fdl.verifying = None;
+        }
+        match result {
+            VerifyResult::Match => {
+                mark_verified(interface, &key, false);
+                let text = format!("Checksum verified: {}", name);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *GREEN));
+            }
+            VerifyResult::SizeMatch => {
+                mark_verified(interface, &key, false);
+                let text = format!("Size matches the server's: {}", name);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *GREEN));
+            }
+            VerifyResult::SizeMismatch { expected, actual } => {
+                mark_verified(interface, &key, true);
+                let text = format!(
+                    "Size mismatch for {}: expected {} bytes, found {}",
+                    name, expected, actual
+                );
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text.clone(), *RED));
+                interface.popups.log.has_error = true;
+                interface.popups.error.value = text;
+                interface.popups.error.show = true;
+            }
+            VerifyResult::NoChecksum => {
+                let text = format!("No published checksum found for: {}", name);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *CYAN));
+            }
+            VerifyResult::Mismatch { expected, actual } => {
+                mark_verified(interface, &key, true);
+                let text = format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    name, expected, actual
+                );
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text.clone(), *RED));
+                interface.popups.log.has_error = true;
+                interface.popups.error.value = text;
+                interface.popups.error.show = true;
+            }
+            VerifyResult::Failed(e) => {
+                let text = format!("Couldn't verify {}: {}", name, e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *RED));
+                interface.popups.log.has_error = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    colors::{CYAN, GREEN, RED},
+    dl::{file2dl::File2Dl, tls::TlsOptions},
+    download_mechanism::{exceeds_size_limit, SizeGuard},
+    DownloadManager, FDl,
+};
+use chrono::Local;
+use regex::Regex;
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, read_dir, read_to_string, rename},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+const PROCESSED_DIR: &str = "processed";
+const TRIGGER_EXTS: [&str; 4] = ["txt", "torrent", "metalink", "meta4"];
+const METALINK_FILE_RE: &str = r#"(?s)<file\b[^>]*>(.*?)</file>"#;
+const METALINK_URL_RE: &str = r#"<url([^>]*)>\s*([^<\s]+)\s*</url>"#;
+const METALINK_ATTR_RE: &str = r#"(priority|preference)\s*=\s*["'](\d+)["']"#;
+
+pub struct WatchFolder {
+    last_scan: Instant,
+    //unsupported files stay where they were dropped, this keeps them from being reported on every scan
+    skipped: HashSet<PathBuf>,
+    file_channel: (Sender<File2Dl>, Receiver<File2Dl>),
+    error_channel: (Sender<String>, Receiver<String>),
+}
+
+impl Default for WatchFolder {
+    fn default() -> Self {
+        Self {
+            last_scan: Instant::now(),
+            skipped: HashSet::new(),
+            file_channel: channel(),
+            error_channel: channel(),
+        }
+    }
+}
+
+pub fn check_watch_folder(interface: &mut DownloadManager) {
+    let now = Local::now();
+    let formatted_time = now.format("%H:%M:%S").to_string();
+    while let Ok(mut file) = interface.watch_folder.file_channel.1.try_recv() {
+        if exceeds_size_limit(interface.settings.max_size_gb, file.url.content_length) {
+            let text = format!(
+                "{} is over the {} GB limit",
+                &file.url.link, interface.settings.max_size_gb
+            );
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+            if interface.settings.size_guard == SizeGuard::Block {
+                continue;
+            }
+        }
+        let text = format!("Added from watch folder: {}", &file.url.link);
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, *GREEN));
+        interface.claim_name(&mut file);
+        file.toggle_status();
+        interface.files.push(FDl {
+            file,
+            ..Default::default()
+        });
+    }
+    while let Ok(err) = interface.watch_folder.error_channel.1.try_recv() {
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), err, *RED));
+        interface.popups.log.has_error = true;
+    }
+
+    let watch_dir = interface.settings.watch_dir.clone();
+    if watch_dir.is_empty() || interface.watch_folder.last_scan.elapsed() < Duration::from_secs(3) {
+        return;
+    }
+    interface.watch_folder.last_scan = Instant::now();
+    let triggers = match get_trigger_files(&watch_dir) {
+        Ok(triggers) => triggers,
+        Err(e) => {
+            let text = format!("Couldn't read watch folder {}: {}", &watch_dir, e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+            return;
+        }
+    };
+    for path in triggers {
+        if is_torrent(&path) {
+            if interface.watch_folder.skipped.insert(path.clone()) {
+                let text = format!("Torrent files are not supported: {}", path.display());
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *RED));
+            }
+            continue;
+        }
+        let links = match parse_trigger_file(&path) {
+            Ok(links) => links,
+            Err(e) => {
+                let text = format!("Couldn't parse {}: {}", path.display(), e);
+                interface
+                    .popups
+                    .log
+                    .logs
+                    .push((formatted_time.clone(), text, *RED));
+                continue;
+            }
+        };
+        //the trigger file is moved before anything is queued so it never gets picked up twice
+        if let Err(e) = move_to_processed(&path, &watch_dir) {
+            let text = format!("Couldn't move {} to processed: {}", path.display(), e);
+            interface
+                .popups
+                .log
+                .logs
+                .push((formatted_time.clone(), text, *RED));
+            continue;
+        }
+        let text = format!("Found {} link(s) in {}", links.len(), path.display());
+        interface
+            .popups
+            .log
+            .logs
+            .push((formatted_time.clone(), text, *CYAN));
+        for link in links {
+            let dl_dir = interface.settings.dl_dir.clone();
+            let tls = TlsOptions {
+                ca_cert: interface.settings.ca_cert.clone(),
+                accept_invalid_certs: false,
+            };
+            let file_tx = interface.watch_folder.file_channel.0.clone();
+            let error_tx = interface.watch_folder.error_channel.0.clone();
+            interface.runtime.spawn(async move {
+                match File2Dl::new(&link, &dl_dir, tls).await {
+                    Ok(file) => file_tx.send(file).unwrap(),
+                    Err(e) => {
+                        let error = format!("{}: {}", &link, e);
+                        error_tx.send(error).unwrap();
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn get_trigger_files(dir: &str) -> Result<Vec<PathBuf>, std::io::Error> {
+    let collection = read_dir(dir)?
+        .filter_map(|entry| {
+            entry.ok().and_then(|e| {
+                let path = e.path();
+                let ext = path
+                    .extension()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_lowercase();
+                if path.is_file() && TRIGGER_EXTS.contains(&ext.as_str()) {
+                    Some(path)
+                } else {
+                    None
+                }
+            })
+        })
+        .collect::<Vec<PathBuf>>();
+    Ok(collection)
+}
+
+fn is_torrent(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("torrent"))
+}
+
+fn parse_trigger_file(path: &Path) -> Result<Vec<String>, std::io::Error> {
+    let content = read_to_string(path)?;
+    let ext = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    let links = if ext == "txt" {
+        //one link per line, blank lines and # comments are skipped
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect()
+    } else {
+        parse_metalink(&content)
+    };
+    Ok(links)
+}
+
+//every url of a file is a mirror of it, so only the preferred http one is downloaded
+fn parse_metalink(content: &str) -> Vec<String> {
+    let file_re = Regex::new(METALINK_FILE_RE).expect("Invalid metalink regex");
+    let url_re = Regex::new(METALINK_URL_RE).expect("Invalid metalink regex");
+    let attr_re = Regex::new(METALINK_ATTR_RE).expect("Invalid metalink regex");
+    let mut files = file_re
+        .captures_iter(content)
+        .map(|cap| cap.get(1).map_or("", |m| m.as_str()))
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        files.push(content);
+    }
+    files
+        .into_iter()
+        .filter_map(|file| {
+            url_re
+                .captures_iter(file)
+                .filter(|cap| {
+                    let link = cap[2].to_lowercase();
+                    link.starts_with("http://") || link.starts_with("https://")
+                })
+                //metalink 4 ranks by priority, lowest first, metalink 3 by preference, highest first
+                .min_by_key(|cap| {
+                    let mut priority = u64::MAX;
+                    let mut preference = 0;
+                    for attr in attr_re.captures_iter(&cap[1]) {
+                        let value = attr[2].parse().unwrap_or_default();
+                        match &attr[1] {
+                            "priority" => priority = value,
+                            _ => preference = value,
+                        }
+                    }
+                    (priority, std::cmp::Reverse(preference))
+                })
+                .map(|cap| cap[2].replace("&amp;", "&"))
+        })
+        .collect()
+}
+
+fn move_to_processed(path: &Path, watch_dir: &str) -> Result<(), std::io::Error> {
+    let processed = Path::new(watch_dir).join(PROCESSED_DIR);
+    create_dir_all(&processed)?;
+    let file_name = path.file_name().unwrap_or_default();
+    let mut target = processed.join(file_name);
+    if target.exists() {
+        let stamp = Local::now().format("%Y%m%d%H%M%S").to_string();
+        target = processed.join(format!("{}_{}", stamp, file_name.to_string_lossy()));
+    }
+    rename(path, target)
+}
@@ -25,6 +25,7 @@ const exts = [
 
 
 var DEBUG = 0;
+var NATIVE_HOST = 'rusty_dl_manager';
 var LOG = function () {
   if (DEBUG) {
     var args = ['download-blocker:'].concat(arguments);
@@ -84,16 +85,27 @@ async function onHeadersReceived(details) {
     }
   }
 
-  // Await isLocalHostOnline to get accurate status
-  const localHostOnline = await isLocalHostOnline();
+  const sent = sentHeaders.get(details.requestId) || {};
+  if (!redirect) {
+    sentHeaders.delete(details.requestId);
+  }
 
   // If not a redirect and the URL has a disallowed extension
-  if (!redirect && check && localHostOnline) {
+  if (!redirect && check) {
     try {
-      await browser.storage.local.set({ lastDownloadLink: url });
-      console.log("Download link saved to storage.");
-      sendJsonRequest(url);
-      res.redirectUrl = 'data:javascript,';
+      const tab = await getTabContext(details);
+      // The native host starts the app if needed and passes the cookies on,
+      // the local server is only tried without it
+      if (await sendNativeMessage(url, tab, sent)) {
+        res.redirectUrl = 'data:javascript,';
+      } else if (await isLocalHostOnline()) {
+        sendJsonRequest(url, tab.title, tab.url);
+        res.redirectUrl = 'data:javascript,';
+      }
+      if (res.redirectUrl) {
+        await browser.storage.local.set({ lastDownloadLink: url });
+        console.log("Download link saved to storage.");
+      }
     } catch (error) {
       console.error("Error saving download link:", error);
     }
@@ -110,6 +122,34 @@ async function onHeadersReceived(details) {
 
 
 
+/*
+ * Cookie, Referer and User-Agent the browser sent with each request,
+ * read back once its response turns out to be a download.
+ */
+var sentHeaders = new Map();
+
+function onBeforeSendHeaders(details) {
+  let picked = {};
+  for (const header of details.requestHeaders || []) {
+    const name = header.name.toLowerCase();
+    if (name == 'cookie' || name == 'referer' || name == 'user-agent') {
+      picked[name] = header.value;
+    }
+  }
+  sentHeaders.set(details.requestId, picked);
+}
+
+browser.webRequest.onBeforeSendHeaders.addListener(
+  onBeforeSendHeaders,
+  { urls: ['<all_urls>'], types: ['main_frame'] },
+  ['requestHeaders']
+);
+
+browser.webRequest.onErrorOccurred.addListener(
+  (details) => sentHeaders.delete(details.requestId),
+  { urls: ['<all_urls>'], types: ['main_frame'] }
+);
+
 browser.webRequest.onHeadersReceived.addListener(
   onHeadersReceived,
   { urls: ['<all_urls>'], types: ['main_frame'] },
@@ -118,26 +158,81 @@ browser.webRequest.onHeadersReceived.addListener(
 
 LOG('background script initialized')
 
-function sendJsonRequest(url) {
-  const data = { value: url };
+/*
+ * Title and URL of the page the download was started from.
+ */
+async function getTabContext(details) {
+  let context = { title: '', url: details.originUrl || details.documentUrl || '' };
+  if (details.tabId < 0) {
+    return context;
+  }
+  try {
+    const tab = await browser.tabs.get(details.tabId);
+    context.title = tab.title || '';
+    if (!context.url) {
+      context.url = tab.url || '';
+    }
+  } catch (error) {
+    LOG('Could not read tab context', error);
+  }
+  return context;
+}
+
+/*
+ * Hands the download to the app through its native messaging host
+ * (`gui install-native-host`), false when the host isn't installed.
+ */
+async function sendNativeMessage(url, tab, sent) {
+  try {
+    const reply = await browser.runtime.sendNativeMessage(NATIVE_HOST, {
+      url: url,
+      title: tab.title || '',
+      page_url: tab.url || '',
+      cookies: sent['cookie'] || '',
+      referrer: sent['referer'] || '',
+      user_agent: sent['user-agent'] || navigator.userAgent
+    });
+    if (reply && !reply.ok) {
+      console.error('Native host error:', reply.error);
+    }
+    return Boolean(reply && reply.ok);
+  } catch (error) {
+    LOG('Native host unavailable', error);
+    return false;
+  }
+}
+
+async function sendJsonRequest(url, title, pageUrl) {
+  const data = { value: url, title: title || '', page_url: pageUrl || '' };
+  const headers = { 'Content-Type': 'application/json' };
+  const stored = await browser.storage.local.get("apiToken");
+  if (stored.apiToken) {
+    headers['X-Api-Token'] = stored.apiToken;
+  }
 
-  fetch('http://127.0.0.1:3000', {
+  fetch(await serverUrl(), {
     method: 'POST',
-    headers: {
-      'Content-Type': 'application/json'
-    },
+    headers: headers,
     body: JSON.stringify(data)
   }).catch((error) => {
     console.error('Error:', error);
   });
 }
 
+/*
+ * The manager's local server, on the port set in the popup.
+ */
+async function serverUrl() {
+  const stored = await browser.storage.local.get("serverPort");
+  return 'http://127.0.0.1:' + (stored.serverPort || 3000);
+}
+
 async function isLocalHostOnline() {
   const controller = new AbortController();
   const timeoutId = setTimeout(() => controller.abort(), 3000); 
 
   try {
-    const response = await fetch('http://127.0.0.1:3000', {
+    const response = await fetch(await serverUrl(), {
       method: 'HEAD',
       mode: 'no-cors',
       signal: controller.signal
@@ -4,4 +4,18 @@ document.addEventListener("DOMContentLoaded", () => {
     const linkElement = document.getElementById("downloadLink");
     linkElement.innerText = downloadLink;
   });
+  const tokenElement = document.getElementById("apiToken");
+  browser.storage.local.get("apiToken", (data) => {
+    tokenElement.value = data.apiToken || "";
+  });
+  tokenElement.addEventListener("change", () => {
+    browser.storage.local.set({ apiToken: tokenElement.value.trim() });
+  });
+  const portElement = document.getElementById("serverPort");
+  browser.storage.local.get("serverPort", (data) => {
+    portElement.value = data.serverPort || "";
+  });
+  portElement.addEventListener("change", () => {
+    browser.storage.local.set({ serverPort: portElement.value.trim() });
+  });
 });